@@ -94,7 +94,7 @@ EOF
 build_dir: build
 content_dir: content
 template_dir: templates
-translations_csv: translate.fa.csv
+translations_dir: translations
 
 language: fa
 text_direction: rtl
@@ -106,7 +106,8 @@ context:
   site_name: "سایت من"
 EOF
 
-    cat > translate.fa.csv << 'EOF'
+    mkdir -p translations
+    cat > translations/fa.csv << 'EOF'
 Home,خانه
 About,درباره
 Blog,وبلاگ
@@ -178,6 +179,7 @@ cat > templates/layout.html << 'EOF'
   <meta charset="utf-8">
   <meta name="viewport" content="width=device-width, initial-scale=1">
   <title>{{ title | default(value=site_name) }}</title>
+  {% if canonical %}<link rel="canonical" href="{{ canonical }}">{% endif %}
   <style>
     body { font-family: system-ui, sans-serif; max-width: 720px; margin: 40px auto; padding: 0 20px; line-height: 1.6; }
     pre { background: #f4f4f4; padding: 12px; overflow: auto; }
@@ -0,0 +1,157 @@
+use clap::{Arg, Command};
+use ssg::{
+    config,
+    site::{build_site, check_links, BuildOptions, PathFilter},
+    version,
+};
+use std::path::PathBuf;
+
+// These crates are used by the `ssg` library that this binary depends on.
+// Declaring them here silences `unused_crate_dependencies` when building
+// just the ssg-build binary target.
+use ammonia as _;
+use chrono as _;
+use comrak as _;
+use image as _;
+use rayon as _;
+use regex as _;
+use serde as _;
+use serde_json as _;
+use serde_yaml as _;
+use tera as _;
+use tiny_http as _;
+use toml as _;
+use walkdir as _;
+
+struct CliArgs {
+    config_path: PathBuf,
+    include_drafts: bool,
+    force: bool,
+    check_links: bool,
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    run(parse_args()?)
+}
+
+fn parse_args() -> Result<CliArgs, Box<dyn std::error::Error>> {
+    let matches = cli_command().get_matches();
+
+    let config_path = matches
+        .get_one::<PathBuf>("config")
+        .cloned()
+        .ok_or("Missing required --config argument")?;
+
+    Ok(CliArgs {
+        config_path,
+        include_drafts: matches.get_flag("include-drafts"),
+        force: matches.get_flag("force"),
+        check_links: matches.get_flag("check-links"),
+        include: patterns(&matches, "include"),
+        exclude: patterns(&matches, "exclude"),
+    })
+}
+
+fn patterns(matches: &clap::ArgMatches, name: &str) -> Vec<String> {
+    matches
+        .get_many::<String>(name)
+        .map(|patterns| patterns.cloned().collect())
+        .unwrap_or_default()
+}
+
+fn cli_command() -> Command {
+    Command::new("ssg-build")
+        .version(version::VERSION)
+        .author("Hadi Moshayedi")
+        .about("Builds every content item in parallel")
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .help("Path to the configuration file")
+                .required(true)
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("include-drafts")
+                .long("include-drafts")
+                .help("Render content marked as draft")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("force")
+                .long("force")
+                .help("Rebuild every item, even those whose inputs did not change")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("check-links")
+                .long("check-links")
+                .help("Fail when built pages link to site paths that do not exist")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("include")
+                .long("include")
+                .help("Only build content whose path under content_dir matches this glob")
+                .value_name("GLOB")
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            Arg::new("exclude")
+                .long("exclude")
+                .help("Skip content whose path under content_dir matches this glob")
+                .value_name("GLOB")
+                .action(clap::ArgAction::Append),
+        )
+}
+
+fn run(args: CliArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let config = config::Config::load(&args.config_path)?;
+    let options = BuildOptions {
+        include_drafts: args.include_drafts,
+        force: args.force,
+        filter: PathFilter::new(&args.include, &args.exclude)?,
+    };
+    let report = build_site(&config, options)?;
+
+    println!(
+        "Built {} content item(s), {} unchanged",
+        report.built.len(),
+        report.unchanged.len()
+    );
+    if !report.skipped_drafts.is_empty() {
+        println!(
+            "Skipped {} draft(s) (pass --include-drafts to render them)",
+            report.skipped_drafts.len()
+        );
+    }
+    if !report.excluded.is_empty() {
+        println!(
+            "Left out {} item(s) not selected by --include/--exclude",
+            report.excluded.len()
+        );
+    }
+
+    for (path, err) in &report.errors {
+        eprintln!("Error building {}: {}", path.display(), err);
+    }
+
+    if !report.errors.is_empty() {
+        return Err(format!("{} content item(s) failed to build", report.errors.len()).into());
+    }
+
+    if args.check_links {
+        let broken = check_links(&config)?;
+        for link in &broken {
+            eprintln!("Broken link in {}: {}", link.page.display(), link.href);
+        }
+        if !broken.is_empty() {
+            return Err(format!("{} broken internal link(s)", broken.len()).into());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use clap::{Arg, Command};
+use ssg::{config, site::clean_build_dir, version};
+use std::path::{Path, PathBuf};
+
+// These crates are used by the `ssg` library that this binary depends on.
+// Declaring them here silences `unused_crate_dependencies` when building
+// just the ssg-clean binary target.
+use ammonia as _;
+use chrono as _;
+use comrak as _;
+use image as _;
+use rayon as _;
+use regex as _;
+use serde as _;
+use serde_json as _;
+use serde_yaml as _;
+use tera as _;
+use tiny_http as _;
+use toml as _;
+use walkdir as _;
+
+struct CliArgs {
+    config_path: PathBuf,
+    dry_run: bool,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    run(parse_args()?)
+}
+
+fn parse_args() -> Result<CliArgs, Box<dyn std::error::Error>> {
+    let matches = cli_command().get_matches();
+
+    let config_path = matches
+        .get_one::<PathBuf>("config")
+        .cloned()
+        .ok_or("Missing required --config argument")?;
+
+    Ok(CliArgs {
+        config_path,
+        dry_run: matches.get_flag("dry-run"),
+    })
+}
+
+fn cli_command() -> Command {
+    Command::new("ssg-clean")
+        .version(version::VERSION)
+        .author("Hadi Moshayedi")
+        .about("Removes the build directory")
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .help("Path to the configuration file")
+                .required(true)
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .help("List what would be removed without removing it")
+                .action(clap::ArgAction::SetTrue),
+        )
+}
+
+fn run(args: CliArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let config = config::Config::load(&args.config_path)?;
+    let project_dir = std::env::current_dir()?;
+    let source_dirs: Vec<&Path> = [&config.content_dir, &config.template_dir]
+        .into_iter()
+        .chain(&config.drafts_dir)
+        .chain(&config.translations_dir)
+        .chain(&config.data_dir)
+        .map(PathBuf::as_path)
+        .collect();
+    let removed = clean_build_dir(&config.build_dir, &project_dir, &source_dirs, args.dry_run)?;
+
+    if args.dry_run {
+        for path in &removed {
+            println!("Would remove {}", path.display());
+        }
+    } else {
+        println!(
+            "Removed {} ({} entries)",
+            config.build_dir.display(),
+            removed.len()
+        );
+    }
+
+    Ok(())
+}
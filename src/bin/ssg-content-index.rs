@@ -0,0 +1,124 @@
+use clap::{Arg, Command};
+use ssg::{
+    config,
+    site::{
+        write_content_index, write_headers_file, write_redirects_file, write_search_index,
+        write_sitemap_file,
+    },
+    version,
+};
+use std::path::PathBuf;
+
+// These crates are used by the `ssg` library that this binary depends on.
+// Declaring them here silences `unused_crate_dependencies` when building
+// just the ssg-content-index binary target.
+use ammonia as _;
+use chrono as _;
+use comrak as _;
+use image as _;
+use rayon as _;
+use regex as _;
+use serde as _;
+use serde_json as _;
+use serde_yaml as _;
+use tera as _;
+use tiny_http as _;
+use toml as _;
+use walkdir as _;
+
+struct CliArgs {
+    config_path: PathBuf,
+    headers: bool,
+    redirects: bool,
+    sitemap: bool,
+    search_index: bool,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    run(parse_args()?)
+}
+
+fn parse_args() -> Result<CliArgs, Box<dyn std::error::Error>> {
+    let matches = cli_command().get_matches();
+
+    let config_path = matches
+        .get_one::<PathBuf>("config")
+        .cloned()
+        .ok_or("Missing required --config argument")?;
+
+    Ok(CliArgs {
+        config_path,
+        headers: matches.get_flag("headers"),
+        redirects: matches.get_flag("redirects"),
+        sitemap: matches.get_flag("sitemap"),
+        search_index: matches.get_flag("search-index"),
+    })
+}
+
+fn cli_command() -> Command {
+    Command::new("ssg-content-index")
+        .version(version::VERSION)
+        .author("Hadi Moshayedi")
+        .about("Exports metadata for every content item as content-index.json")
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .help("Path to the configuration file")
+                .required(true)
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("headers")
+                .long("headers")
+                .help("Also write a _headers file with per-content Cache-Control rules")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("redirects")
+                .long("redirects")
+                .help("Also write a _redirects file from redirect_from metadata")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("sitemap")
+                .long("sitemap")
+                .help("Also write a sitemap.xml of every published page")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("search-index")
+                .long("search-index")
+                .help("Also write a search-index.json for client-side search")
+                .action(clap::ArgAction::SetTrue),
+        )
+}
+
+fn run(args: CliArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let config = config::Config::load(&args.config_path)?;
+    let output_path = write_content_index(&config)?;
+
+    println!("Wrote {}", output_path.display());
+
+    if args.headers {
+        let headers_path = write_headers_file(&config)?;
+        println!("Wrote {}", headers_path.display());
+    }
+
+    if args.redirects {
+        let redirects_path = write_redirects_file(&config)?;
+        println!("Wrote {}", redirects_path.display());
+    }
+
+    if args.sitemap {
+        let sitemap_path = write_sitemap_file(&config)?;
+        println!("Wrote {}", sitemap_path.display());
+    }
+
+    if args.search_index {
+        let search_index_path = write_search_index(&config)?;
+        println!("Wrote {}", search_index_path.display());
+    }
+
+    Ok(())
+}
@@ -1,53 +1,91 @@
 use clap::{Arg, Command};
-use ssg::{config, content::*, formatted_text::check_math_markdown, render::*, version};
+use ssg::{
+    config,
+    formatted_text::{check_math_markdown, limit_concurrent_processes},
+    logging::{self, format_duration, Logger, Verbosity},
+    render::*,
+    site::{self, run_item_with_timeout, ItemOutcome},
+    version,
+};
 use std::{
-    fs,
+    fs, io,
     path::{Path, PathBuf},
+    process::ExitCode,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
 // These crates are used by the `ssg` library crate. We re-declare them here
 // (as _) so that `cargo check` with -W unused_crate_dependencies does not
 // complain when building only this binary target.
+use ammonia as _;
 use chrono as _;
 use comrak as _;
+use image as _;
+use rayon as _;
 use regex as _;
 use serde as _;
 use serde_json as _;
 use serde_yaml as _;
 use tera as _;
+use tiny_http as _;
+use toml as _;
 use walkdir::WalkDir;
 
 struct CliArgs {
-    path: PathBuf,
+    paths: Vec<PathBuf>,
     config_path: Option<PathBuf>,
     check_math: bool,
     strict_math: bool,
+    include_drafts: bool,
+    to_stdout: bool,
+    verbosity: Verbosity,
+}
+
+/// What processing one item produced.
+enum ItemResult {
+    Outcome(ItemOutcome),
+    /// The HTML of an item rendered for `--stdout`.
+    Html(String),
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    run(parse_args()?)
+fn main() -> ExitCode {
+    // Report failures as one line on stderr instead of `main`'s Debug output.
+    match parse_args().and_then(run) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("ssg-content: {}", err);
+            ExitCode::FAILURE
+        }
+    }
 }
 
 fn parse_args() -> Result<CliArgs, Box<dyn std::error::Error>> {
     let matches = cli_command().get_matches();
 
-    let path = matches
-        .get_one::<String>("path")
+    let paths: Vec<PathBuf> = matches
+        .get_many::<String>("path")
+        .ok_or("Missing required 'path' argument")?
         .map(PathBuf::from)
-        .ok_or("Missing required 'path' argument")?;
+        .collect();
     let config_path = matches.get_one::<PathBuf>("config").cloned();
     let check_math = matches.get_flag("check-math");
     let strict_math = matches.get_flag("strict-math");
+    let include_drafts = matches.get_flag("include-drafts");
+    let verbosity = Verbosity::from_flags(matches.get_flag("quiet"), matches.get_flag("verbose"));
 
     if !check_math && config_path.is_none() {
         return Err("Missing required --config argument".into());
     }
 
     Ok(CliArgs {
-        path,
+        paths,
         config_path,
         check_math,
         strict_math,
+        include_drafts,
+        to_stdout: matches.get_flag("stdout"),
+        verbosity,
     })
 }
 
@@ -69,10 +107,39 @@ fn cli_command() -> Command {
                 .requires("check-math")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("include-drafts")
+                .long("include-drafts")
+                .help("Render content marked as draft")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("stdout")
+                .long("stdout")
+                .help("Print the rendered HTML instead of writing it to the build dir")
+                .conflicts_with("check-math")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("quiet")
+                .long("quiet")
+                .short('q')
+                .help("Only print errors")
+                .conflicts_with("verbose")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("verbose")
+                .long("verbose")
+                .short('v')
+                .help("Print progress details and timings")
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("path")
-                .help("Path to the directory to process")
+                .help("Paths of the content items to process")
                 .required(true)
+                .num_args(1..)
                 .index(1),
         )
         .arg(
@@ -85,10 +152,26 @@ fn cli_command() -> Command {
 }
 
 fn run(args: CliArgs) -> Result<(), Box<dyn std::error::Error>> {
+    logging::set_library_verbosity(args.verbosity);
+    // With --stdout, stdout carries only the HTML.
+    let logger = if args.to_stdout {
+        Logger::with_writers(
+            args.verbosity,
+            Box::new(io::stderr()),
+            Box::new(io::stderr()),
+        )
+    } else {
+        Logger::new(args.verbosity)
+    };
     if args.check_math {
         let default_math_shorthand = load_optional_config(args.config_path.as_deref())?
             .is_some_and(|config| config.math_shorthand);
-        return check_math_path(&args.path, default_math_shorthand, args.strict_math);
+        return check_math_paths(
+            &args.paths,
+            default_math_shorthand,
+            args.strict_math,
+            &logger,
+        );
     }
 
     let config_path = args
@@ -96,16 +179,79 @@ fn run(args: CliArgs) -> Result<(), Box<dyn std::error::Error>> {
         .as_deref()
         .ok_or("Missing required --config argument")?;
     let config = config::Config::load(config_path)?;
+    logger.debug(format_args!("Loaded config from {}", config_path.display()));
+    limit_concurrent_processes(config.max_concurrency());
 
-    fs::create_dir_all(&config.build_dir)?;
+    if !args.to_stdout {
+        fs::create_dir_all(&config.build_dir)?;
+    }
 
-    let content = load_content(&args.path, &config)?;
-    let renderer = Renderer::new(&config)?;
-    let html = render_with_images(&args.path, &content, &renderer, &config)?;
+    let timeout = Duration::from_secs(config.render_timeout_seconds);
+    let include_drafts = args.include_drafts;
+    let to_stdout = args.to_stdout;
+    let renderer = Arc::new(Renderer::new(&config)?);
+    let config = Arc::new(config);
 
-    write_content_output(&content, html)?;
+    // Keep going past broken items so that one run reports all of them.
+    let mut errors = Vec::new();
+    for path in args.paths {
+        let item = path.display().to_string();
+        let renderer = Arc::clone(&renderer);
+        let item_config = Arc::clone(&config);
+        let start = Instant::now();
+        let outcome = run_item_with_timeout(&item, timeout, move || {
+            process_item(&path, &renderer, &item_config, include_drafts, to_stdout)
+                .map_err(|e| e.to_string())
+        });
 
-    Ok(())
+        match outcome {
+            Ok(ItemResult::Html(html)) => {
+                logger.debug(format_args!(
+                    "Rendered {item} in {}",
+                    format_duration(start.elapsed())
+                ));
+                print!("{html}");
+            }
+            Ok(ItemResult::Outcome(ItemOutcome::Built(output))) => {
+                logger.debug(format_args!(
+                    "Rendered {item} in {}",
+                    format_duration(start.elapsed())
+                ));
+                logger.info(format_args!("Built {item} -> {}", output.display()));
+            }
+            Ok(ItemResult::Outcome(ItemOutcome::SkippedDraft)) => logger.info(format_args!(
+                "Skipping draft {item} (pass --include-drafts to render it)"
+            )),
+            Err(err) => errors.push((item, err)),
+        }
+    }
+
+    for (item, err) in &errors {
+        logger.error(format_args!("Error building {item}: {err}"));
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("{} content item(s) failed to build", errors.len()).into())
+    }
+}
+
+fn process_item(
+    path: &Path,
+    renderer: &Renderer,
+    config: &config::Config,
+    include_drafts: bool,
+    to_stdout: bool,
+) -> Result<ItemResult, Box<dyn std::error::Error>> {
+    if !to_stdout {
+        return site::build_item(path, renderer, config, include_drafts).map(ItemResult::Outcome);
+    }
+    Ok(
+        match site::render_item(path, renderer, config, include_drafts)? {
+            Some(html) => ItemResult::Html(html),
+            None => ItemResult::Outcome(ItemOutcome::SkippedDraft),
+        },
+    )
 }
 
 fn load_optional_config(
@@ -114,33 +260,45 @@ fn load_optional_config(
     config_path.map(config::Config::load).transpose()
 }
 
-fn check_math_path(
-    path: &Path,
+fn check_math_paths(
+    paths: &[PathBuf],
     default_math_shorthand: bool,
     strict: bool,
+    logger: &Logger,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let files = markdown_files(path)?;
+    let mut files = Vec::new();
+    for path in paths {
+        files.extend(markdown_files(path)?);
+    }
+    let file_count = files.len();
     let mut error_count = 0;
 
     for file in files {
         let markdown = fs::read_to_string(&file)?;
-        for diagnostic in check_math_markdown(&markdown, default_math_shorthand, strict) {
-            if diagnostic.severity.as_str() == "error" {
-                error_count += 1;
-            }
-            eprintln!(
+        let diagnostics = logger.timed(format_args!("Checking {}", file.display()), || {
+            check_math_markdown(&markdown, default_math_shorthand, strict)
+        });
+        for diagnostic in diagnostics {
+            let message = format!(
                 "{}:{}: {}: {}",
                 file.display(),
                 diagnostic.line,
                 diagnostic.severity.as_str(),
                 diagnostic.message
             );
+            if diagnostic.severity.as_str() == "error" {
+                error_count += 1;
+                logger.error(message);
+            } else {
+                logger.warn(message);
+            }
         }
     }
 
     if error_count > 0 {
         Err(format!("{error_count} math check error(s)").into())
     } else {
+        logger.info(format_args!("Checked math in {file_count} file(s)"));
         Ok(())
     }
 }
@@ -166,44 +324,3 @@ fn markdown_files(path: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error
 
     Ok(files)
 }
-
-fn load_content(
-    path: &Path,
-    config: &config::Config,
-) -> Result<Content, Box<dyn std::error::Error>> {
-    Content::load(path, config)
-        .map_err(|e| format!("Failed to load content from {}: {e}", path.display()).into())
-}
-
-fn render_with_images(
-    path: &Path,
-    content: &Content,
-    renderer: &Renderer,
-    config: &config::Config,
-) -> Result<String, Box<dyn std::error::Error>> {
-    let mut html = content.render_html(renderer, config)?;
-    let mut image_processor = ImageProcessor::new(
-        path.to_path_buf(),
-        config.content_dir.clone(),
-        config.build_dir.clone(),
-    )?;
-
-    if image_processor.has_images() {
-        image_processor.copy_images_to_build_dir()?;
-        html = image_processor.update_html_with_image_urls(&html);
-    }
-
-    Ok(html)
-}
-
-fn write_content_output(content: &Content, html: String) -> Result<(), Box<dyn std::error::Error>> {
-    let output_file_path = &content.metadata().output_path;
-
-    if let Some(parent) = output_file_path.parent() {
-        fs::create_dir_all(parent)?;
-    }
-
-    fs::write(output_file_path, html)?;
-
-    Ok(())
-}
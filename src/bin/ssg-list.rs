@@ -1,25 +1,37 @@
 use clap::{Arg, Command};
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
-use ssg::{config, content::*, render::*, version};
+use ssg::{
+    config,
+    content::*,
+    logging::{self, Logger, Verbosity},
+    render::*,
+    site::{
+        filter_by_tags, write_date_pages, write_feed, write_list_pages, write_tag_pages, GroupBy,
+        IndexConfig,
+    },
+    version,
+};
 use std::{
-    collections::HashMap,
     fs,
     path::{Path, PathBuf},
+    process::ExitCode,
 };
-use walkdir::WalkDir;
 
 // These crates are used by the `ssg` library that this binary depends on.
 // Declaring them here silences `unused_crate_dependencies` when building
 // just the ssg-list binary target.
+use ammonia as _;
 use chrono as _;
 use comrak as _;
+use image as _;
+use rayon as _;
 use regex as _;
+use serde as _;
+use serde_json as _;
+use serde_yaml as _;
 use tera as _;
-
-fn default_template() -> String {
-    "list.html".to_string()
-}
+use tiny_http as _;
+use toml as _;
+use walkdir as _;
 
 fn absolute_path(path: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
     if path.is_absolute() {
@@ -29,23 +41,29 @@ fn absolute_path(path: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-struct IndexConfig {
-    title: Option<String>,
-    #[serde(rename = "content-type")]
-    content_type: ContentKind,
-    path: Option<String>,
-    #[serde(default = "default_template")]
-    template: String,
-}
-
 struct CliArgs {
     index_yaml_path: PathBuf,
     config_path: PathBuf,
+    verbosity: Verbosity,
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    run(parse_args()?)
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("ssg-list: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    logging::set_library_verbosity(args.verbosity);
+    let logger = Logger::new(args.verbosity);
+    match run(args, &logger) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            logger.error(format_args!("ssg-list: {}", err));
+            ExitCode::FAILURE
+        }
+    }
 }
 
 fn parse_args() -> Result<CliArgs, Box<dyn std::error::Error>> {
@@ -63,6 +81,7 @@ fn parse_args() -> Result<CliArgs, Box<dyn std::error::Error>> {
     Ok(CliArgs {
         index_yaml_path,
         config_path,
+        verbosity: Verbosity::from_flags(matches.get_flag("quiet"), matches.get_flag("verbose")),
     })
 }
 
@@ -85,44 +104,92 @@ fn cli_command() -> Command {
                 .value_name("FILE")
                 .value_parser(clap::value_parser!(PathBuf)),
         )
+        .arg(
+            Arg::new("quiet")
+                .long("quiet")
+                .short('q')
+                .help("Only print errors")
+                .conflicts_with("verbose")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("verbose")
+                .long("verbose")
+                .short('v')
+                .help("Print progress details and timings")
+                .action(clap::ArgAction::SetTrue),
+        )
 }
 
-fn run(args: CliArgs) -> Result<(), Box<dyn std::error::Error>> {
+fn run(args: CliArgs, logger: &Logger) -> Result<(), Box<dyn std::error::Error>> {
     let config = config::Config::load(&args.config_path)?;
 
     fs::create_dir_all(&config.build_dir)?;
 
-    println!(
+    logger.debug(format_args!(
         "Loading index_config from: {}",
         args.index_yaml_path.display()
-    );
-    println!("Build directory: {}", config.build_dir.display());
+    ));
+    logger.debug(format_args!(
+        "Build directory: {}",
+        config.build_dir.display()
+    ));
 
-    let index_config = load_index_config(&args.index_yaml_path)?;
+    let index_config = IndexConfig::load(&args.index_yaml_path)?;
     let renderer = Renderer::new(&config)?;
     let output_base_dir = output_base_dir(&args.index_yaml_path, &config)?;
 
-    println!("Base content path: {}", output_base_dir.display());
+    logger.debug(format_args!(
+        "Base content path: {}",
+        output_base_dir.display()
+    ));
 
     let search_path = search_path(&args.index_yaml_path, &index_config)?;
-    let mut content_items = find_content_files(&search_path, index_config.content_type, &config)?;
-    sort_content_items(&mut content_items);
+    let content_items = logger.timed("Loading content items", || {
+        find_content_files(&search_path, Some(index_config.content_type), &config)
+    })?;
+    let content_items = filter_by_tags(content_items, &index_config.tags);
+    let mut content_items = order_content_items(&search_path, content_items, index_config.sort_by)?;
+    logger.timed("Deriving excerpts", || {
+        for item in &mut content_items {
+            fill_excerpt(item, &config);
+        }
+    });
+
+    logger.debug(format_args!("Found {} content items", content_items.len()));
 
-    println!("Found {} content items", content_items.len());
+    let write_pages = match index_config.group_by {
+        Some(GroupBy::Tag) => write_tag_pages,
+        Some(GroupBy::Date) => write_date_pages,
+        None => write_list_pages,
+    };
+    let written = logger.timed("Writing list pages", || {
+        write_pages(
+            &renderer,
+            &index_config,
+            &content_items,
+            &output_base_dir,
+            &config,
+        )
+    })?;
+    for page in &written {
+        logger.debug(format_args!("Wrote {}", page.display()));
+    }
 
-    fs::create_dir_all(&output_base_dir)?;
-    let html = render_list(&renderer, &index_config, &content_items)?;
-    fs::write(output_base_dir.join("index.html"), html)?;
+    let mut summary = format!(
+        "Wrote {} list page(s) for {} content item(s)",
+        written.len(),
+        content_items.len()
+    );
+    if index_config.feed {
+        let feed_path = write_feed(&index_config, &content_items, &output_base_dir, &config)?;
+        summary.push_str(&format!(" and {}", feed_path.display()));
+    }
 
-    println!("List generation completed successfully!");
+    logger.info(summary);
     Ok(())
 }
 
-fn load_index_config(path: &Path) -> Result<IndexConfig, Box<dyn std::error::Error>> {
-    let config_content = fs::read_to_string(path)?;
-    Ok(serde_yaml::from_str(&config_content)?)
-}
-
 fn output_base_dir(
     index_yaml_path: &Path,
     config: &config::Config,
@@ -158,181 +225,3 @@ fn search_path(
         .as_ref()
         .map_or_else(|| parent_dir.to_owned(), |path| parent_dir.join(path)))
 }
-
-fn sort_content_items(content_items: &mut [ContentMetadata]) {
-    content_items.sort_by(|a, b| match (&a.timestamp, &b.timestamp) {
-        (Some(a_date), Some(b_date)) => b_date.cmp(a_date),
-        _ => a.title.cmp(&b.title),
-    });
-}
-
-fn render_list(
-    renderer: &Renderer,
-    index_config: &IndexConfig,
-    content_items: &[ContentMetadata],
-) -> Result<String, Box<dyn std::error::Error>> {
-    let mut context = HashMap::new();
-    if let Some(title) = &index_config.title {
-        context.insert("title".to_string(), Value::String(title.clone()));
-    }
-
-    context.insert(
-        "content_items".to_string(),
-        Value::Array(serializable_content_items(content_items)),
-    );
-
-    renderer.render(&index_config.template, context)
-}
-
-fn serializable_content_items(content_items: &[ContentMetadata]) -> Vec<Value> {
-    content_items
-        .iter()
-        .map(|item| serde_json::to_value(item).unwrap())
-        .collect()
-}
-
-fn find_content_files(
-    base_path: &Path,
-    content_type: ContentKind,
-    config: &config::Config,
-) -> Result<Vec<ContentMetadata>, Box<dyn std::error::Error>> {
-    let mut content_items = Vec::new();
-
-    for entry in WalkDir::new(base_path).into_iter().filter_map(|e| e.ok()) {
-        let path = entry.path();
-
-        if path.is_dir() {
-            continue;
-        }
-
-        if path.file_name() == Some("metadata.yaml".as_ref()) {
-            load_directory_metadata(path, content_type, config, &mut content_items);
-            continue;
-        }
-
-        if content_type == ContentKind::Page && is_bare_content_file(path) {
-            if has_directory_metadata(path) {
-                continue;
-            }
-            load_bare_page_metadata(path, config, &mut content_items);
-        }
-    }
-
-    Ok(content_items)
-}
-
-fn load_directory_metadata(
-    metadata_path: &Path,
-    content_type: ContentKind,
-    config: &config::Config,
-    content_items: &mut Vec<ContentMetadata>,
-) {
-    let Some(dir) = metadata_path.parent() else {
-        println!(
-            "Warning: Failed to load metadata from {}: metadata.yaml has no parent directory",
-            metadata_path.display()
-        );
-        return;
-    };
-
-    match ContentMetadata::load(dir, config) {
-        Ok(metadata) => {
-            if metadata.kind == content_type {
-                content_items.push(metadata);
-            }
-        }
-        Err(err) => {
-            println!(
-                "Warning: Failed to load metadata from {}: {}",
-                metadata_path.display(),
-                err
-            );
-        }
-    }
-}
-
-fn has_directory_metadata(path: &Path) -> bool {
-    path.parent()
-        .map(|parent| parent.join("metadata.yaml").exists())
-        .unwrap_or(false)
-}
-
-fn load_bare_page_metadata(
-    path: &Path,
-    config: &config::Config,
-    content_items: &mut Vec<ContentMetadata>,
-) {
-    match Content::load(path, config) {
-        Ok(Content::Page { metadata, .. }) => content_items.push(metadata),
-        Ok(_) => {}
-        Err(err) => {
-            println!(
-                "Warning: Failed to load bare page from {}: {}",
-                path.display(),
-                err
-            );
-        }
-    }
-}
-
-fn is_bare_content_file(path: &Path) -> bool {
-    matches!(
-        path.extension().and_then(|ext| ext.to_str()),
-        Some("md" | "html" | "tex")
-    )
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
-    use tempfile::tempdir;
-
-    #[test]
-    fn find_content_files_includes_bare_pages() -> Result<(), Box<dyn std::error::Error>> {
-        let temp_dir = tempdir()?;
-        let content_dir = temp_dir.path().join("content");
-        let build_dir = temp_dir.path().join("build");
-        fs::create_dir_all(&content_dir)?;
-        fs::write(content_dir.join("about.md"), "# About\n\nBody")?;
-
-        let config = config::Config {
-            content_dir: content_dir.clone(),
-            build_dir,
-            ..Default::default()
-        };
-
-        let items = find_content_files(&content_dir, ContentKind::Page, &config)?;
-
-        assert_eq!(items.len(), 1);
-        assert_eq!(items[0].title, "About");
-        assert!(items[0].url.ends_with("/about.html"));
-
-        Ok(())
-    }
-
-    #[test]
-    fn find_content_files_skips_bare_body_in_metadata_directory(
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let temp_dir = tempdir()?;
-        let content_dir = temp_dir.path().join("content");
-        let build_dir = temp_dir.path().join("build");
-        let page_dir = content_dir.join("page");
-        fs::create_dir_all(&page_dir)?;
-        fs::write(page_dir.join("metadata.yaml"), "title: Page\ntype: page\n")?;
-        fs::write(page_dir.join("body.md"), "# Body\n")?;
-
-        let config = config::Config {
-            content_dir: content_dir.clone(),
-            build_dir,
-            ..Default::default()
-        };
-
-        let items = find_content_files(&content_dir, ContentKind::Page, &config)?;
-
-        assert_eq!(items.len(), 1);
-        assert_eq!(items[0].title, "Page");
-
-        Ok(())
-    }
-}
@@ -0,0 +1,91 @@
+use clap::{Arg, Command};
+use ssg::{config, site::export_pdf, version};
+use std::path::PathBuf;
+
+// These crates are used by the `ssg` library that this binary depends on.
+// Declaring them here silences `unused_crate_dependencies` when building
+// just the ssg-pdf binary target.
+use ammonia as _;
+use chrono as _;
+use comrak as _;
+use image as _;
+use rayon as _;
+use regex as _;
+use serde as _;
+use serde_json as _;
+use serde_yaml as _;
+use tera as _;
+use tiny_http as _;
+use toml as _;
+use walkdir as _;
+
+struct CliArgs {
+    content_path: PathBuf,
+    output_path: PathBuf,
+    config_path: PathBuf,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    run(parse_args()?)
+}
+
+fn parse_args() -> Result<CliArgs, Box<dyn std::error::Error>> {
+    let matches = cli_command().get_matches();
+
+    let content_path = matches
+        .get_one::<PathBuf>("content")
+        .cloned()
+        .ok_or("Missing content path")?;
+    let output_path = matches
+        .get_one::<PathBuf>("output")
+        .cloned()
+        .ok_or("Missing required --output argument")?;
+    let config_path = matches
+        .get_one::<PathBuf>("config")
+        .cloned()
+        .ok_or("Missing required --config argument")?;
+
+    Ok(CliArgs {
+        content_path,
+        output_path,
+        config_path,
+    })
+}
+
+fn cli_command() -> Command {
+    Command::new("ssg-pdf")
+        .version(version::VERSION)
+        .author("Hadi Moshayedi")
+        .about("Exports a problem or page as a printable PDF with pandoc")
+        .arg(
+            Arg::new("content")
+                .help("Content directory or file to export")
+                .required(true)
+                .index(1)
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .help("Path of the PDF to write")
+                .required(true)
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .help("Path to the configuration file")
+                .required(true)
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+}
+
+fn run(args: CliArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let config = config::Config::load(&args.config_path)?;
+    export_pdf(&args.content_path, &args.output_path, &config)?;
+    println!("Wrote {}", args.output_path.display());
+    Ok(())
+}
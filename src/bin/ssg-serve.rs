@@ -0,0 +1,105 @@
+use clap::{Arg, Command};
+use ssg::{
+    config,
+    site::{DevServer, ServeOptions},
+    version,
+};
+use std::path::PathBuf;
+
+// These crates are used by the `ssg` library that this binary depends on.
+// Declaring them here silences `unused_crate_dependencies` when building
+// just the ssg-serve binary target.
+use ammonia as _;
+use chrono as _;
+use comrak as _;
+use image as _;
+use rayon as _;
+use regex as _;
+use serde as _;
+use serde_json as _;
+use serde_yaml as _;
+use tera as _;
+use tiny_http as _;
+use toml as _;
+use walkdir as _;
+
+struct CliArgs {
+    config_path: PathBuf,
+    options: ServeOptions,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    run(parse_args()?)
+}
+
+fn parse_args() -> Result<CliArgs, Box<dyn std::error::Error>> {
+    let matches = cli_command().get_matches();
+
+    let config_path = matches
+        .get_one::<PathBuf>("config")
+        .cloned()
+        .ok_or("Missing required --config argument")?;
+    let defaults = ServeOptions::default();
+    let options = ServeOptions {
+        port: matches
+            .get_one::<u16>("port")
+            .copied()
+            .unwrap_or(defaults.port),
+        not_found_page: matches
+            .get_one::<PathBuf>("not-found")
+            .cloned()
+            .unwrap_or(defaults.not_found_page),
+        live_reload: !matches.get_flag("no-live-reload"),
+    };
+
+    Ok(CliArgs {
+        config_path,
+        options,
+    })
+}
+
+fn cli_command() -> Command {
+    Command::new("ssg-serve")
+        .version(version::VERSION)
+        .author("Hadi Moshayedi")
+        .about("Serves the build directory for local preview")
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .help("Path to the configuration file")
+                .required(true)
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("port")
+                .long("port")
+                .help("Port to listen on (default 8000)")
+                .value_name("PORT")
+                .value_parser(clap::value_parser!(u16)),
+        )
+        .arg(
+            Arg::new("not-found")
+                .long("not-found")
+                .help("Page served for missing paths, relative to the build directory (default 404.html)")
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("no-live-reload")
+                .long("no-live-reload")
+                .help("Do not inject the live-reload script into pages")
+                .action(clap::ArgAction::SetTrue),
+        )
+}
+
+fn run(args: CliArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let config = config::Config::load(&args.config_path)?;
+    let server = DevServer::bind(&config, args.options)?;
+
+    if let Some(addr) = server.local_addr() {
+        println!("Serving {} at http://{}/", config.build_dir.display(), addr);
+    }
+    server.run();
+    Ok(())
+}
@@ -1,11 +1,12 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
 use std::{
     collections::HashMap,
     path::{Path, PathBuf},
 };
 
-use crate::formatted_text::Theorem;
+use crate::content::TrailingSlash;
+use crate::formatted_text::{MarkdownOptions, MathRenderer, Theorem};
 
 fn default_language() -> String {
     "en".to_string()
@@ -32,10 +33,69 @@ fn default_syntax_highlighter_theme() -> String {
     "base16-ocean.dark".to_string()
 }
 
+fn default_output_extension() -> String {
+    "html".to_string()
+}
+
+fn default_verbatim_environments() -> Vec<String> {
+    vec!["verbatim".to_string(), "lstlisting".to_string()]
+}
+
+fn default_pandoc_bin() -> String {
+    "pandoc".to_string()
+}
+
 fn default_pandoc_timeout_seconds() -> u64 {
     10
 }
 
+fn default_render_timeout_seconds() -> u64 {
+    300
+}
+
+fn default_words_per_minute() -> usize {
+    200
+}
+
+fn default_related_items() -> usize {
+    5
+}
+
+fn default_search_text_length() -> usize {
+    5000
+}
+
+fn default_redirect_status() -> u16 {
+    301
+}
+
+fn default_lazy_images() -> bool {
+    true
+}
+
+fn default_allow_raw_html() -> bool {
+    true
+}
+
+fn default_not_found_template() -> String {
+    "404.html".to_string()
+}
+
+fn default_image_extensions() -> Vec<String> {
+    ["jpg", "jpeg", "png", "gif", "bmp", "tiff", "svg"]
+        .iter()
+        .map(|ext| ext.to_string())
+        .collect()
+}
+
+fn default_assets_dir() -> PathBuf {
+    PathBuf::from("static/assets")
+}
+
+fn default_asset_extensions() -> Vec<String> {
+    vec!["pdf".to_string(), "zip".to_string(), "csv".to_string()]
+}
+
 fn default_geomdsl_python() -> String {
     "python3".to_string()
 }
@@ -44,12 +104,39 @@ fn default_geomdsl_timeout_seconds() -> u64 {
     15
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct Config {
     pub build_dir: PathBuf,
+    /// Absolute URL the site is served from, e.g. `https://example.com`.
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// Whether absolute urls made with `base_url` end with a `/`.
+    #[serde(default)]
+    pub trailing_slash: TrailingSlash,
     pub content_dir: PathBuf,
+    /// Tree of unpublished content, laid out like `content_dir`.
+    #[serde(default)]
+    pub drafts_dir: Option<PathBuf>,
     pub template_dir: PathBuf,
-    pub translations_csv: Option<PathBuf>,
+    /// Template rendered to `404.html` by a site build, if it exists.
+    #[serde(default = "default_not_found_template")]
+    pub not_found_template: String,
+    /// Directory of `<language>.csv` translation files.
+    #[serde(default)]
+    pub translations_dir: Option<PathBuf>,
+    /// Directory of YAML/JSON files, each available to templates under its
+    /// file stem.
+    #[serde(default)]
+    pub data_dir: Option<PathBuf>,
+    /// Language whose translations fill keys missing from `language`.
+    #[serde(default)]
+    pub fallback_language: Option<String>,
+
+    #[serde(default = "default_output_extension")]
+    pub output_extension: String,
+    /// Write pages as `<name>/index.html`, with urls ending in `<name>/`.
+    #[serde(default)]
+    pub clean_urls: bool,
 
     #[serde(default = "default_syntax_highlighter_theme")]
     pub syntax_highlighter_theme: String,
@@ -65,15 +152,112 @@ pub struct Config {
     #[serde(default)]
     pub theorems: Vec<Theorem>,
 
+    #[serde(default = "default_verbatim_environments")]
+    pub verbatim_environments: Vec<String>,
+
     #[serde(default = "default_escape_markdown_in_math")]
     pub escape_markdown_in_math: bool,
 
     #[serde(default = "default_math_shorthand")]
     pub math_shorthand: bool,
 
+    /// Render `x^2^` and `H~2~O` as superscript and subscript in Markdown.
+    #[serde(default)]
+    pub markdown_sub_superscript: bool,
+
+    /// Comrak extensions to turn on or off, over the defaults.
+    #[serde(default)]
+    pub markdown: MarkdownOptions,
+
+    /// Remove HTML comments such as `<!-- TODO -->` from rendered pages.
+    #[serde(default)]
+    pub strip_comments: bool,
+
+    /// Start every generated file with `<!-- generated by ssg vX -->`.
+    #[serde(default)]
+    pub generated_by_comment: bool,
+
+    #[serde(default)]
+    pub math_renderer: MathRenderer,
+
+    /// The pandoc executable, e.g. a full path when it is not on `PATH`.
+    #[serde(default = "default_pandoc_bin")]
+    pub pandoc_bin: String,
+
     #[serde(default = "default_pandoc_timeout_seconds")]
     pub pandoc_timeout_seconds: u64,
 
+    /// Pandoc processes kept started and waiting for input; 0 starts one
+    /// per conversion. Waiting processes count against `max_concurrency`.
+    #[serde(default)]
+    pub pandoc_pool_size: usize,
+
+    /// Rendered texts kept in memory for reuse within a build; 0 disables
+    /// the cache.
+    #[serde(default)]
+    pub render_cache_size: usize,
+
+    #[serde(default = "default_render_timeout_seconds")]
+    pub render_timeout_seconds: u64,
+
+    #[serde(default)]
+    pub max_concurrency: Option<usize>,
+
+    /// Reading speed used for the `reading_time` of blog posts and pages.
+    #[serde(default = "default_words_per_minute")]
+    pub words_per_minute: usize,
+    /// Characters of body text kept per item in `search-index.json`.
+    #[serde(default = "default_search_text_length")]
+    pub search_text_length: usize,
+    /// HTTP status of the rules written to `_redirects`.
+    #[serde(default = "default_redirect_status")]
+    pub redirect_status: u16,
+
+    /// Image url used in place of a content `image` that does not exist.
+    #[serde(default)]
+    pub fallback_image: Option<String>,
+
+    /// Also write a lossless `.webp` copy of PNG images (`photo.png.webp`)
+    /// and point `<img>` tags at it. JPEGs are kept as they are.
+    #[serde(default)]
+    pub convert_webp: bool,
+    /// Add `loading="lazy"` and `decoding="async"` to rewritten `<img>` tags.
+    #[serde(default = "default_lazy_images")]
+    pub lazy_images: bool,
+    /// Keep raw HTML written in Markdown as is. When off, rendered Markdown
+    /// is cleaned with an allow-list of elements, attributes and url schemes.
+    #[serde(default = "default_allow_raw_html")]
+    pub allow_raw_html: bool,
+    /// Extensions of the files treated as content images.
+    #[serde(default = "default_image_extensions")]
+    pub image_extensions: Vec<String>,
+    /// Directory in the build dir that content images and assets are copied
+    /// to.
+    #[serde(default = "default_assets_dir")]
+    pub assets_dir: PathBuf,
+    /// Extensions of non-image files copied next to the images, with
+    /// `<a href>` links to them rewritten.
+    #[serde(default = "default_asset_extensions")]
+    pub asset_extensions: Vec<String>,
+    /// Fail a content item whose `<img>` tags reference missing local files,
+    /// instead of warning.
+    #[serde(default)]
+    pub strict_images: bool,
+
+    /// Number of items sharing tags with a page listed as its `related`.
+    #[serde(default = "default_related_items")]
+    pub related_items: usize,
+
+    /// Enter symlinked directories when discovering content, images and
+    /// assets.
+    #[serde(default)]
+    pub follow_symlinks: bool,
+
+    /// Fail builds when several content items share an `id`, instead of
+    /// warning.
+    #[serde(default)]
+    pub strict_ids: bool,
+
     pub geomdsl_dir: Option<PathBuf>,
 
     #[serde(default = "default_geomdsl_python")]
@@ -90,17 +274,50 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             build_dir: PathBuf::new(),
+            base_url: None,
+            trailing_slash: TrailingSlash::default(),
             content_dir: PathBuf::new(),
+            drafts_dir: None,
             template_dir: PathBuf::new(),
-            translations_csv: None,
+            not_found_template: default_not_found_template(),
+            translations_dir: None,
+            data_dir: None,
+            fallback_language: None,
+            output_extension: default_output_extension(),
+            clean_urls: false,
             syntax_highlighter_theme: default_syntax_highlighter_theme(),
             language: default_language(),
             text_direction: default_text_direction(),
             context: None,
             theorems: Vec::new(),
+            verbatim_environments: default_verbatim_environments(),
             escape_markdown_in_math: default_escape_markdown_in_math(),
             math_shorthand: default_math_shorthand(),
+            markdown_sub_superscript: false,
+            markdown: MarkdownOptions::default(),
+            strip_comments: false,
+            generated_by_comment: false,
+            math_renderer: MathRenderer::default(),
+            pandoc_bin: default_pandoc_bin(),
             pandoc_timeout_seconds: default_pandoc_timeout_seconds(),
+            pandoc_pool_size: 0,
+            render_cache_size: 0,
+            render_timeout_seconds: default_render_timeout_seconds(),
+            max_concurrency: None,
+            words_per_minute: default_words_per_minute(),
+            search_text_length: default_search_text_length(),
+            redirect_status: default_redirect_status(),
+            fallback_image: None,
+            convert_webp: false,
+            lazy_images: default_lazy_images(),
+            allow_raw_html: default_allow_raw_html(),
+            image_extensions: default_image_extensions(),
+            assets_dir: default_assets_dir(),
+            asset_extensions: default_asset_extensions(),
+            strict_images: false,
+            related_items: default_related_items(),
+            follow_symlinks: false,
+            strict_ids: false,
             geomdsl_dir: None,
             geomdsl_python: default_geomdsl_python(),
             geomdsl_timeout_seconds: default_geomdsl_timeout_seconds(),
@@ -110,12 +327,155 @@ impl Default for Config {
 }
 
 impl Config {
+    /// Load a YAML, TOML or JSON config, chosen by the file extension.
+    ///
+    /// A config may name a base config in `extends`, resolved against its own
+    /// directory; its settings are applied on top of the base's. `${VAR}` and
+    /// `${VAR:-default}` in string values are replaced from the environment.
     pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
-        let config_str = std::fs::read_to_string(path)?;
-        let config: Config = serde_yaml::from_str(&config_str)?;
+        let mut settings = load_settings(path, &mut Vec::new())?;
+        interpolate_settings(&mut settings, &|name| std::env::var(name).ok())
+            .map_err(|e| format!("Invalid config {}: {}", path.display(), e))?;
+        if settings.get("translations_csv").is_some() {
+            return Err(format!(
+                "Invalid config {}: `translations_csv` was replaced by `translations_dir`; \
+                 move the file to <translations_dir>/<language>.csv",
+                path.display()
+            )
+            .into());
+        }
+        serde_json::from_value(settings)
+            .map_err(|e| format!("Invalid config {}: {}", path.display(), e).into())
+    }
+
+    /// Maximum number of content items or external processes to run at
+    /// once, defaulting to the number of available CPUs.
+    pub fn max_concurrency(&self) -> usize {
+        self.max_concurrency
+            .unwrap_or_else(|| {
+                std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+            })
+            .max(1)
+    }
+}
+
+/// The settings of the config at `path` merged over those of the configs
+/// it extends. `chain` holds the configs already being loaded.
+fn load_settings(
+    path: &Path,
+    chain: &mut Vec<PathBuf>,
+) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| format!("Cannot read config {}: {}", path.display(), e))?;
+    if chain.contains(&canonical) {
+        return Err(format!("Config {} extends itself", path.display()).into());
+    }
+    chain.push(canonical);
+
+    let mut settings = parse_config_file(path)?;
+    let extends = settings
+        .as_object_mut()
+        .and_then(|settings| settings.remove("extends"));
+    match extends {
+        Some(serde_json::Value::String(base)) => {
+            let base_path = path.parent().unwrap_or(Path::new("")).join(base);
+            let mut base_settings = load_settings(&base_path, chain)?;
+            merge_settings(&mut base_settings, settings);
+            Ok(base_settings)
+        }
+        Some(_) => Err(format!("`extends` in {} must be a path", path.display()).into()),
+        None => Ok(settings),
+    }
+}
+
+fn parse_config_file(path: &Path) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    let config_str = std::fs::read_to_string(path)?;
+    let settings = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("yaml" | "yml") => serde_yaml::from_str(&config_str)?,
+        Some("toml") => toml::from_str(&config_str)?,
+        Some("json") => serde_json::from_str(&config_str)?,
+        _ => {
+            return Err(format!(
+                "Unsupported config file {}: expected .yaml, .toml or .json",
+                path.display()
+            )
+            .into())
+        }
+    };
+    Ok(settings)
+}
+
+/// Overlay `overrides` on `base`; maps are merged key by key, anything else
+/// is replaced.
+fn merge_settings(base: &mut serde_json::Value, overrides: serde_json::Value) {
+    match (base, overrides) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_settings(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
 
-        Ok(config)
+/// Interpolate environment variables into every string of `settings`,
+/// however deeply nested. Values are inserted raw: a setting rendered into
+/// HTML, such as a `context` entry, gets the variable unescaped.
+fn interpolate_settings(
+    settings: &mut serde_json::Value,
+    lookup: &dyn Fn(&str) -> Option<String>,
+) -> Result<(), String> {
+    match settings {
+        serde_json::Value::String(text) => *text = interpolate_env(text, lookup)?,
+        serde_json::Value::Array(items) => {
+            for item in items {
+                interpolate_settings(item, lookup)?;
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for value in map.values_mut() {
+                interpolate_settings(value, lookup)?;
+            }
+        }
+        _ => {}
     }
+    Ok(())
+}
+
+/// Replace each `${NAME}` in `text` with `lookup(NAME)`, and `${NAME:-default}`
+/// with `default` when `NAME` is unset. An unset variable without a default
+/// is an error.
+fn interpolate_env(text: &str, lookup: &dyn Fn(&str) -> Option<String>) -> Result<String, String> {
+    let mut result = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| format!("Unclosed `${{` in {text:?}"))?;
+        let expression = &after[..end];
+        let (name, default) = match expression.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (expression, None),
+        };
+        match (lookup(name), default) {
+            (Some(value), _) => result.push_str(&value),
+            (None, Some(default)) => result.push_str(default),
+            (None, None) => return Err(format!("Environment variable {name} is not set")),
+        }
+        rest = &after[end + 1..];
+    }
+    result.push_str(rest);
+
+    Ok(result)
 }
 
 #[cfg(test)]
@@ -128,12 +488,56 @@ mod tests {
 
         assert_eq!(config.language, "en");
         assert_eq!(config.text_direction, "ltr");
+        assert_eq!(config.output_extension, "html");
         assert!(config.escape_markdown_in_math);
         assert!(!config.math_shorthand);
+        assert_eq!(config.math_renderer, MathRenderer::MathJax);
         assert_eq!(config.pandoc_timeout_seconds, 10);
+        assert_eq!(config.render_timeout_seconds, 300);
         assert_eq!(config.geomdsl_python, "python3");
         assert_eq!(config.geomdsl_timeout_seconds, 15);
         assert_eq!(config.geomdsl_dpi, None);
+        assert!(config.max_concurrency() >= 1);
+    }
+
+    #[test]
+    fn load_parses_math_renderer() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempfile::tempdir()?;
+        let config_path = temp_dir.path().join("config.yaml");
+        std::fs::write(
+            &config_path,
+            "build_dir: build\ncontent_dir: content\ntemplate_dir: templates\nmath_renderer: mathml\n",
+        )?;
+
+        let config = Config::load(&config_path)?;
+
+        assert_eq!(config.math_renderer, MathRenderer::MathML);
+
+        Ok(())
+    }
+
+    #[test]
+    fn markdown_section_merges_over_defaults() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempfile::tempdir()?;
+        let config_path = temp_dir.path().join("config.yaml");
+        std::fs::write(
+            &config_path,
+            "build_dir: build\ncontent_dir: content\ntemplate_dir: templates\nmarkdown:\n  smart: false\n  table: false\n",
+        )?;
+
+        let config = Config::load(&config_path)?;
+
+        assert_eq!(
+            config.markdown,
+            MarkdownOptions {
+                smart: false,
+                table: false,
+                ..Default::default()
+            }
+        );
+        assert!(config.markdown.strikethrough);
+
+        Ok(())
     }
 
     #[test]
@@ -148,7 +552,171 @@ mod tests {
         let config = Config::load(&config_path)?;
 
         assert_eq!(config.pandoc_timeout_seconds, 10);
+        assert_eq!(config.math_renderer, MathRenderer::MathJax);
 
         Ok(())
     }
+
+    #[test]
+    fn load_rejects_translations_csv() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempfile::tempdir()?;
+        let config_path = temp_dir.path().join("config.yaml");
+        std::fs::write(
+            &config_path,
+            "build_dir: build\ncontent_dir: content\ntemplate_dir: templates\ntranslations_csv: en.csv\n",
+        )?;
+
+        let Err(err) = Config::load(&config_path) else {
+            panic!("translations_csv should be rejected");
+        };
+
+        assert!(err.to_string().contains("replaced by `translations_dir`"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_accepts_yaml_toml_and_json() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempfile::tempdir()?;
+        let files = [
+            (
+                "config.yaml",
+                "build_dir: out\ncontent_dir: pages\ntemplate_dir: layouts\nlanguage: fa\nmath_renderer: mathml\ncontext:\n  site_name: Notes\n",
+            ),
+            (
+                "config.toml",
+                "build_dir = \"out\"\ncontent_dir = \"pages\"\ntemplate_dir = \"layouts\"\nlanguage = \"fa\"\nmath_renderer = \"mathml\"\n\n[context]\nsite_name = \"Notes\"\n",
+            ),
+            (
+                "config.json",
+                r#"{"build_dir": "out", "content_dir": "pages", "template_dir": "layouts", "language": "fa", "math_renderer": "mathml", "context": {"site_name": "Notes"}}"#,
+            ),
+        ];
+
+        for (name, contents) in files {
+            let path = temp_dir.path().join(name);
+            std::fs::write(&path, contents)?;
+            let config = Config::load(&path)?;
+
+            assert_eq!(config.build_dir, PathBuf::from("out"), "{name}");
+            assert_eq!(config.content_dir, PathBuf::from("pages"), "{name}");
+            assert_eq!(config.template_dir, PathBuf::from("layouts"), "{name}");
+            assert_eq!(config.language, "fa", "{name}");
+            assert_eq!(config.math_renderer, MathRenderer::MathML, "{name}");
+            assert_eq!(
+                config.context.as_ref().and_then(|c| c.get("site_name")),
+                Some(&Value::from("Notes")),
+                "{name}"
+            );
+        }
+
+        let ini = temp_dir.path().join("config.ini");
+        std::fs::write(&ini, "build_dir=out")?;
+        let err = Config::load(&ini).err().expect("unknown extension");
+        assert!(
+            err.to_string().starts_with("Unsupported config file"),
+            "{err}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn extends_overlays_a_base_config() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempfile::tempdir()?;
+        std::fs::create_dir_all(temp_dir.path().join("shared"))?;
+        std::fs::write(
+            temp_dir.path().join("shared/base.yaml"),
+            "build_dir: build\ncontent_dir: content\ntemplate_dir: templates\ncontext:\n  site_name: Notes\n  year: 2024\n",
+        )?;
+        let site = temp_dir.path().join("site.toml");
+        std::fs::write(
+            &site,
+            "extends = \"shared/base.yaml\"\nbuild_dir = \"public\"\n\n[context]\nyear = 2025\n",
+        )?;
+
+        let config = Config::load(&site)?;
+
+        assert_eq!(config.build_dir, PathBuf::from("public"));
+        assert_eq!(config.template_dir, PathBuf::from("templates"));
+        let context = config.context.expect("context");
+        assert_eq!(context.get("site_name"), Some(&Value::from("Notes")));
+        assert_eq!(context.get("year"), Some(&Value::from(2025)));
+
+        let looped = temp_dir.path().join("loop.yaml");
+        std::fs::write(&looped, "extends: loop.yaml\nbuild_dir: build\n")?;
+        let err = Config::load(&looped).err().expect("cycle");
+        assert!(err.to_string().contains("extends itself"), "{err}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn interpolates_environment_variables() {
+        let lookup = |name: &str| (name == "PANDOC_HOME").then(|| "/opt/pandoc".to_string());
+
+        assert_eq!(
+            interpolate_env("${PANDOC_HOME}/bin/pandoc", &lookup),
+            Ok("/opt/pandoc/bin/pandoc".to_string())
+        );
+        assert_eq!(
+            interpolate_env("${SITE_URL:-http://localhost:8000}/", &lookup),
+            Ok("http://localhost:8000/".to_string())
+        );
+        assert_eq!(
+            interpolate_env("${PANDOC_HOME:-/usr}", &lookup),
+            Ok("/opt/pandoc".to_string())
+        );
+        assert_eq!(
+            interpolate_env("${SITE_URL}", &lookup),
+            Err("Environment variable SITE_URL is not set".to_string())
+        );
+        assert_eq!(
+            interpolate_env("$5 and {braces}", &lookup),
+            Ok("$5 and {braces}".to_string())
+        );
+    }
+
+    #[test]
+    fn load_interpolates_string_settings() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("config.yaml");
+        std::fs::write(
+            &path,
+            "build_dir: ${SSG_TEST_UNSET_BUILD_DIR:-out}\ncontent_dir: content\ntemplate_dir: templates\n",
+        )?;
+        assert_eq!(Config::load(&path)?.build_dir, PathBuf::from("out"));
+
+        std::fs::write(
+            &path,
+            "build_dir: ${SSG_TEST_UNSET_BUILD_DIR}\ncontent_dir: content\ntemplate_dir: templates\n",
+        )?;
+        let err = Config::load(&path).err().expect("unset variable");
+        assert!(
+            err.to_string()
+                .contains("SSG_TEST_UNSET_BUILD_DIR is not set"),
+            "{err}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn interpolates_nested_settings_raw() {
+        let lookup = |name: &str| (name == "SITE_NAME").then(|| "<b>Notes</b>".to_string());
+        let mut settings = serde_json::json!({
+            "context": {"site_name": "${SITE_NAME}", "links": [{"url": "${SITE_URL:-/}"}]},
+        });
+        interpolate_settings(&mut settings, &lookup).unwrap();
+        assert_eq!(settings["context"]["site_name"], "<b>Notes</b>");
+        assert_eq!(settings["context"]["links"][0]["url"], "/");
+
+        let mut settings = serde_json::json!({
+            "context": {"links": [{"url": "${SITE_URL}"}]},
+        });
+        assert_eq!(
+            interpolate_settings(&mut settings, &lookup),
+            Err("Environment variable SITE_URL is not set".to_string())
+        );
+    }
 }
@@ -1,17 +1,39 @@
 use super::metadata::{ContentKind, ContentMetadata};
 use crate::config::Config;
 use crate::formatted_text::FormattedText;
+use crate::render::Translations;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
 use std::error::Error;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
 const BODY_BASENAME: &str = "body";
 
+/// A solution of a problem, with the title given on its first line.
+#[derive(Debug)]
+pub struct Solution {
+    pub title: Option<String>,
+    pub text: FormattedText,
+}
+
+impl Solution {
+    /// The solution's title, or the translation of "Solution {n}" for the
+    /// `index`-th (from 0) untitled one.
+    pub fn label(&self, index: usize, translations: &Translations) -> String {
+        self.title.clone().unwrap_or_else(|| {
+            let args = HashMap::from([("n".to_string(), Value::from(index + 1))]);
+            translations.translate("Solution {n}", &args)
+        })
+    }
+}
+
 #[derive(Debug)]
 pub enum Content {
     Problem {
         metadata: ContentMetadata,
         statement: FormattedText,
-        solutions: Vec<FormattedText>,
+        solutions: Vec<Solution>,
         hints: Vec<FormattedText>,
     },
     Blog {
@@ -33,6 +55,22 @@ impl Content {
         }
     }
 
+    /// Load the metadata of the item at `path` without rendering its texts.
+    /// A bare Markdown page gets its title and template from its front
+    /// matter or first heading; other bare pages only get their kind and
+    /// output location.
+    pub fn load_metadata(path: &Path, config: &Config) -> Result<ContentMetadata, Box<dyn Error>> {
+        if path.is_dir() {
+            return ContentMetadata::load(path, config);
+        }
+        let mut metadata = bare_page_metadata(path, config)?;
+        if path.extension().and_then(|s| s.to_str()) == Some("md") {
+            let text = load_markdown_with_includes(path)?;
+            apply_front_matter(&text, path, &mut metadata)?;
+        }
+        Ok(metadata)
+    }
+
     pub fn metadata(&self) -> &ContentMetadata {
         match self {
             Content::Problem { metadata, .. } => metadata,
@@ -40,6 +78,24 @@ impl Content {
             Content::Page { metadata, .. } => metadata,
         }
     }
+
+    /// The main text of the item: a problem's statement or a post/page body.
+    pub fn main_text(&self) -> &FormattedText {
+        match self {
+            Content::Problem { statement, .. } => statement,
+            Content::Blog { body, .. } => body,
+            Content::Page { body, .. } => body,
+        }
+    }
+
+    /// Approximate number of words in the main text's source.
+    pub fn word_count(&self) -> usize {
+        self.main_text()
+            .source()
+            .split_whitespace()
+            .filter(|word| word.chars().any(char::is_alphanumeric))
+            .count()
+    }
 }
 
 fn load_directory_content(path: &Path, config: &Config) -> Result<Content, Box<dyn Error>> {
@@ -102,6 +158,50 @@ pub(super) fn load_markdown_file(path: &Path, config: &Config) -> Result<String,
     crate::formatted_text::preprocess_learning_blocks(&markdown, path, config)
 }
 
+/// Files pulled into the Markdown file at `path` by `#include` directives.
+pub(super) fn markdown_includes(path: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let content = std::fs::read_to_string(path)?;
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    let mut in_fence = false;
+    let mut includes = Vec::new();
+
+    for line in content.lines() {
+        if is_fence_line(line) {
+            in_fence = !in_fence;
+        }
+        if in_fence {
+            continue;
+        }
+        if let Some(include_path) = parse_include_directive(line) {
+            includes.push(normalize_lexically(&base_dir.join(include_path)));
+        }
+    }
+
+    Ok(includes)
+}
+
+/// Drop `.` components and resolve `..` against the preceding component,
+/// without touching the filesystem, so the same file reached from different
+/// directories gets the same path.
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
 fn load_include_for_line(
     line: &str,
     base_dir: &Path,
@@ -117,7 +217,9 @@ fn load_include_for_line(
     }
 
     let include_file = base_dir.join(include_path);
-    let canonical_include_file = include_file.canonicalize()?;
+    let canonical_include_file = include_file
+        .canonicalize()
+        .map_err(|e| format!("Cannot include {}: {e}", include_file.display()))?;
     if !canonical_include_file.starts_with(canonical_base_dir) {
         return Err(format!(
             "Include path escapes content directory: {}",
@@ -157,6 +259,7 @@ fn load_bare_page(path: &Path, config: &Config) -> Result<Content, Box<dyn Error
 fn bare_page_metadata(path: &Path, config: &Config) -> Result<ContentMetadata, Box<dyn Error>> {
     Ok(ContentMetadata {
         kind: ContentKind::Page,
+        source_path: path.to_path_buf(),
         output_path: content_output_path(path, config)?,
         url: content_url(path, config)?,
         ..Default::default()
@@ -171,14 +274,17 @@ fn load_bare_page_body(
     match path.extension().and_then(|s| s.to_str()) {
         Some("md") => {
             let text = load_markdown_file(path, config)?;
-            if let Some(title) = first_markdown_heading(&text) {
-                metadata.title = title;
-            }
-            Ok(FormattedText::Markdown(text))
+            let text = apply_front_matter(&text, path, metadata)?;
+            Ok(FormattedText::Markdown(text.to_string()))
         }
-        Some("tex") => {
+        Some("tex") => Ok(FormattedText::read_latex(path)?),
+        Some("typ") => Ok(FormattedText::Typst {
+            source: std::fs::read_to_string(path)?,
+            root: path.parent().map(Path::to_path_buf),
+        }),
+        Some("org") => {
             let text = std::fs::read_to_string(path)?;
-            Ok(FormattedText::Latex(text))
+            Ok(FormattedText::Org(text))
         }
         Some("html") => {
             let text = std::fs::read_to_string(path)?;
@@ -188,6 +294,56 @@ fn load_bare_page_body(
     }
 }
 
+/// Settings a bare Markdown page may give in a leading `---` YAML block.
+#[derive(Debug, Default, Deserialize)]
+struct FrontMatter {
+    title: Option<String>,
+    template: Option<String>,
+}
+
+/// Split a leading `---`-delimited YAML block off `markdown`.
+fn split_front_matter(markdown: &str) -> Result<(FrontMatter, &str), serde_yaml::Error> {
+    let Some(rest) = markdown
+        .strip_prefix("---\n")
+        .or_else(|| markdown.strip_prefix("---\r\n"))
+    else {
+        return Ok((FrontMatter::default(), markdown));
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let yaml = &rest[..offset];
+            let front_matter = if yaml.trim().is_empty() {
+                FrontMatter::default()
+            } else {
+                serde_yaml::from_str(yaml)?
+            };
+            return Ok((front_matter, &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+
+    Ok((FrontMatter::default(), markdown))
+}
+
+/// Take the title and template of a bare Markdown page from its front
+/// matter (or first heading) and return the text after the front matter.
+fn apply_front_matter<'a>(
+    markdown: &'a str,
+    path: &Path,
+    metadata: &mut ContentMetadata,
+) -> Result<&'a str, Box<dyn Error>> {
+    let (front_matter, text) = split_front_matter(markdown)
+        .map_err(|e| format!("Invalid front matter in {}: {}", path.display(), e))?;
+    if let Some(title) = front_matter.title.or_else(|| first_markdown_heading(text)) {
+        metadata.title = title;
+    }
+    if front_matter.template.is_some() {
+        metadata.template = front_matter.template;
+    }
+    Ok(text)
+}
+
 fn first_markdown_heading(markdown: &str) -> Option<String> {
     let first_line = markdown.lines().next()?;
     if first_line.starts_with("# ") || first_line.starts_with("## ") {
@@ -225,21 +381,42 @@ fn load_named_content_file(
     let md_file = base_path.join(format!("{}.md", file_basename));
     let tex_file = base_path.join(format!("{}.tex", file_basename));
     let html_file = base_path.join(format!("{}.html", file_basename));
+    let typst_file = base_path.join(format!("{}.typ", file_basename));
+    let org_file = base_path.join(format!("{}.org", file_basename));
 
     if md_file.exists() {
         let text = load_markdown_file(&md_file, config)?;
         Ok(FormattedText::Markdown(text))
     } else if tex_file.exists() {
-        let text = std::fs::read_to_string(tex_file)?;
-        Ok(FormattedText::Latex(text))
+        Ok(FormattedText::read_latex(&tex_file)?)
     } else if html_file.exists() {
         let text = std::fs::read_to_string(html_file)?;
         Ok(FormattedText::Html(text))
+    } else if typst_file.exists() {
+        Ok(FormattedText::Typst {
+            source: std::fs::read_to_string(typst_file)?,
+            root: Some(base_path.to_path_buf()),
+        })
+    } else if org_file.exists() {
+        let text = std::fs::read_to_string(org_file)?;
+        Ok(FormattedText::Org(text))
     } else {
         Err(format!("No {} file found", file_basename).into())
     }
 }
 
+/// The dir `path` is loaded from: the drafts dir for drafts, otherwise the
+/// content dir.
+pub fn source_root(path: &Path, config: &Config) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let cwd = std::env::current_dir()?;
+    let path = cwd.join(path);
+    let drafts_dir = config.drafts_dir.as_ref().map(|dir| cwd.join(dir));
+    Ok(match drafts_dir {
+        Some(dir) if path.starts_with(&dir) => dir,
+        _ => cwd.join(&config.content_dir),
+    })
+}
+
 pub fn content_output_path(
     path: &Path,
     config: &Config,
@@ -248,23 +425,77 @@ pub fn content_output_path(
     let path = path.with_extension("");
     let path = cwd.join(path);
     let content_dir = cwd.join(&config.content_dir);
-    let rel_path = path.strip_prefix(content_dir.clone()).map_err(|_e| {
-        format!(
-            "Path {} is not a subpath of content directory {}",
-            path.display(),
-            content_dir.display()
-        )
-    })?;
+    // Drafts mirror the content tree, so they map to the same output paths.
+    let drafts_dir = config.drafts_dir.as_ref().map(|dir| cwd.join(dir));
+    let rel_path = path
+        .strip_prefix(&content_dir)
+        .ok()
+        .or_else(|| {
+            drafts_dir
+                .as_ref()
+                .and_then(|dir| path.strip_prefix(dir).ok())
+        })
+        .ok_or_else(|| {
+            format!(
+                "Path {} is not a subpath of content directory {}",
+                path.display(),
+                content_dir.display()
+            )
+        })?;
 
     // Create output file path that preserves directory structure
     let mut output_file_path = config.build_dir.join(rel_path);
-    output_file_path.set_extension("html");
+    if config.clean_urls && output_file_path.file_name() != Some("index".as_ref()) {
+        output_file_path.push(format!("index.{}", config.output_extension));
+    } else {
+        output_file_path.set_extension(&config.output_extension);
+    }
 
     Ok(output_file_path)
 }
 
+/// Output path for content with an explicit `slug`: `<slug>.html`, or
+/// `<slug>/index.html` when the slug ends with `/` or `clean_urls` is set.
+///
+/// Slugs are relative to the build directory and may not leave it.
+pub fn slug_output_path(slug: &str, config: &Config) -> Result<PathBuf, Box<dyn Error>> {
+    let relative = Path::new(slug.trim_end_matches('/'));
+    let is_safe = relative
+        .components()
+        .all(|component| matches!(component, std::path::Component::Normal(_)));
+    if slug.trim_end_matches('/').is_empty() || !is_safe {
+        return Err(format!(
+            "Invalid slug {:?}: must be a relative path inside the build directory",
+            slug
+        )
+        .into());
+    }
+
+    let mut output_path = config.build_dir.join(relative);
+    if slug.ends_with('/') || config.clean_urls {
+        output_path.push(format!("index.{}", config.output_extension));
+    } else {
+        let file_name = format!(
+            "{}.{}",
+            output_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy(),
+            config.output_extension
+        );
+        output_path.set_file_name(file_name);
+    }
+
+    Ok(output_path)
+}
+
 pub fn content_url(path: &Path, config: &Config) -> Result<String, Box<dyn std::error::Error>> {
-    let output_path = content_output_path(path, config)?;
+    output_url(&content_output_path(path, config)?, config)
+}
+
+/// Site-absolute URL of a file written to `output_path` in the build dir.
+/// With `clean_urls`, `index` pages are addressed by their directory.
+pub fn output_url(output_path: &Path, config: &Config) -> Result<String, Box<dyn Error>> {
     let rel_path = output_path.strip_prefix(&config.build_dir).map_err(|_e| {
         format!(
             "Path {} is not a subpath of build directory {}",
@@ -274,6 +505,10 @@ pub fn content_url(path: &Path, config: &Config) -> Result<String, Box<dyn std::
     })?;
     let url = rel_path.to_string_lossy().to_string();
     let url = url.replace("\\", "/"); // Normalize path separators for URLs
+    let index_page = format!("index.{}", config.output_extension);
+    if config.clean_urls && (url == index_page || url.ends_with(&format!("/{index_page}"))) {
+        return Ok(format!("/{}", &url[..url.len() - index_page.len()]));
+    }
     Ok(format!("/{}", url))
 }
 
@@ -439,6 +674,78 @@ id: "test-page"
         Ok(())
     }
 
+    #[test]
+    fn test_content_output_extension() -> Result<(), Box<dyn std::error::Error>> {
+        let conf = Config {
+            content_dir: PathBuf::from("content"),
+            build_dir: PathBuf::from("build"),
+            output_extension: "htm".to_string(),
+            ..Default::default()
+        };
+
+        let path = Path::new("content/subdir/page1.md");
+        assert_eq!(
+            content_output_path(path, &conf)?,
+            Path::new("build/subdir/page1.htm")
+        );
+        assert_eq!(content_url(path, &conf)?, "/subdir/page1.htm");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_drafts_dir_maps_like_content_dir() -> Result<(), Box<dyn std::error::Error>> {
+        let conf = Config {
+            content_dir: PathBuf::from("content"),
+            drafts_dir: Some(PathBuf::from("drafts")),
+            build_dir: PathBuf::from("build"),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            content_url(Path::new("drafts/blog/wip"), &conf)?,
+            "/blog/wip.html"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_slug_output_path() -> Result<(), Box<dyn std::error::Error>> {
+        let conf = Config {
+            build_dir: PathBuf::from("build"),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            slug_output_path("posts/hello-world", &conf)?,
+            Path::new("build/posts/hello-world.html")
+        );
+        assert_eq!(
+            slug_output_path("about/", &conf)?,
+            Path::new("build/about/index.html")
+        );
+        assert_eq!(
+            output_url(&slug_output_path("about/", &conf)?, &conf)?,
+            "/about/index.html"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_slug_cannot_escape_build_dir() {
+        let conf = Config {
+            build_dir: PathBuf::from("build"),
+            ..Default::default()
+        };
+
+        for slug in ["../etc", "posts/../../etc", "/etc/passwd", ""] {
+            let err = slug_output_path(slug, &conf).unwrap_err();
+            assert!(err.to_string().contains("Invalid slug"), "{slug}: {err}");
+        }
+    }
+
     #[test]
     fn test_content_url() -> Result<(), Box<dyn std::error::Error>> {
         let conf = Config {
@@ -453,4 +760,79 @@ id: "test-page"
 
         Ok(())
     }
+
+    #[test]
+    fn clean_urls_write_directory_index_pages() -> Result<(), Box<dyn std::error::Error>> {
+        let conf = Config {
+            content_dir: PathBuf::from("content"),
+            build_dir: PathBuf::from("build"),
+            clean_urls: true,
+            ..Default::default()
+        };
+
+        let nested = Path::new("content/docs/guide/install.md");
+        assert_eq!(
+            content_output_path(nested, &conf)?,
+            PathBuf::from("build/docs/guide/install/index.html")
+        );
+        assert_eq!(content_url(nested, &conf)?, "/docs/guide/install/");
+
+        let index = Path::new("content/docs/index.md");
+        assert_eq!(
+            content_output_path(index, &conf)?,
+            PathBuf::from("build/docs/index.html")
+        );
+        assert_eq!(content_url(index, &conf)?, "/docs/");
+        assert_eq!(
+            slug_output_path("about", &conf)?,
+            PathBuf::from("build/about/index.html")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn front_matter_template_is_used_for_bare_pages() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let content_dir = temp_dir.path().join("content");
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&content_dir)?;
+        fs::create_dir_all(&template_dir)?;
+        fs::write(
+            template_dir.join("page.html"),
+            "page: {{ page.body | safe }}",
+        )?;
+        fs::write(
+            template_dir.join("landing.html"),
+            "landing {{ title }}: {{ page.body | safe }}",
+        )?;
+        fs::write(
+            content_dir.join("home.md"),
+            "---\ntitle: Welcome\ntemplate: landing.html\n---\nHello\n",
+        )?;
+        fs::write(
+            content_dir.join("broken.md"),
+            "---\ntemplate: missing.html\n---\nHello\n",
+        )?;
+        let config = Config {
+            content_dir: content_dir.clone(),
+            build_dir: temp_dir.path().join("build"),
+            template_dir,
+            ..Default::default()
+        };
+        let renderer = crate::render::Renderer::new(&config)?;
+
+        let content = Content::load(&content_dir.join("home.md"), &config)?;
+        assert_eq!(content.metadata().template.as_deref(), Some("landing.html"));
+        assert_eq!(
+            content.render_html(&renderer, &config)?,
+            "landing Welcome: <p>Hello</p>\n"
+        );
+
+        let broken = Content::load(&content_dir.join("broken.md"), &config)?;
+        let err = broken.render_html(&renderer, &config).unwrap_err();
+        assert!(err.to_string().contains("missing.html"), "{err}");
+
+        Ok(())
+    }
 }
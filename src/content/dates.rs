@@ -0,0 +1,65 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Deserializer};
+
+/// Parse a metadata date: RFC 3339 (`2025-03-06T12:00:00Z`), a naive date
+/// and time (`2025-03-06 12:00:00`, taken as UTC) or a date alone
+/// (`2025-03-06`, midnight UTC).
+pub fn parse_date(value: &str) -> Result<DateTime<Utc>, String> {
+    let value = value.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date.with_timezone(&Utc));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(date) = NaiveDateTime::parse_from_str(value, format) {
+            return Ok(date.and_utc());
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+
+    Err(format!(
+        "invalid date {:?}: expected RFC 3339 (2025-03-06T12:00:00Z) or YYYY-MM-DD",
+        value
+    ))
+}
+
+/// Serde helper for optional metadata dates, see [`parse_date`].
+pub(super) fn deserialize_optional_date<'de, D>(
+    deserializer: D,
+) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|value| parse_date(&value).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rfc3339_dates() {
+        assert_eq!(
+            parse_date("2025-03-06T14:00:00+02:00").unwrap(),
+            "2025-03-06T12:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+    }
+
+    #[test]
+    fn parses_date_only_values_as_midnight_utc() {
+        assert_eq!(
+            parse_date("2025-03-06").unwrap(),
+            "2025-03-06T00:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        let err = parse_date("last tuesday").unwrap_err();
+
+        assert!(err.contains("invalid date \"last tuesday\""));
+    }
+}
@@ -0,0 +1,271 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use super::content::Content;
+use super::metadata::{ContentKind, ContentMetadata};
+use crate::config::Config;
+use crate::logging;
+
+const METADATA_FILE: &str = "metadata.yaml";
+
+/// Walk `base_path` and load the metadata of every content item below it.
+///
+/// Only items of `content_type` are returned; `None` returns every kind.
+/// Only metadata is read: texts are not rendered, so items without an
+/// `excerpt` keep none (see [`fill_excerpt`](super::fill_excerpt)). Drafts
+/// are left out, and items that fail to load are reported as warnings
+/// and skipped.
+pub fn find_content_files(
+    base_path: &Path,
+    content_type: Option<ContentKind>,
+    config: &Config,
+) -> Result<Vec<ContentMetadata>, Box<dyn Error>> {
+    let mut content_items = Vec::new();
+
+    for entry in WalkDir::new(base_path)
+        .follow_links(config.follow_symlinks)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let path = entry.path();
+
+        if path.is_dir() {
+            continue;
+        }
+
+        if path.file_name() == Some(METADATA_FILE.as_ref()) {
+            load_directory_metadata(path, content_type, config, &mut content_items);
+            continue;
+        }
+
+        if content_type.is_none_or(|kind| kind == ContentKind::Page) && is_bare_content_file(path) {
+            if has_directory_metadata(path) {
+                continue;
+            }
+            load_bare_page_metadata(path, config, &mut content_items);
+        }
+    }
+
+    Ok(content_items)
+}
+
+/// Walk `base_path` and return the path of every content item below it: the
+/// directory of each `metadata.yaml` and each bare page file. Nothing is
+/// loaded, so drafts and broken items are included.
+pub fn find_content_paths(base_path: &Path, config: &Config) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    for entry in WalkDir::new(base_path)
+        .follow_links(config.follow_symlinks)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let path = entry.path();
+
+        if path.is_dir() {
+            continue;
+        }
+
+        if path.file_name() == Some(METADATA_FILE.as_ref()) {
+            paths.extend(path.parent().map(Path::to_path_buf));
+        } else if is_bare_content_file(path) && !has_directory_metadata(path) {
+            paths.push(path.to_path_buf());
+        }
+    }
+
+    paths
+}
+
+fn load_directory_metadata(
+    metadata_path: &Path,
+    content_type: Option<ContentKind>,
+    config: &Config,
+    content_items: &mut Vec<ContentMetadata>,
+) {
+    let Some(dir) = metadata_path.parent() else {
+        logging::warn(format_args!(
+            "Failed to load metadata from {}: metadata.yaml has no parent directory",
+            metadata_path.display()
+        ));
+        return;
+    };
+
+    match ContentMetadata::load(dir, config) {
+        Ok(metadata) => {
+            if !metadata.is_draft(config) && content_type.is_none_or(|kind| metadata.kind == kind) {
+                content_items.push(metadata);
+            }
+        }
+        Err(err) => {
+            logging::warn(format_args!(
+                "Failed to load metadata from {}: {}",
+                metadata_path.display(),
+                err
+            ));
+        }
+    }
+}
+
+fn has_directory_metadata(path: &Path) -> bool {
+    path.parent()
+        .map(|parent| parent.join(METADATA_FILE).exists())
+        .unwrap_or(false)
+}
+
+fn load_bare_page_metadata(path: &Path, config: &Config, content_items: &mut Vec<ContentMetadata>) {
+    match Content::load_metadata(path, config) {
+        Ok(metadata) => content_items.push(metadata),
+        Err(err) => {
+            logging::warn(format_args!(
+                "Failed to load bare page from {}: {}",
+                path.display(),
+                err
+            ));
+        }
+    }
+}
+
+fn is_bare_content_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("md" | "html" | "tex" | "typ" | "org")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn find_content_files_includes_bare_pages() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let build_dir = temp_dir.path().join("build");
+        fs::create_dir_all(&content_dir)?;
+        fs::write(content_dir.join("about.md"), "# About\n\nBody")?;
+
+        let config = Config {
+            content_dir: content_dir.clone(),
+            build_dir,
+            ..Default::default()
+        };
+
+        let items = find_content_files(&content_dir, Some(ContentKind::Page), &config)?;
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "About");
+        assert!(items[0].url.ends_with("/about.html"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn find_content_files_reads_metadata_without_rendering() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let post_dir = content_dir.join("post");
+        fs::create_dir_all(&post_dir)?;
+        fs::write(post_dir.join("metadata.yaml"), "title: Post\ntype: blog\n")?;
+        fs::write(post_dir.join("body.md"), "Some text.\n")?;
+        fs::write(
+            content_dir.join("about.md"),
+            "---\ntitle: About us\n---\n:::geomdsl\nnot valid\n:::\n",
+        )?;
+        let config = Config {
+            content_dir: content_dir.clone(),
+            build_dir: temp_dir.path().join("build"),
+            ..Default::default()
+        };
+
+        let mut items = find_content_files(&content_dir, None, &config)?;
+        items.sort_by(|a, b| a.title.cmp(&b.title));
+
+        let titles: Vec<&str> = items.iter().map(|item| item.title.as_str()).collect();
+        assert_eq!(titles, ["About us", "Post"]);
+        assert!(items.iter().all(|item| item.excerpt.is_none()));
+        assert!(!temp_dir.path().join("build").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn find_content_files_skips_bare_body_in_metadata_directory() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let build_dir = temp_dir.path().join("build");
+        let page_dir = content_dir.join("page");
+        fs::create_dir_all(&page_dir)?;
+        fs::write(page_dir.join("metadata.yaml"), "title: Page\ntype: page\n")?;
+        fs::write(page_dir.join("body.md"), "# Body\n")?;
+
+        let config = Config {
+            content_dir: content_dir.clone(),
+            build_dir,
+            ..Default::default()
+        };
+
+        let items = find_content_files(&content_dir, Some(ContentKind::Page), &config)?;
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "Page");
+
+        Ok(())
+    }
+
+    #[test]
+    fn find_content_files_returns_all_kinds_without_filter() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let blog_dir = content_dir.join("post");
+        fs::create_dir_all(&blog_dir)?;
+        fs::write(blog_dir.join("metadata.yaml"), "title: Post\ntype: blog\n")?;
+        fs::write(content_dir.join("about.md"), "# About\n")?;
+
+        let config = Config {
+            content_dir: content_dir.clone(),
+            build_dir: temp_dir.path().join("build"),
+            ..Default::default()
+        };
+
+        let mut items = find_content_files(&content_dir, None, &config)?;
+        items.sort_by(|a, b| a.title.cmp(&b.title));
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].kind, ContentKind::Page);
+        assert_eq!(items[1].kind, ContentKind::Blog);
+
+        Ok(())
+    }
+
+    #[test]
+    fn find_content_files_excludes_drafts() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        for (name, draft) in [("published", false), ("unpublished", true)] {
+            let dir = content_dir.join(name);
+            fs::create_dir_all(&dir)?;
+            fs::write(
+                dir.join("metadata.yaml"),
+                format!("title: {name}\ntype: blog\ndraft: {draft}\n"),
+            )?;
+        }
+
+        let config = Config {
+            content_dir: content_dir.clone(),
+            build_dir: temp_dir.path().join("build"),
+            ..Default::default()
+        };
+
+        let items = find_content_files(&content_dir, Some(ContentKind::Blog), &config)?;
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "published");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use super::content::Content;
+use super::metadata::ContentMetadata;
+use crate::config::Config;
+
+/// Target length of derived excerpts, in characters.
+pub const EXCERPT_LENGTH: usize = 200;
+
+/// Fill in `metadata.excerpt` from the item's main text when it has none.
+/// This loads and converts the text, so it is done only for items that are
+/// listed, not by discovery.
+pub fn fill_excerpt(metadata: &mut ContentMetadata, config: &Config) {
+    if metadata.excerpt.is_some() {
+        return;
+    }
+    metadata.excerpt =
+        plain_text(metadata, config, EXCERPT_LENGTH).filter(|excerpt| !excerpt.is_empty());
+}
+
+/// Plain text of the item's main text, as
+/// [`FormattedText::to_plain_text`](crate::formatted_text::FormattedText::to_plain_text)
+/// gives it, cut near `max_chars` like [`derive_excerpt`]. `None` when the
+/// item can't be loaded or converted.
+pub fn plain_text(metadata: &ContentMetadata, config: &Config, max_chars: usize) -> Option<String> {
+    let content = Content::load(&metadata.source_path, config).ok()?;
+    let text = content.main_text().to_plain_text(config).ok()?;
+    Some(derive_excerpt(&text, max_chars))
+}
+
+/// Cut plain `text` at a word boundary near `max_chars`, ending it with `…`
+/// when anything was left out.
+pub fn derive_excerpt(text: &str, max_chars: usize) -> String {
+    let mut excerpt = String::new();
+    for word in text.split_whitespace() {
+        let extra = usize::from(!excerpt.is_empty());
+        if excerpt.chars().count() + extra + word.chars().count() > max_chars {
+            excerpt.push('…');
+            return excerpt;
+        }
+        if extra > 0 {
+            excerpt.push(' ');
+        }
+        excerpt.push_str(word);
+    }
+    excerpt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn keeps_explicit_excerpt() {
+        let mut metadata = ContentMetadata {
+            excerpt: Some("Hand written.".to_string()),
+            source_path: "missing".into(),
+            ..Default::default()
+        };
+
+        fill_excerpt(&mut metadata, &Config::default());
+
+        assert_eq!(metadata.excerpt.as_deref(), Some("Hand written."));
+    }
+
+    #[test]
+    fn derives_excerpt_without_cutting_words() {
+        let text = "Title\nSome bold text with a link.\nAnother sentence follows here.";
+
+        assert_eq!(
+            derive_excerpt(text, 200),
+            "Title Some bold text with a link. Another sentence follows here."
+        );
+        assert_eq!(derive_excerpt(text, 22), "Title Some bold text…");
+    }
+
+    #[test]
+    fn excerpt_is_plain_text_of_rendered_body() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = tempfile::tempdir()?;
+        let post_dir = temp_dir.path().join("content/post");
+        fs::create_dir_all(&post_dir)?;
+        fs::write(post_dir.join("metadata.yaml"), "title: Post\ntype: blog\n")?;
+        fs::write(
+            post_dir.join("body.md"),
+            "Some **bold** text with a [link](https://example.com) &amp; $x^2$.\n",
+        )?;
+        let config = Config {
+            content_dir: temp_dir.path().join("content"),
+            build_dir: temp_dir.path().join("build"),
+            ..Default::default()
+        };
+        let mut metadata = ContentMetadata::load(&post_dir, &config)?;
+
+        fill_excerpt(&mut metadata, &config);
+
+        assert_eq!(
+            metadata.excerpt.as_deref(),
+            Some("Some bold text with a link & x^2.")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn problems_excerpt_from_statement() {
+        if std::process::Command::new("pandoc")
+            .arg("--version")
+            .output()
+            .is_err()
+        {
+            eprintln!("skipping: pandoc is not installed");
+            return;
+        }
+        let temp_dir = tempfile::tempdir().unwrap();
+        let problem_dir = temp_dir.path().join("content/p1");
+        fs::create_dir_all(&problem_dir).unwrap();
+        fs::write(
+            problem_dir.join("metadata.yaml"),
+            "title: P1\ntype: problem\n",
+        )
+        .unwrap();
+        fs::write(
+            problem_dir.join("problem.tex"),
+            "Prove that \\emph{every} prime $p > 2$ is odd.",
+        )
+        .unwrap();
+        let config = Config {
+            content_dir: temp_dir.path().join("content"),
+            build_dir: temp_dir.path().join("build"),
+            ..Default::default()
+        };
+        let mut metadata = ContentMetadata::load(&problem_dir, &config).unwrap();
+
+        fill_excerpt(&mut metadata, &config);
+
+        assert_eq!(
+            metadata.excerpt.as_deref(),
+            Some("Prove that every prime p > 2 is odd.")
+        );
+    }
+}
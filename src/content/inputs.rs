@@ -0,0 +1,182 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use super::content::markdown_includes;
+use super::metadata::inherited_metadata_files;
+use crate::config::Config;
+
+/// Every file the content item at `path` is built from: the files of its
+/// directory (metadata, statements, solutions, hints, images), the files
+/// its Markdown `#include`s, directly or through other included files, and
+/// the `_index.yaml` files it inherits from.
+/// Sorted and without duplicates.
+pub fn content_inputs(path: &Path, config: &Config) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut inputs = Vec::new();
+
+    if path.is_dir() {
+        for entry in WalkDir::new(path).follow_links(config.follow_symlinks) {
+            let entry = entry?;
+            if entry.file_type().is_file() {
+                inputs.push(entry.into_path());
+            }
+        }
+    } else {
+        inputs.push(path.to_path_buf());
+    }
+
+    let mut pending: Vec<PathBuf> = inputs
+        .iter()
+        .filter(|input| is_markdown(input))
+        .cloned()
+        .collect();
+    let mut seen = BTreeSet::new();
+    while let Some(markdown_file) = pending.pop() {
+        if !seen.insert(markdown_file.clone()) {
+            continue;
+        }
+        for include in markdown_includes(&markdown_file)? {
+            if is_markdown(&include) && include.is_file() {
+                pending.push(include.clone());
+            }
+            inputs.push(include);
+        }
+    }
+    inputs.extend(inherited_metadata_files(path, config)?);
+
+    inputs.sort();
+    inputs.dedup();
+    Ok(inputs)
+}
+
+fn is_markdown(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()) == Some("md")
+}
+
+/// Map every file `#include`d by Markdown below `base_path` to the
+/// top-level Markdown files (those no other file includes) that pull it in,
+/// directly or through other fragments.
+pub fn include_graph(
+    base_path: &Path,
+) -> Result<BTreeMap<PathBuf, BTreeSet<PathBuf>>, Box<dyn Error>> {
+    let mut includes = BTreeMap::new();
+    for entry in WalkDir::new(base_path) {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type().is_file() && is_markdown(path) {
+            includes.insert(path.to_path_buf(), markdown_includes(path)?);
+        }
+    }
+
+    let fragments: BTreeSet<PathBuf> = includes.values().flatten().cloned().collect();
+    let mut graph: BTreeMap<PathBuf, BTreeSet<PathBuf>> = BTreeMap::new();
+    for top_level in includes.keys().filter(|path| !fragments.contains(*path)) {
+        let mut pending = includes[top_level].clone();
+        let mut seen = BTreeSet::new();
+        while let Some(fragment) = pending.pop() {
+            if !seen.insert(fragment.clone()) {
+                continue;
+            }
+            if let Some(nested) = includes.get(&fragment) {
+                pending.extend(nested.iter().cloned());
+            } else if fragment.is_file() {
+                pending.extend(markdown_includes(&fragment)?);
+            }
+            graph.entry(fragment).or_default().insert(top_level.clone());
+        }
+    }
+
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn bare_page_inputs_include_nested_includes() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        fs::create_dir_all(content_dir.join("parts"))?;
+        fs::write(content_dir.join("_index.yaml"), "author: Sam\n")?;
+        fs::write(
+            content_dir.join("about.md"),
+            "# About\n```\n#include \"ignored.md\"\n```\n#include \"parts/footer.md\"\n",
+        )?;
+        fs::write(
+            content_dir.join("parts/footer.md"),
+            "Bye\n#include \"sign.md\"\n",
+        )?;
+        fs::write(content_dir.join("parts/sign.md"), "Sam\n")?;
+        let config = Config {
+            content_dir: content_dir.clone(),
+            ..Default::default()
+        };
+
+        let inputs = content_inputs(&content_dir.join("about.md"), &config)?;
+
+        assert_eq!(
+            inputs,
+            [
+                content_dir.join("_index.yaml"),
+                content_dir.join("about.md"),
+                content_dir.join("parts/footer.md"),
+                content_dir.join("parts/sign.md"),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn include_graph_matches_fragments_reached_through_dot_dot() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        fs::create_dir_all(content_dir.join("blog"))?;
+        fs::write(content_dir.join("shared.md"), "Shared\n")?;
+        fs::write(content_dir.join("one.md"), "#include \"./shared.md\"\n")?;
+        fs::write(
+            content_dir.join("blog/two.md"),
+            "#include \"../shared.md\"\n",
+        )?;
+
+        let graph = include_graph(&content_dir)?;
+
+        assert_eq!(
+            graph,
+            BTreeMap::from([(
+                content_dir.join("shared.md"),
+                BTreeSet::from([content_dir.join("blog/two.md"), content_dir.join("one.md")]),
+            )])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn include_graph_lists_every_page_including_a_fragment() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        fs::create_dir_all(content_dir.join("parts"))?;
+        fs::write(content_dir.join("one.md"), "#include \"parts/shared.md\"\n")?;
+        fs::write(content_dir.join("two.md"), "#include \"parts/shared.md\"\n")?;
+        fs::write(content_dir.join("three.md"), "# No includes\n")?;
+        fs::write(content_dir.join("parts/shared.md"), "Shared\n")?;
+
+        let graph = include_graph(&content_dir)?;
+
+        assert_eq!(
+            graph,
+            BTreeMap::from([(
+                content_dir.join("parts/shared.md"),
+                BTreeSet::from([content_dir.join("one.md"), content_dir.join("two.md")]),
+            )])
+        );
+
+        Ok(())
+    }
+}
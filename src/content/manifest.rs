@@ -0,0 +1,139 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use super::metadata::ContentMetadata;
+use super::sort::{sort_content_items_by, SortBy};
+
+/// Name of the manifest that fixes the order of the content below it.
+pub const ORDER_FILE: &str = "order.yaml";
+
+/// What to do with content that an `order.yaml` does not list.
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UnlistedItems {
+    /// Keep them after the listed items, in the usual list order.
+    #[default]
+    Append,
+    /// Leave them out.
+    Exclude,
+}
+
+/// An `order.yaml` manifest, e.g. the chapters of a book:
+///
+/// ```yaml
+/// items:
+///   - intro
+///   - part1/basics
+/// unlisted: exclude
+/// ```
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct OrderManifest {
+    /// Content paths in reading order, relative to the manifest.
+    pub items: Vec<PathBuf>,
+    #[serde(default)]
+    pub unlisted: UnlistedItems,
+}
+
+impl OrderManifest {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let yaml = fs::read_to_string(path)?;
+        serde_yaml::from_str(&yaml).map_err(|e| format!("Invalid {}: {}", path.display(), e).into())
+    }
+
+    /// Load the manifest in `dir`, if there is one.
+    pub fn find(dir: &Path) -> Result<Option<Self>, Box<dyn Error>> {
+        let path = dir.join(ORDER_FILE);
+        if path.is_file() {
+            Self::load(&path).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Arrange `content_items` found below `base_dir` in manifest order.
+    pub fn apply(
+        &self,
+        base_dir: &Path,
+        content_items: Vec<ContentMetadata>,
+    ) -> Vec<ContentMetadata> {
+        self.apply_sorted(base_dir, content_items, SortBy::default())
+    }
+
+    /// Like [`OrderManifest::apply`], ordering appended unlisted items by
+    /// `sort_by`.
+    pub fn apply_sorted(
+        &self,
+        base_dir: &Path,
+        content_items: Vec<ContentMetadata>,
+        sort_by: SortBy,
+    ) -> Vec<ContentMetadata> {
+        let mut remaining = content_items;
+        let mut ordered = Vec::with_capacity(remaining.len());
+
+        for entry in &self.items {
+            let path = base_dir.join(entry);
+            if let Some(index) = remaining.iter().position(|item| item.source_path == path) {
+                ordered.push(remaining.remove(index));
+            }
+        }
+
+        if self.unlisted == UnlistedItems::Append {
+            sort_content_items_by(&mut remaining, sort_by);
+            ordered.extend(remaining);
+        }
+        ordered
+    }
+}
+
+/// Put the items found below `base_dir` in list order: the order of its
+/// `order.yaml` when present, otherwise newest first by `sort_by`.
+pub fn order_content_items(
+    base_dir: &Path,
+    mut content_items: Vec<ContentMetadata>,
+    sort_by: SortBy,
+) -> Result<Vec<ContentMetadata>, Box<dyn Error>> {
+    match OrderManifest::find(base_dir)? {
+        Some(manifest) => Ok(manifest.apply_sorted(base_dir, content_items, sort_by)),
+        None => {
+            sort_content_items_by(&mut content_items, sort_by);
+            Ok(content_items)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(base_dir: &Path, name: &str) -> ContentMetadata {
+        ContentMetadata {
+            title: name.to_string(),
+            source_path: base_dir.join(name),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn unlisted_items_are_appended_or_excluded() {
+        let base_dir = Path::new("content/book");
+        let items = || {
+            vec![
+                item(base_dir, "a"),
+                item(base_dir, "b"),
+                item(base_dir, "c"),
+            ]
+        };
+        let mut manifest: OrderManifest = serde_yaml::from_str("items: [c, a]").unwrap();
+
+        let titles = |items: Vec<ContentMetadata>| -> Vec<String> {
+            items.into_iter().map(|item| item.title).collect()
+        };
+
+        assert_eq!(titles(manifest.apply(base_dir, items())), ["c", "a", "b"]);
+        manifest.unlisted = UnlistedItems::Exclude;
+        assert_eq!(titles(manifest.apply(base_dir, items())), ["c", "a"]);
+    }
+}
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::error::Error;
 use std::fs;
@@ -7,10 +8,29 @@ use chrono::DateTime;
 use serde::{Deserialize, Serialize};
 
 use crate::config::Config;
+use crate::formatted_text::Theorem;
 
-use super::content::{content_output_path, content_url};
+use super::content::{content_output_path, output_url, slug_output_path};
+use super::dates::deserialize_optional_date;
+use super::urls::absolute_url;
 
 const METADATA_FILE: &str = "metadata.yaml";
+const INHERITED_METADATA_FILE: &str = "_index.yaml";
+/// Keys that identify or describe a single item and are never inherited,
+/// including the aliases of `redirect_from`.
+const NON_INHERITED_KEYS: [&str; 11] = [
+    "type",
+    "id",
+    "slug",
+    "canonical",
+    "redirect_from",
+    "aliases",
+    "redirects",
+    "description",
+    "excerpt",
+    "image",
+    "title",
+];
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "lowercase")]
@@ -22,15 +42,68 @@ pub enum ContentKind {
     Unknown,
 }
 
-#[derive(Deserialize, Serialize, Debug, PartialEq, Default)]
+/// How hard a problem is, ordered from easy to hard.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
 pub struct ContentMetadata {
+    #[serde(default)]
     pub title: String,
-    pub author: Option<String>,
+    /// Written as `author: Name` or as a list, `author: [Ann, Bob]`
+    /// (also accepted as `authors`). Serialized as both `authors` and, for
+    /// templates written for a single author, `author` (the first one).
+    #[serde(
+        flatten,
+        serialize_with = "serialize_authors",
+        deserialize_with = "deserialize_authors"
+    )]
+    pub authors: Vec<String>,
     pub id: Option<String>,
+    /// Output location relative to the build dir, overriding the one
+    /// derived from the content path.
+    pub slug: Option<String>,
     pub tags: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "deserialize_optional_date")]
     pub timestamp: Option<DateTime<chrono::Utc>>,
+    /// When the item was last edited, if after `timestamp`.
+    #[serde(default, deserialize_with = "deserialize_optional_date")]
+    pub updated: Option<DateTime<chrono::Utc>>,
+    /// Solutions stay out of builds made before this time (the build time
+    /// being `SOURCE_DATE_EPOCH` when set).
+    #[serde(default, deserialize_with = "deserialize_optional_date")]
+    pub solutions_visible_after: Option<DateTime<chrono::Utc>>,
     pub image: Option<PathBuf>,
     pub description: Option<String>,
+    /// Preview shown on list pages; derived from the main text when unset.
+    pub excerpt: Option<String>,
+
+    /// Deepest heading level listed in the page's table of contents.
+    pub toc_depth: Option<u8>,
+
+    /// Old paths that should redirect to this item.
+    #[serde(default, alias = "aliases", alias = "redirects")]
+    pub redirect_from: Vec<String>,
+
+    /// Preferred url of the item, e.g. the original of a cross-post.
+    pub canonical: Option<String>,
+
+    /// `Cache-Control` value for the item, exported to `content-index.json`
+    /// and the `_headers` file.
+    pub cache_control: Option<String>,
+
+    /// Unpublished work in progress, skipped by builds and listings.
+    #[serde(default)]
+    pub draft: bool,
+
+    pub difficulty: Option<Difficulty>,
+    /// Position in lists with `sort_by: weight`, lightest first.
+    pub weight: Option<i64>,
 
     #[serde(rename = "type")]
     pub kind: ContentKind,
@@ -38,21 +111,204 @@ pub struct ContentMetadata {
     pub template: Option<String>,
     pub context: Option<HashMap<String, serde_yaml::Value>>,
 
+    /// Theorem-like environments for this item, added to the configured
+    /// `theorems` and replacing those with the same name.
+    pub theorems: Option<Vec<Theorem>>,
+
+    #[serde(skip_deserializing, default)]
+    pub source_path: PathBuf,
     #[serde(skip_deserializing, default)]
     pub output_path: PathBuf,
     #[serde(skip_deserializing, default)]
     pub url: String,
 }
 
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// The keys an item's authors may be given under.
+#[derive(Deserialize)]
+struct AuthorKeys {
+    author: Option<OneOrMany>,
+    authors: Option<OneOrMany>,
+}
+
+fn deserialize_authors<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let keys = AuthorKeys::deserialize(deserializer)?;
+    Ok(match keys.author.or(keys.authors) {
+        Some(OneOrMany::One(author)) => vec![author],
+        Some(OneOrMany::Many(authors)) => authors,
+        None => Vec::new(),
+    })
+}
+
+fn serialize_authors<S>(authors: &[String], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use serde::ser::SerializeMap;
+
+    let mut map = serializer.serialize_map(Some(2))?;
+    map.serialize_entry("author", &authors.first())?;
+    map.serialize_entry("authors", authors)?;
+    map.end()
+}
+
 impl ContentMetadata {
     pub fn load(path: &Path, config: &Config) -> Result<ContentMetadata, Box<dyn Error>> {
         let yaml = read_metadata_yaml(path)?;
-        let mut meta: Self = serde_yaml::from_str(&yaml)?;
+        let mut values = inherited_metadata(path, config)?;
+        merge_yaml_mapping(&mut values, serde_yaml::from_str(&yaml)?);
+        let metadata_path = path.join(METADATA_FILE);
+        let mut meta: Self = serde_yaml::from_value(serde_yaml::Value::Mapping(values))
+            .map_err(|e| format!("Invalid {}: {}", metadata_path.display(), e))?;
+        meta.validate(&metadata_path)?;
 
         attach_output_locations(&mut meta, path, config)?;
+        apply_fallback_image(&mut meta, path, config);
 
         Ok(meta)
     }
+
+    /// Check that the fields required for the item's kind are present.
+    fn validate(&self, metadata_path: &Path) -> Result<(), Box<dyn Error>> {
+        let missing = if self.title.trim().is_empty() {
+            Some("title")
+        } else if self.kind == ContentKind::Problem && self.id.is_none() {
+            Some("id")
+        } else {
+            None
+        };
+
+        match missing {
+            Some(field) => Err(format!(
+                "{}: missing required field `{}` for {:?} content",
+                metadata_path.display(),
+                field,
+                self.kind
+            )
+            .into()),
+            None => Ok(()),
+        }
+    }
+
+    /// `config` with the item's own `theorems` merged in, for rendering its
+    /// texts.
+    pub fn render_config<'a>(&self, config: &'a Config) -> Cow<'a, Config> {
+        let Some(theorems) = &self.theorems else {
+            return Cow::Borrowed(config);
+        };
+
+        let mut merged = config.clone();
+        for theorem in theorems {
+            match merged.theorems.iter_mut().find(|t| t.name == theorem.name) {
+                Some(existing) => *existing = theorem.clone(),
+                None => merged.theorems.push(theorem.clone()),
+            }
+        }
+        Cow::Owned(merged)
+    }
+
+    /// The url for `<link rel="canonical">`: the item's `canonical`, or else
+    /// its own url made absolute against `base_url`.
+    pub fn canonical_url(&self, config: &Config) -> Option<String> {
+        self.canonical
+            .clone()
+            .or_else(|| absolute_url(&self.url, config))
+    }
+
+    /// The first author, for templates written for a single `author`.
+    pub fn author(&self) -> Option<&str> {
+        self.authors.first().map(String::as_str)
+    }
+
+    /// Whether the item is unpublished: marked `draft: true` or stored under
+    /// the configured `drafts_dir`.
+    pub fn is_draft(&self, config: &Config) -> bool {
+        self.draft || is_in_drafts_dir(&self.source_path, config)
+    }
+
+    /// Whether a build should render this item; drafts only render when
+    /// `include_drafts` is set.
+    pub fn should_build(&self, config: &Config, include_drafts: bool) -> bool {
+        include_drafts || !self.is_draft(config)
+    }
+
+    /// Whether solutions may be published at `now`, honoring
+    /// `solutions_visible_after` when it is set.
+    pub fn solutions_visible_at(&self, now: DateTime<chrono::Utc>) -> bool {
+        self.solutions_visible_after
+            .is_none_or(|visible_after| now >= visible_after)
+    }
+}
+
+fn is_in_drafts_dir(path: &Path, config: &Config) -> bool {
+    let Some(drafts_dir) = &config.drafts_dir else {
+        return false;
+    };
+    let absolute = |path: &Path| match std::env::current_dir() {
+        Ok(cwd) => cwd.join(path),
+        Err(_) => path.to_path_buf(),
+    };
+    absolute(path).starts_with(absolute(drafts_dir))
+}
+
+/// Defaults from `_index.yaml` files in the ancestors of `path`, up to and
+/// including the content (or drafts) dir; nearer files win.
+fn inherited_metadata(path: &Path, config: &Config) -> Result<serde_yaml::Mapping, Box<dyn Error>> {
+    let index_files = inherited_metadata_files(path, config)?;
+
+    let mut values = serde_yaml::Mapping::new();
+    for index_file in index_files.iter().rev() {
+        let yaml = fs::read_to_string(index_file)?;
+        let defaults: serde_yaml::Value = serde_yaml::from_str(&yaml)
+            .map_err(|e| format!("Invalid {}: {}", index_file.display(), e))?;
+        merge_yaml_mapping(&mut values, defaults);
+    }
+    for key in NON_INHERITED_KEYS {
+        values.remove(key);
+    }
+
+    Ok(values)
+}
+
+/// The `_index.yaml` files that `path` inherits from, nearest first.
+pub(super) fn inherited_metadata_files(
+    path: &Path,
+    config: &Config,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let cwd = std::env::current_dir()?;
+    let roots: Vec<PathBuf> = std::iter::once(&config.content_dir)
+        .chain(&config.drafts_dir)
+        .map(|dir| cwd.join(dir))
+        .collect();
+
+    let mut index_files = Vec::new();
+    for ancestor in path.ancestors().skip(1) {
+        let absolute = cwd.join(ancestor);
+        if !roots.iter().any(|root| absolute.starts_with(root)) {
+            break;
+        }
+        let index_file = ancestor.join(INHERITED_METADATA_FILE);
+        if index_file.is_file() {
+            index_files.push(index_file);
+        }
+    }
+
+    Ok(index_files)
+}
+
+fn merge_yaml_mapping(values: &mut serde_yaml::Mapping, overrides: serde_yaml::Value) {
+    if let serde_yaml::Value::Mapping(overrides) = overrides {
+        values.extend(overrides);
+    }
 }
 
 fn read_metadata_yaml(path: &Path) -> Result<String, Box<dyn Error>> {
@@ -64,11 +320,40 @@ fn attach_output_locations(
     path: &Path,
     config: &Config,
 ) -> Result<(), Box<dyn Error>> {
-    metadata.output_path = content_output_path(path, config)?;
-    metadata.url = content_url(path, config)?;
+    metadata.source_path = path.to_path_buf();
+    metadata.output_path = match &metadata.slug {
+        Some(slug) => slug_output_path(slug, config)?,
+        None => content_output_path(path, config)?,
+    };
+    metadata.url = output_url(&metadata.output_path, config)?;
     Ok(())
 }
 
+/// Replace a declared `image` that does not exist next to the content with
+/// the configured `fallback_image`, warning about the missing file.
+fn apply_fallback_image(metadata: &mut ContentMetadata, path: &Path, config: &Config) {
+    let Some(image) = &metadata.image else {
+        return;
+    };
+    if is_url_or_rooted(image) || path.join(image).exists() {
+        return;
+    }
+
+    crate::logging::warn(format_args!(
+        "Image {} of {} does not exist",
+        image.display(),
+        path.display()
+    ));
+    if let Some(fallback) = &config.fallback_image {
+        metadata.image = Some(PathBuf::from(fallback));
+    }
+}
+
+fn is_url_or_rooted(image: &Path) -> bool {
+    let image = image.to_string_lossy();
+    image.starts_with('/') || image.contains("://")
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::test::get_test_config;
@@ -105,6 +390,288 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parses_updated_timestamp() {
+        let metadata: ContentMetadata = serde_yaml::from_str(
+            "title: Post\ntype: blog\ntimestamp: 2025-01-01T00:00:00Z\nupdated: 2025-02-03T04:05:06Z\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            metadata.updated,
+            Some("2025-02-03T04:05:06Z".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn load_names_file_with_invalid_timestamp() {
+        let (path, err) = load_error("title: Post\ntype: blog\ntimestamp: soon\n");
+
+        assert!(err.contains(&path.display().to_string()), "{err}");
+        assert!(err.contains("invalid date \"soon\""), "{err}");
+    }
+
+    #[test]
+    fn parses_date_only_timestamp() {
+        let metadata: ContentMetadata =
+            serde_yaml::from_str("title: Post\ntype: blog\ntimestamp: 2025-03-06\n").unwrap();
+
+        assert_eq!(
+            metadata.timestamp,
+            Some("2025-03-06T00:00:00Z".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn solutions_are_withheld_until_visible_after() {
+        let metadata: ContentMetadata = serde_yaml::from_str(
+            "title: Contest\ntype: problem\nsolutions_visible_after: 2025-06-01T00:00:00Z\n",
+        )
+        .unwrap();
+        let before = "2025-05-31T23:59:59Z".parse().unwrap();
+        let after = "2025-06-01T00:00:00Z".parse().unwrap();
+
+        assert!(!metadata.solutions_visible_at(before));
+        assert!(metadata.solutions_visible_at(after));
+        assert!(ContentMetadata::default().solutions_visible_at(before));
+    }
+
+    #[test]
+    fn parses_draft_flag() {
+        let metadata: ContentMetadata =
+            serde_yaml::from_str("title: WIP\ntype: blog\ndraft: true\n").unwrap();
+
+        assert!(metadata.draft);
+        assert!(!ContentMetadata::default().draft);
+    }
+
+    #[test]
+    fn slug_overrides_output_location() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::write(
+            temp_dir.path().join(METADATA_FILE),
+            "title: Post\ntype: blog\nslug: posts/hello/\n",
+        )
+        .unwrap();
+
+        let metadata = ContentMetadata::load(temp_dir.path(), &get_test_config()).unwrap();
+
+        assert_eq!(
+            metadata.output_path,
+            Path::new("build/posts/hello/index.html")
+        );
+        assert_eq!(metadata.url, "/posts/hello/index.html");
+    }
+
+    #[test]
+    fn rejects_slug_outside_build_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::write(
+            temp_dir.path().join(METADATA_FILE),
+            "title: Post\ntype: blog\nslug: ../etc\n",
+        )
+        .unwrap();
+
+        assert!(ContentMetadata::load(temp_dir.path(), &get_test_config()).is_err());
+    }
+
+    #[test]
+    fn drafts_dir_content_only_builds_with_include_drafts() {
+        let config = Config {
+            drafts_dir: Some(PathBuf::from("drafts")),
+            ..get_test_config()
+        };
+        let draft = ContentMetadata {
+            source_path: PathBuf::from("drafts/blog/wip"),
+            ..Default::default()
+        };
+        let published = ContentMetadata {
+            source_path: PathBuf::from("src/test_assets/blog/done"),
+            ..Default::default()
+        };
+
+        assert!(draft.is_draft(&config));
+        assert!(!draft.should_build(&config, false));
+        assert!(draft.should_build(&config, true));
+        assert!(published.should_build(&config, false));
+    }
+
+    #[test]
+    fn missing_image_falls_back_to_placeholder() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::write(
+            temp_dir.path().join(METADATA_FILE),
+            "title: Post\ntype: blog\nslug: post\nimage: cover.png\n",
+        )
+        .unwrap();
+        let config = Config {
+            fallback_image: Some("/static/placeholder.png".to_string()),
+            ..get_test_config()
+        };
+
+        let metadata = ContentMetadata::load(temp_dir.path(), &config).unwrap();
+        assert_eq!(
+            metadata.image,
+            Some(PathBuf::from("/static/placeholder.png"))
+        );
+
+        fs::write(temp_dir.path().join("cover.png"), "").unwrap();
+        let metadata = ContentMetadata::load(temp_dir.path(), &config).unwrap();
+        assert_eq!(metadata.image, Some(PathBuf::from("cover.png")));
+    }
+
+    #[test]
+    fn inherits_defaults_from_parent_index() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempfile::tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let problem_dir = content_dir.join("olympiad/p1");
+        fs::create_dir_all(&problem_dir)?;
+        fs::write(
+            content_dir.join("_index.yaml"),
+            "author: Site Author\ntemplate: base.html\n",
+        )?;
+        fs::write(
+            content_dir.join("olympiad/_index.yaml"),
+            "author: Jane Doe\ntags: [olympiad]\ntype: blog\n",
+        )?;
+        fs::write(
+            problem_dir.join(METADATA_FILE),
+            "title: P1\nid: p1\ntype: problem\ntemplate: problem.html\n",
+        )?;
+        let config = Config {
+            content_dir,
+            build_dir: temp_dir.path().join("build"),
+            ..Default::default()
+        };
+
+        let metadata = ContentMetadata::load(&problem_dir, &config)?;
+
+        assert_eq!(metadata.author(), Some("Jane Doe"));
+        assert_eq!(metadata.tags, Some(vec!["olympiad".to_string()]));
+        assert_eq!(metadata.template.as_deref(), Some("problem.html"));
+        assert_eq!(metadata.kind, ContentKind::Problem);
+
+        Ok(())
+    }
+
+    #[test]
+    fn does_not_inherit_item_specific_keys() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempfile::tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let post_dir = content_dir.join("blog/post");
+        fs::create_dir_all(&post_dir)?;
+        fs::write(
+            content_dir.join("blog/_index.yaml"),
+            concat!(
+                "title: Blog\n",
+                "canonical: https://example.com/blog/\n",
+                "aliases: [/old-blog/]\n",
+                "description: All posts\n",
+                "excerpt: Posts\n",
+                "image: cover.png\n",
+                "tags: [blog]\n",
+            ),
+        )?;
+        fs::write(post_dir.join(METADATA_FILE), "title: Post\ntype: blog\n")?;
+        let config = Config {
+            content_dir,
+            build_dir: temp_dir.path().join("build"),
+            ..Default::default()
+        };
+
+        let metadata = ContentMetadata::load(&post_dir, &config)?;
+
+        assert_eq!(metadata.title, "Post");
+        assert_eq!(metadata.canonical, None);
+        assert!(metadata.redirect_from.is_empty());
+        assert_eq!(metadata.description, None);
+        assert_eq!(metadata.excerpt, None);
+        assert_eq!(metadata.image, None);
+        assert_eq!(metadata.tags, Some(vec!["blog".to_string()]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn author_accepts_a_name_or_a_list() -> Result<(), Box<dyn Error>> {
+        let single: ContentMetadata =
+            serde_yaml::from_str("title: Post\ntype: blog\nauthor: Ann\n")?;
+        assert_eq!(single.authors, ["Ann"]);
+
+        let several: ContentMetadata =
+            serde_yaml::from_str("title: Post\ntype: blog\nauthor: [Ann, Bob]\n")?;
+        assert_eq!(several.authors, ["Ann", "Bob"]);
+        assert_eq!(several.author(), Some("Ann"));
+
+        let plural: ContentMetadata =
+            serde_yaml::from_str("title: Post\ntype: blog\nauthors:\n  - Bob\n")?;
+        assert_eq!(plural.authors, ["Bob"]);
+
+        let none: ContentMetadata = serde_yaml::from_str("title: Post\ntype: blog\n")?;
+        assert!(none.authors.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn parses_difficulty() {
+        let metadata: ContentMetadata =
+            serde_yaml::from_str("title: P\ntype: problem\ndifficulty: medium\n").unwrap();
+        assert_eq!(metadata.difficulty, Some(Difficulty::Medium));
+
+        let err = serde_yaml::from_str::<ContentMetadata>(
+            "title: P\ntype: problem\ndifficulty: brutal\n",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown variant `brutal`"));
+    }
+
+    #[test]
+    fn canonical_defaults_to_absolute_url() {
+        let config = Config {
+            base_url: Some("https://example.com".to_string()),
+            ..Default::default()
+        };
+        let mut metadata = ContentMetadata {
+            url: "/blog/post.html".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            metadata.canonical_url(&config).as_deref(),
+            Some("https://example.com/blog/post.html")
+        );
+
+        metadata.canonical = Some("https://elsewhere.org/post".to_string());
+        assert_eq!(
+            metadata.canonical_url(&config).as_deref(),
+            Some("https://elsewhere.org/post")
+        );
+    }
+
+    fn load_error(yaml: &str) -> (PathBuf, String) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::write(temp_dir.path().join(METADATA_FILE), yaml).unwrap();
+        let err = ContentMetadata::load(temp_dir.path(), &get_test_config()).unwrap_err();
+        (temp_dir.path().join(METADATA_FILE), err.to_string())
+    }
+
+    #[test]
+    fn problem_without_id_is_rejected() {
+        let (path, err) = load_error("title: P\ntype: problem\n");
+
+        assert!(err.contains(&path.display().to_string()), "{err}");
+        assert!(err.contains("missing required field `id`"), "{err}");
+    }
+
+    #[test]
+    fn page_without_title_is_rejected() {
+        let (path, err) = load_error("type: page\nid: about\n");
+
+        assert!(err.contains(&path.display().to_string()), "{err}");
+        assert!(err.contains("missing required field `title`"), "{err}");
+    }
+
     #[test]
     fn test_metadata_file_not_found() {
         // Create a temporary directory without a metadata file
@@ -2,9 +2,27 @@
 
 #[allow(clippy::module_inception)]
 mod content;
+mod dates;
+mod discovery;
+mod excerpt;
+mod inputs;
+mod manifest;
 mod metadata;
+mod neighbors;
 mod problem;
+mod related;
+mod sort;
 pub mod test;
+mod urls;
 
-pub use content::{content_url, Content};
-pub use metadata::{ContentKind, ContentMetadata};
+pub use content::{content_url, output_url, slug_output_path, source_root, Content, Solution};
+pub use dates::parse_date;
+pub use discovery::{find_content_files, find_content_paths};
+pub use excerpt::{derive_excerpt, fill_excerpt, plain_text};
+pub use inputs::{content_inputs, include_graph};
+pub use manifest::{order_content_items, OrderManifest, UnlistedItems, ORDER_FILE};
+pub use metadata::{ContentKind, ContentMetadata, Difficulty};
+pub use neighbors::{ContentLink, ContentNeighbors};
+pub use related::related_content;
+pub use sort::{sort_content_items, sort_content_items_by, SortBy};
+pub use urls::{absolute_url, join_base_url, TrailingSlash};
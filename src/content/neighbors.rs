@@ -0,0 +1,202 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use super::manifest::OrderManifest;
+use super::metadata::ContentMetadata;
+use super::sort::sort_content_items;
+use crate::config::Config;
+
+/// Title and url of a neighboring content item.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ContentLink {
+    pub title: String,
+    pub url: String,
+}
+
+/// The chronologically adjacent items of a content item: `prev` is the next
+/// older item and `next` the next newer one.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct ContentNeighbors {
+    pub prev: Option<ContentLink>,
+    pub next: Option<ContentLink>,
+}
+
+impl ContentNeighbors {
+    /// Find the neighbors of the item at `source_path` in `items`, which must
+    /// already be in list order (see [`sort_content_items`]), newest first.
+    ///
+    /// Items are matched by source path since ids are optional.
+    pub fn find(items: &[ContentMetadata], source_path: &Path) -> Self {
+        let Self { prev, next } = Self::find_in_sequence(items, source_path);
+        Self {
+            prev: next,
+            next: prev,
+        }
+    }
+
+    /// Like [`ContentNeighbors::find`] for items in reading order, such as
+    /// the chapters listed in an `order.yaml`.
+    pub fn find_in_sequence(items: &[ContentMetadata], source_path: &Path) -> Self {
+        let Some(index) = items
+            .iter()
+            .position(|item| item.source_path == source_path)
+        else {
+            return Self::default();
+        };
+
+        Self {
+            prev: index
+                .checked_sub(1)
+                .and_then(|earlier| items.get(earlier))
+                .map(ContentLink::from),
+            next: items.get(index + 1).map(ContentLink::from),
+        }
+    }
+
+    /// Neighbors of `metadata` in the nearest enclosing `order.yaml` within
+    /// the content dir, or else among the items of the same kind that sit
+    /// next to it, as `ssg-list` would list them.
+    ///
+    /// `items` are every published item of the content dir, as returned by
+    /// [`find_content_files`](super::find_content_files); they are loaded
+    /// once and shared by all the items of a build.
+    pub fn load(
+        metadata: &ContentMetadata,
+        items: &[ContentMetadata],
+        config: &Config,
+    ) -> Result<Self, Box<dyn Error>> {
+        let Some(parent) = metadata.source_path.parent() else {
+            return Ok(Self::default());
+        };
+        let items_below = |dir: &Path| -> Vec<ContentMetadata> {
+            items
+                .iter()
+                .filter(|item| item.kind == metadata.kind && item.source_path.starts_with(dir))
+                .cloned()
+                .collect()
+        };
+
+        if let Some((manifest_dir, manifest)) = enclosing_manifest(parent, config)? {
+            let items = manifest.apply(&manifest_dir, items_below(&manifest_dir));
+            return Ok(Self::find_in_sequence(&items, &metadata.source_path));
+        }
+
+        let mut siblings = items_below(parent);
+        sort_content_items(&mut siblings);
+
+        Ok(Self::find(&siblings, &metadata.source_path))
+    }
+}
+
+fn enclosing_manifest(
+    dir: &Path,
+    config: &Config,
+) -> Result<Option<(PathBuf, OrderManifest)>, Box<dyn Error>> {
+    let cwd = std::env::current_dir()?;
+    let content_dir = cwd.join(&config.content_dir);
+
+    for ancestor in dir.ancestors() {
+        if !cwd.join(ancestor).starts_with(&content_dir) {
+            break;
+        }
+        if let Some(manifest) = OrderManifest::find(ancestor)? {
+            return Ok(Some((ancestor.to_path_buf(), manifest)));
+        }
+    }
+
+    Ok(None)
+}
+
+impl From<&ContentMetadata> for ContentLink {
+    fn from(metadata: &ContentMetadata) -> Self {
+        Self {
+            title: metadata.title.clone(),
+            url: metadata.url.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dated_item(name: &str, timestamp: &str) -> ContentMetadata {
+        ContentMetadata {
+            title: name.to_string(),
+            timestamp: Some(timestamp.parse().unwrap()),
+            source_path: Path::new("content/blog").join(name),
+            url: format!("/blog/{name}.html"),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn middle_item_links_to_older_and_newer_items() {
+        let mut items = vec![
+            dated_item("second", "2025-02-01T00:00:00Z"),
+            dated_item("third", "2025-03-01T00:00:00Z"),
+            dated_item("first", "2025-01-01T00:00:00Z"),
+        ];
+        sort_content_items(&mut items);
+
+        let neighbors = ContentNeighbors::find(&items, Path::new("content/blog/second"));
+
+        assert_eq!(neighbors.prev.unwrap().url, "/blog/first.html");
+        assert_eq!(neighbors.next.unwrap().url, "/blog/third.html");
+    }
+
+    #[test]
+    fn manifest_order_drives_prev_and_next() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempfile::tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let book_dir = content_dir.join("book");
+        for (name, title) in [
+            ("intro", "A Intro"),
+            ("basics", "C Basics"),
+            ("advanced", "B Advanced"),
+        ] {
+            std::fs::create_dir_all(book_dir.join(name))?;
+            std::fs::write(
+                book_dir.join(name).join("metadata.yaml"),
+                format!("title: {title}\ntype: page\n"),
+            )?;
+        }
+        std::fs::write(
+            book_dir.join("order.yaml"),
+            "items: [intro, basics, advanced]\n",
+        )?;
+        let config = Config {
+            content_dir,
+            build_dir: temp_dir.path().join("build"),
+            ..Default::default()
+        };
+
+        let basics = ContentMetadata::load(&book_dir.join("basics"), &config)?;
+        let items = crate::content::find_content_files(&config.content_dir, None, &config)?;
+        let neighbors = ContentNeighbors::load(&basics, &items, &config)?;
+
+        assert_eq!(neighbors.prev.unwrap().title, "A Intro");
+        assert_eq!(neighbors.next.unwrap().title, "B Advanced");
+
+        Ok(())
+    }
+
+    #[test]
+    fn first_and_last_items_have_one_neighbor() {
+        let mut items = vec![
+            dated_item("first", "2025-01-01T00:00:00Z"),
+            dated_item("second", "2025-02-01T00:00:00Z"),
+        ];
+        sort_content_items(&mut items);
+
+        let oldest = ContentNeighbors::find(&items, Path::new("content/blog/first"));
+        let newest = ContentNeighbors::find(&items, Path::new("content/blog/second"));
+
+        assert_eq!(oldest.prev, None);
+        assert_eq!(oldest.next.unwrap().title, "second");
+        assert_eq!(newest.prev.unwrap().title, "first");
+        assert_eq!(newest.next, None);
+    }
+}
@@ -1,22 +1,28 @@
-use super::content::Content;
+use super::content::{Content, Solution};
 use super::metadata::*;
 use crate::formatted_text::FormattedText;
 use regex::Regex;
 use std::error::Error;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 const PROBLEM_FILE_BASENAME: &str = "problem";
 const SOLUTION_FILE_BASENAME: &str = "solution";
 const HINT_FILE_BASENAME: &str = "hint";
 
+static TITLE_LINE_RE: OnceLock<Regex> = OnceLock::new();
+
 pub fn load_problem(
     base_path: &Path,
     metadata: ContentMetadata,
     config: &crate::config::Config,
 ) -> Result<Content, Box<dyn Error>> {
     let problem = load_problem_statement(base_path, config)?;
-    let solutions = load_multiple_files(base_path, SOLUTION_FILE_BASENAME, config)?;
+    let solutions = load_multiple_files(base_path, SOLUTION_FILE_BASENAME, config)?
+        .into_iter()
+        .map(split_solution_title)
+        .collect();
     let hints = load_multiple_files(base_path, HINT_FILE_BASENAME, config)?;
 
     Ok(Content::Problem {
@@ -57,7 +63,12 @@ fn load_formatted_file(
         Some("md") => {
             FormattedText::Markdown(super::content::load_markdown_file(file_path, config)?)
         }
-        Some("tex") => FormattedText::Latex(fs::read_to_string(file_path)?),
+        Some("tex") => FormattedText::read_latex(file_path)?,
+        Some("typ") => FormattedText::Typst {
+            source: fs::read_to_string(file_path)?,
+            root: file_path.parent().map(Path::to_path_buf),
+        },
+        Some("org") => FormattedText::Org(fs::read_to_string(file_path)?),
         _ => return Err("Unsupported file extension".into()),
     };
     Ok(content)
@@ -78,6 +89,43 @@ fn load_multiple_files(
     Ok(result)
 }
 
+/// Take the title from a first line such as `<!-- title: Greedy -->` (or
+/// `% title: ...` in LaTeX, `// title: ...` in Typst, `#+title: ...` in
+/// org-mode) off the text.
+fn split_solution_title(text: FormattedText) -> Solution {
+    let source = text.source();
+    let (first_line, rest) = source.split_once('\n').unwrap_or((source, ""));
+    let Some(title) = title_line_regex()
+        .captures(first_line)
+        .and_then(|caps| caps.iter().skip(1).flatten().next())
+        .map(|title| title.as_str().to_string())
+    else {
+        return Solution { title: None, text };
+    };
+
+    let rest = rest.to_string();
+    let text = match text {
+        FormattedText::Latex(_) => FormattedText::Latex(rest),
+        FormattedText::Markdown(_) => FormattedText::Markdown(rest),
+        FormattedText::Html(_) => FormattedText::Html(rest),
+        FormattedText::Typst { root, .. } => FormattedText::Typst { source: rest, root },
+        FormattedText::Org(_) => FormattedText::Org(rest),
+    };
+    Solution {
+        title: Some(title),
+        text,
+    }
+}
+
+fn title_line_regex() -> &'static Regex {
+    TITLE_LINE_RE.get_or_init(|| {
+        Regex::new(
+            r"(?i)^\s*(?:<!--\s*title:\s*(.+?)\s*-->|%\s*title:\s*(.+?)|//\s*title:\s*(.+?)|#\+title:\s*(.+?))\s*$",
+        )
+        .expect("valid title line regex")
+    })
+}
+
 fn collect_numbered_files(
     base_path: &Path,
     basename: &str,
@@ -102,7 +150,10 @@ fn collect_numbered_files(
 }
 
 fn numbered_file_regex(basename: &str) -> Result<Regex, Box<dyn Error>> {
-    let pattern = format!(r"^{}(?:\.(\d+))?\.(tex|md)$", regex::escape(basename));
+    let pattern = format!(
+        r"^{}(?:\.(\d+))?\.(tex|md|typ|org)$",
+        regex::escape(basename)
+    );
     Ok(Regex::new(&pattern)?)
 }
 
@@ -112,6 +163,7 @@ mod tests {
 
     use super::super::test::get_test_config;
     use super::*;
+    use crate::render::Translations;
     use std::path::Path;
 
     #[test]
@@ -150,7 +202,9 @@ mod tests {
 
             // Verify solutions
             assert_eq!(solutions.len(), 1);
+            assert_eq!(solutions[0].title, None);
             let solution_html = solutions[0]
+                .text
                 .to_html(&config)
                 .expect("Failed to convert solution to HTML");
             assert!(solution_html.contains("<p>Some Solution</p>"));
@@ -258,4 +312,46 @@ type: "problem"
             panic!("Expected Markdown");
         }
     }
+
+    #[test]
+    fn solutions_take_titles_from_their_first_line() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempfile::tempdir()?;
+        let problem_dir = temp_dir.path();
+        fs::write(problem_dir.join("problem.md"), "Problem")?;
+        fs::write(
+            problem_dir.join("solution.1.md"),
+            "<!-- title: Solution by DP -->\nFill the table.\n",
+        )?;
+        fs::write(
+            problem_dir.join("solution.2.tex"),
+            "% title: Greedy approach\nTake the largest.\n",
+        )?;
+        fs::write(problem_dir.join("solution.3.md"), "No title.\n")?;
+
+        let content = load_problem(problem_dir, ContentMetadata::default(), &Config::default())?;
+
+        let Content::Problem { solutions, .. } = content else {
+            panic!("Expected Content::Problem");
+        };
+        let labeled: Vec<(String, &str)> = solutions
+            .iter()
+            .enumerate()
+            .map(|(i, solution)| {
+                (
+                    solution.label(i, &Translations::default()),
+                    solution.text.source(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            labeled,
+            [
+                ("Solution by DP".to_string(), "Fill the table.\n"),
+                ("Greedy approach".to_string(), "Take the largest.\n"),
+                ("Solution 3".to_string(), "No title.\n"),
+            ]
+        );
+
+        Ok(())
+    }
 }
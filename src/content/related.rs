@@ -0,0 +1,82 @@
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+use super::metadata::ContentMetadata;
+use super::neighbors::ContentLink;
+
+/// Up to `limit` items of `items` sharing the most tags with `item`, ties
+/// going to the newer item. Items without a shared tag, and `item` itself,
+/// are left out.
+pub fn related_content(
+    items: &[ContentMetadata],
+    item: &ContentMetadata,
+    limit: usize,
+) -> Vec<ContentLink> {
+    let tags: HashSet<&String> = item.tags.iter().flatten().collect();
+    if tags.is_empty() || limit == 0 {
+        return Vec::new();
+    }
+
+    let mut scored: Vec<(usize, &ContentMetadata)> = items
+        .iter()
+        .filter(|other| other.source_path != item.source_path)
+        .map(|other| {
+            let shared: HashSet<&String> = other
+                .tags
+                .iter()
+                .flatten()
+                .filter(|tag| tags.contains(tag))
+                .collect();
+            (shared.len(), other)
+        })
+        .filter(|(score, _)| *score > 0)
+        .collect();
+    scored.sort_by_key(|(score, other)| (Reverse(*score), Reverse(other.timestamp)));
+
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, other)| ContentLink::from(other))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tagged_item(name: &str, tags: &[&str], timestamp: &str) -> ContentMetadata {
+        ContentMetadata {
+            title: name.to_string(),
+            tags: Some(tags.iter().map(|tag| tag.to_string()).collect()),
+            timestamp: Some(timestamp.parse().unwrap()),
+            source_path: format!("content/blog/{name}").into(),
+            url: format!("/blog/{name}.html"),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn ranks_by_shared_tags_then_recency() {
+        let items = vec![
+            tagged_item(
+                "current",
+                &["rust", "math", "graphs"],
+                "2024-01-01T00:00:00Z",
+            ),
+            tagged_item("one-shared-old", &["rust"], "2023-01-01T00:00:00Z"),
+            tagged_item(
+                "one-shared-new",
+                &["math", "travel"],
+                "2024-06-01T00:00:00Z",
+            ),
+            tagged_item("two-shared", &["rust", "graphs"], "2022-01-01T00:00:00Z"),
+            tagged_item("unrelated", &["travel"], "2024-07-01T00:00:00Z"),
+        ];
+
+        let related = related_content(&items, &items[0], 2);
+
+        let titles: Vec<&str> = related.iter().map(|link| link.title.as_str()).collect();
+        assert_eq!(titles, ["two-shared", "one-shared-new"]);
+        assert!(related_content(&items, &ContentMetadata::default(), 2).is_empty());
+    }
+}
@@ -0,0 +1,146 @@
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::metadata::ContentMetadata;
+
+/// What orders list pages.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortBy {
+    /// When the item was first published.
+    #[default]
+    Timestamp,
+    /// When the item was last updated, or its timestamp if never updated.
+    Updated,
+    /// Easiest problems first, newest first within a difficulty; items
+    /// without a difficulty come last.
+    Difficulty,
+    /// Lightest `weight` first, then by title; unweighted items come last.
+    Weight,
+}
+
+impl SortBy {
+    fn date(self, metadata: &ContentMetadata) -> Option<&DateTime<Utc>> {
+        match self {
+            SortBy::Updated => metadata.updated.as_ref().or(metadata.timestamp.as_ref()),
+            SortBy::Timestamp | SortBy::Difficulty | SortBy::Weight => metadata.timestamp.as_ref(),
+        }
+    }
+}
+
+/// Sort items newest first, falling back to titles for undated items.
+pub fn sort_content_items(content_items: &mut [ContentMetadata]) {
+    sort_content_items_by(content_items, SortBy::default());
+}
+
+/// Like [`sort_content_items`], in the order selected by `sort_by`.
+pub fn sort_content_items_by(content_items: &mut [ContentMetadata], sort_by: SortBy) {
+    content_items.sort_by(|a, b| match sort_by {
+        SortBy::Weight => present_first(a.weight, b.weight).then_with(|| a.title.cmp(&b.title)),
+        SortBy::Difficulty => {
+            present_first(a.difficulty, b.difficulty).then_with(|| newest_first(a, b, sort_by))
+        }
+        SortBy::Timestamp | SortBy::Updated => newest_first(a, b, sort_by),
+    });
+}
+
+fn newest_first(a: &ContentMetadata, b: &ContentMetadata, sort_by: SortBy) -> Ordering {
+    match (sort_by.date(a), sort_by.date(b)) {
+        (Some(a_date), Some(b_date)) => b_date.cmp(a_date),
+        _ => a.title.cmp(&b.title),
+    }
+}
+
+/// Ascending order with missing values last.
+fn present_first<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::Difficulty;
+
+    fn item(title: &str, timestamp: &str, updated: Option<&str>) -> ContentMetadata {
+        ContentMetadata {
+            title: title.to_string(),
+            timestamp: Some(timestamp.parse().unwrap()),
+            updated: updated.map(|updated| updated.parse().unwrap()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn sorts_by_updated_date() {
+        let mut items = vec![
+            item(
+                "old but edited",
+                "2024-01-01T00:00:00Z",
+                Some("2025-06-01T00:00:00Z"),
+            ),
+            item("recent", "2025-03-01T00:00:00Z", None),
+            item("oldest", "2023-01-01T00:00:00Z", None),
+        ];
+
+        sort_content_items(&mut items);
+        let titles: Vec<&str> = items.iter().map(|item| item.title.as_str()).collect();
+        assert_eq!(titles, ["recent", "old but edited", "oldest"]);
+
+        sort_content_items_by(&mut items, SortBy::Updated);
+        let titles: Vec<&str> = items.iter().map(|item| item.title.as_str()).collect();
+        assert_eq!(titles, ["old but edited", "recent", "oldest"]);
+    }
+
+    #[test]
+    fn sorts_by_difficulty() {
+        let mut items: Vec<ContentMetadata> = [
+            ("unrated", None, "2025-01-01T00:00:00Z"),
+            ("hard", Some(Difficulty::Hard), "2025-01-01T00:00:00Z"),
+            ("old easy", Some(Difficulty::Easy), "2023-01-01T00:00:00Z"),
+            ("medium", Some(Difficulty::Medium), "2024-01-01T00:00:00Z"),
+            ("new easy", Some(Difficulty::Easy), "2024-06-01T00:00:00Z"),
+        ]
+        .into_iter()
+        .map(|(title, difficulty, timestamp)| ContentMetadata {
+            difficulty,
+            ..item(title, timestamp, None)
+        })
+        .collect();
+
+        sort_content_items_by(&mut items, SortBy::Difficulty);
+
+        let titles: Vec<&str> = items.iter().map(|item| item.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            ["new easy", "old easy", "medium", "hard", "unrated"]
+        );
+    }
+
+    #[test]
+    fn sorts_by_weight_then_title() {
+        let mut items: Vec<ContentMetadata> = [
+            ("Unweighted", None),
+            ("Setup", Some(20)),
+            ("Intro", Some(10)),
+            ("Advanced", Some(20)),
+        ]
+        .into_iter()
+        .map(|(title, weight)| ContentMetadata {
+            weight,
+            ..item(title, "2025-01-01T00:00:00Z", None)
+        })
+        .collect();
+
+        sort_content_items_by(&mut items, SortBy::Weight);
+
+        let titles: Vec<&str> = items.iter().map(|item| item.title.as_str()).collect();
+        assert_eq!(titles, ["Intro", "Advanced", "Setup", "Unweighted"]);
+    }
+}
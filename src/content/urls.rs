@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+/// Whether absolute urls of directory-like paths end with a `/`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrailingSlash {
+    /// Leave urls as they are.
+    #[default]
+    Keep,
+    /// Add a `/` to paths whose last segment has no extension.
+    Always,
+    /// Remove trailing `/`s.
+    Never,
+}
+
+/// Join `base_url` with the site-relative `url`, collapsing duplicate
+/// slashes and applying `trailing_slash`. Urls with a scheme are returned
+/// unchanged.
+pub fn join_base_url(base_url: &str, url: &str, trailing_slash: TrailingSlash) -> String {
+    if url.contains("://") {
+        return url.to_string();
+    }
+
+    let split_at = url.find(['?', '#']).unwrap_or(url.len());
+    let (path, suffix) = url.split_at(split_at);
+    let mut path = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+    let ends_with_slash = path.is_empty() || url[..split_at].ends_with('/');
+
+    match trailing_slash {
+        TrailingSlash::Keep if ends_with_slash && !path.is_empty() => path.push('/'),
+        TrailingSlash::Always if !path.is_empty() && !has_extension(&path) => path.push('/'),
+        _ => {}
+    }
+
+    format!("{}/{}{}", base_url.trim_end_matches('/'), path, suffix)
+}
+
+/// `url` made absolute against the configured `base_url`, if there is one.
+pub fn absolute_url(url: &str, config: &Config) -> Option<String> {
+    config
+        .base_url
+        .as_deref()
+        .map(|base_url| join_base_url(base_url, url, config.trailing_slash))
+}
+
+fn has_extension(path: &str) -> bool {
+    path.rsplit('/')
+        .next()
+        .is_some_and(|segment| segment.contains('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joins_with_and_without_trailing_slashes() {
+        for base_url in ["https://example.com", "https://example.com/"] {
+            for url in ["blog/post.html", "/blog/post.html", "//blog//post.html"] {
+                assert_eq!(
+                    join_base_url(base_url, url, TrailingSlash::Keep),
+                    "https://example.com/blog/post.html",
+                    "{base_url} + {url}"
+                );
+            }
+            assert_eq!(
+                join_base_url(base_url, "/", TrailingSlash::Keep),
+                "https://example.com/"
+            );
+        }
+        assert_eq!(
+            join_base_url(
+                "https://example.com/",
+                "https://cdn.example.com/a.png",
+                TrailingSlash::Keep
+            ),
+            "https://cdn.example.com/a.png"
+        );
+    }
+
+    #[test]
+    fn applies_the_trailing_slash_policy() {
+        let join = |url, policy| join_base_url("https://example.com", url, policy);
+
+        assert_eq!(
+            join("/docs/", TrailingSlash::Keep),
+            "https://example.com/docs/"
+        );
+        assert_eq!(
+            join("/docs", TrailingSlash::Keep),
+            "https://example.com/docs"
+        );
+        assert_eq!(
+            join("/docs?q=1", TrailingSlash::Always),
+            "https://example.com/docs/?q=1"
+        );
+        assert_eq!(
+            join("/docs/index.html", TrailingSlash::Always),
+            "https://example.com/docs/index.html"
+        );
+        assert_eq!(
+            join("/docs/", TrailingSlash::Never),
+            "https://example.com/docs"
+        );
+        assert_eq!(join("/", TrailingSlash::Never), "https://example.com/");
+    }
+}
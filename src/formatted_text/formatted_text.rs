@@ -1,5 +1,8 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use std::time::Duration;
 
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use crate::config::Config;
@@ -9,22 +12,89 @@ use super::{
         preprocess_cards, preprocess_expandables, preprocess_figures, preprocess_semantic_cards,
     },
     markdown_math::{math_shorthand_enabled, preprocess_math_blocks, protect_math, ProtectedMath},
+    mathml::render_math_segment,
     pandoc_latex_filters::{EnvFilter, PandocFilter},
-    shell::run_with_timeout,
+    plain_text::html_to_plain_text,
+    render_cache::{render_cache, ConfigFingerprint, RenderKey},
+    shell::{run_pooled, run_with_timeout},
 };
 
+static LATEX_COMMAND_RE: OnceLock<Regex> = OnceLock::new();
+
 #[derive(Debug, Clone)]
 pub enum FormattedText {
     Latex(String),
     Markdown(String),
     Html(String),
+    /// Typst source, with the directory its relative `#import`s and
+    /// `#image`s are resolved in (the working directory when `None`).
+    Typst {
+        source: String,
+        root: Option<PathBuf>,
+    },
+    Org(String),
+}
+
+/// How math is emitted. `native` renders Markdown math to MathML in
+/// process and has pandoc emit MathML for LaTeX sources.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MathRenderer {
+    #[default]
+    MathJax,
+    MathML,
+    WebTeX,
+    Native,
+}
+
+impl MathRenderer {
+    fn pandoc_flag(self) -> &'static str {
+        match self {
+            MathRenderer::MathJax => "--mathjax",
+            MathRenderer::MathML | MathRenderer::Native => "--mathml",
+            MathRenderer::WebTeX => "--webtex",
+        }
+    }
+}
+
+/// The `markdown` config section: which comrak extensions are on and how
+/// lines are broken. Keys left out keep their default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarkdownOptions {
+    pub tasklist: bool,
+    pub strikethrough: bool,
+    pub table: bool,
+    pub autolink: bool,
+    pub alerts: bool,
+    /// Curly quotes and dashes for `"..."`, `--` and `---`.
+    pub smart: bool,
+    /// Render a single newline inside a paragraph as `<br />`.
+    pub hardbreaks: bool,
+    /// Column to wrap rendered text at; 0 keeps lines as they are.
+    pub width: usize,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            tasklist: true,
+            strikethrough: true,
+            table: true,
+            autolink: true,
+            alerts: true,
+            smart: true,
+            hardbreaks: false,
+            width: 0,
+        }
+    }
 }
 
 fn default_numbered() -> bool {
     true
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Theorem {
     pub name: String,
     pub label: String,
@@ -43,34 +113,322 @@ impl Theorem {
     }
 }
 
+/// File name suffix marking a LaTeX file as a bare math fragment.
+pub const MATH_FRAGMENT_SUFFIX: &str = ".math.tex";
+
 impl FormattedText {
+    /// Read a LaTeX file. A file named `*.math.tex` holds a bare math
+    /// fragment such as `\frac{1}{2}` and is wrapped in math delimiters.
+    pub fn read_latex(path: &Path) -> std::io::Result<FormattedText> {
+        let latex = std::fs::read_to_string(path)?;
+        let is_math_fragment = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(MATH_FRAGMENT_SUFFIX));
+        if is_math_fragment {
+            Ok(FormattedText::Latex(wrap_math_fragment(&latex)))
+        } else {
+            Ok(FormattedText::Latex(latex))
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        match self {
+            FormattedText::Latex(s)
+            | FormattedText::Markdown(s)
+            | FormattedText::Html(s)
+            | FormattedText::Typst { source: s, .. }
+            | FormattedText::Org(s) => s,
+        }
+    }
+
+    /// Render the text to HTML. With `render_cache_size` set, conversions
+    /// already done with the same settings are answered from memory.
     pub fn to_html(&self, config: &Config) -> Result<String, Box<dyn std::error::Error>> {
+        if config.render_cache_size == 0 {
+            return self.render_html(config);
+        }
+        self.to_html_keyed(config, ConfigFingerprint::new(config))
+    }
+
+    /// [`to_html`](Self::to_html) with the fingerprint of `config` computed
+    /// by the caller, for rendering many texts with the same config.
+    pub fn to_html_keyed(
+        &self,
+        config: &Config,
+        fingerprint: ConfigFingerprint,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        if config.render_cache_size == 0 {
+            return self.render_html(config);
+        }
+
+        let key = RenderKey::new(self, fingerprint);
+        if let Some(html) = render_cache().get(&key) {
+            return Ok(html);
+        }
+        let html = self.render_html(config)?;
+        render_cache().insert(key, html.clone(), config.render_cache_size);
+        Ok(html)
+    }
+
+    fn render_html(&self, config: &Config) -> Result<String, Box<dyn std::error::Error>> {
         match self {
-            FormattedText::Latex(s) => latex_to_html(
-                s,
-                &config.theorems,
-                Duration::from_secs(config.pandoc_timeout_seconds),
-            )
-            .map_err(Into::into),
+            FormattedText::Latex(s) => {
+                latex_to_html(s, &LatexOptions::from_config(config)).map_err(Into::into)
+            }
             FormattedText::Markdown(s) => markdown_to_html(s, config).map_err(Into::into),
             FormattedText::Html(s) => Ok(s.clone()),
+            FormattedText::Typst { source, root } => {
+                typst_to_html(source, root.as_deref(), config).map_err(Into::into)
+            }
+            FormattedText::Org(s) => org_to_html(s, config).map_err(Into::into),
+        }
+    }
+
+    /// The prose of the text without markup: Markdown and HTML are rendered
+    /// and stripped of tags, with math reduced to its bare source; LaTeX is
+    /// converted by pandoc.
+    pub fn to_plain_text(&self, config: &Config) -> Result<String, String> {
+        match self {
+            FormattedText::Latex(s) => run_with_timeout(
+                &config.pandoc_bin,
+                &["--from=latex", "--to=plain", "--wrap=none"],
+                Some(s),
+                Duration::from_secs(config.pandoc_timeout_seconds),
+            ),
+            FormattedText::Markdown(s) => Ok(html_to_plain_text(&markdown_to_html(s, config)?)),
+            FormattedText::Html(s) => Ok(html_to_plain_text(s)),
+            FormattedText::Typst { source, root } => Ok(html_to_plain_text(&typst_to_html(
+                source,
+                root.as_deref(),
+                config,
+            )?)),
+            FormattedText::Org(s) => run_with_timeout(
+                &config.pandoc_bin,
+                &["--from=org", "--to=plain", "--wrap=none"],
+                Some(s),
+                Duration::from_secs(config.pandoc_timeout_seconds),
+            ),
         }
     }
+
+    /// Render several texts at once, converting all LaTeX entries with a
+    /// single pandoc run. Results are returned in input order.
+    pub fn to_html_batch(
+        texts: &[&FormattedText],
+        config: &Config,
+    ) -> Vec<Result<String, Box<dyn std::error::Error>>> {
+        let fingerprint = (config.render_cache_size > 0).then(|| ConfigFingerprint::new(config));
+        let cached: Vec<Option<String>> = texts
+            .iter()
+            .map(|text| match (text, fingerprint) {
+                (FormattedText::Latex(_), Some(fingerprint)) => {
+                    render_cache().get(&RenderKey::new(text, fingerprint))
+                }
+                _ => None,
+            })
+            .collect();
+        let latex_sources: Vec<&str> = texts
+            .iter()
+            .zip(&cached)
+            .filter_map(|(text, cached)| match text {
+                FormattedText::Latex(s) if cached.is_none() => Some(s.as_str()),
+                _ => None,
+            })
+            .collect();
+        let mut latex_outputs =
+            latex_batch_to_html(&latex_sources, &LatexOptions::from_config(config)).into_iter();
+
+        texts
+            .iter()
+            .zip(cached)
+            .map(|(text, cached)| match (text, cached) {
+                (FormattedText::Latex(_), Some(html)) => Ok(html),
+                (FormattedText::Latex(_), None) => {
+                    let html = latex_outputs
+                        .next()
+                        .unwrap_or_else(|| Err("Missing batched LaTeX output".to_string()))?;
+                    if let Some(fingerprint) = fingerprint {
+                        render_cache().insert(
+                            RenderKey::new(text, fingerprint),
+                            html.clone(),
+                            config.render_cache_size,
+                        );
+                    }
+                    Ok(html)
+                }
+                _ => match fingerprint {
+                    Some(fingerprint) => text.to_html_keyed(config, fingerprint),
+                    None => text.render_html(config),
+                },
+            })
+            .collect()
+    }
 }
 
-fn latex_to_html(
-    latex: &str,
-    theorems: &[Theorem],
+/// Settings for converting LaTeX through pandoc.
+struct LatexOptions<'a> {
+    theorems: &'a [Theorem],
+    verbatim_environments: &'a [String],
+    math_renderer: MathRenderer,
+    pandoc_bin: &'a str,
     pandoc_timeout: Duration,
-) -> Result<String, String> {
-    let mut filters = latex_filters(theorems);
+    pandoc_pool_size: usize,
+}
+
+impl<'a> LatexOptions<'a> {
+    fn from_config(config: &'a Config) -> Self {
+        Self {
+            theorems: &config.theorems,
+            verbatim_environments: &config.verbatim_environments,
+            math_renderer: config.math_renderer,
+            pandoc_bin: &config.pandoc_bin,
+            pandoc_timeout: Duration::from_secs(config.pandoc_timeout_seconds),
+            pandoc_pool_size: config.pandoc_pool_size,
+        }
+    }
+}
+
+fn latex_to_html(latex: &str, options: &LatexOptions) -> Result<String, String> {
+    let mut filters = latex_filters(options);
     let preprocessed = apply_latex_preprocessors(latex, &mut filters)?;
-    let pandoc_output = run_pandoc_latex(&preprocessed, pandoc_timeout)?;
+    let pandoc_output = run_pandoc_latex(&preprocessed, options)?;
     Ok(apply_latex_postprocessors(&pandoc_output, &mut filters))
 }
 
-fn latex_filters(theorems: &[Theorem]) -> Vec<Box<dyn PandocFilter>> {
-    vec![Box::new(EnvFilter::new(theorems.to_vec()))]
+/// Convert several LaTeX snippets with one pandoc process by joining them
+/// with a separator paragraph and splitting the output afterwards.
+///
+/// Only self-contained snippets (see [`is_self_contained`]) share the run;
+/// the others, and all of them if the batch fails or its output does not
+/// split into one part per snippet, get one pandoc call each, so a broken
+/// snippet only affects its own result.
+fn latex_batch_to_html(
+    latex_sources: &[&str],
+    options: &LatexOptions,
+) -> Vec<Result<String, String>> {
+    let batchable: Vec<&str> = latex_sources
+        .iter()
+        .copied()
+        .filter(|latex| is_self_contained(latex))
+        .collect();
+    let mut batched = if batchable.len() > 1 {
+        try_latex_batch(&batchable, options).map(Vec::into_iter)
+    } else {
+        None
+    };
+
+    latex_sources
+        .iter()
+        .map(|latex| {
+            if is_self_contained(latex) {
+                if let Some(html) = batched.as_mut().and_then(Iterator::next) {
+                    return Ok(html);
+                }
+            }
+            latex_to_html(latex, options)
+        })
+        .collect()
+}
+
+/// Commands whose definitions would carry over to the snippets after them.
+const MACRO_DEFINITIONS: &[&str] = &[
+    r"\newcommand",
+    r"\renewcommand",
+    r"\providecommand",
+    r"\newenvironment",
+    r"\renewenvironment",
+    r"\DeclareMathOperator",
+    r"\def",
+    r"\gdef",
+    r"\edef",
+    r"\let",
+];
+
+/// Whether `latex` can share a pandoc run with other snippets: its
+/// environments and `$` math are closed and it defines no macros, so nothing
+/// in it leaks into its neighbors.
+fn is_self_contained(latex: &str) -> bool {
+    let mut environments = Vec::new();
+    for command in latex_command_regex().find_iter(latex) {
+        let name = latex[command.end()..]
+            .strip_prefix('{')
+            .and_then(|rest| rest.split_once('}'))
+            .map(|(name, _)| name);
+        match command.as_str().trim_end_matches('*') {
+            r"\begin" => environments.push(name),
+            r"\end" if environments.pop() != Some(name) => return false,
+            command if MACRO_DEFINITIONS.contains(&command) => return false,
+            _ => {}
+        }
+    }
+
+    let dollars = latex
+        .char_indices()
+        .filter(|&(i, c)| c == '$' && !latex[..i].ends_with('\\'))
+        .count();
+    environments.is_empty() && dollars % 2 == 0
+}
+
+fn try_latex_batch(latex_sources: &[&str], options: &LatexOptions) -> Option<Vec<String>> {
+    let separator = batch_separator(latex_sources);
+    let mut snippet_filters = Vec::new();
+    let mut preprocessed = Vec::new();
+
+    for latex in latex_sources {
+        let mut filters = latex_filters(options);
+        preprocessed.push(apply_latex_preprocessors(latex, &mut filters).ok()?);
+        snippet_filters.push(filters);
+    }
+
+    let joined = preprocessed.join(&format!("\n\n{separator}\n\n"));
+    let pandoc_output = run_pandoc_latex(&joined, options).ok()?;
+    let parts: Vec<&str> = pandoc_output
+        .split(&format!("<p>{separator}</p>\n"))
+        .collect();
+    if parts.len() != latex_sources.len() {
+        return None;
+    }
+
+    Some(
+        parts
+            .into_iter()
+            .zip(snippet_filters.iter_mut())
+            .map(|(part, filters)| apply_latex_postprocessors(part, filters))
+            .collect(),
+    )
+}
+
+fn batch_separator(latex_sources: &[&str]) -> String {
+    (0..)
+        .map(|n| format!("SSGBATCHSEPARATOR{n}"))
+        .find(|separator| !latex_sources.iter().any(|s| s.contains(separator.as_str())))
+        .expect("unbounded separator candidates")
+}
+
+/// Wrap a bare math snippet (e.g. a reusable `\frac{1}{2}` file) in math
+/// delimiters so pandoc does not drop it as unknown text-mode commands.
+///
+/// Single-line snippets become inline math, multi-line ones display math.
+fn wrap_math_fragment(latex: &str) -> String {
+    let body = latex.trim();
+    if body.contains('\n') {
+        format!("\\[\n{body}\n\\]\n")
+    } else {
+        format!("\\({body}\\)\n")
+    }
+}
+
+fn latex_command_regex() -> &'static Regex {
+    LATEX_COMMAND_RE
+        .get_or_init(|| Regex::new(r"\\[a-zA-Z]+\*?").expect("valid latex command regex"))
+}
+
+fn latex_filters(options: &LatexOptions) -> Vec<Box<dyn PandocFilter>> {
+    vec![Box::new(EnvFilter::new(
+        options.theorems.to_vec(),
+        options.verbatim_environments,
+    ))]
 }
 
 fn apply_latex_preprocessors(
@@ -84,15 +442,61 @@ fn apply_latex_preprocessors(
     Ok(preprocessed)
 }
 
-fn run_pandoc_latex(latex: &str, timeout: Duration) -> Result<String, String> {
-    run_with_timeout(
-        "pandoc",
-        &["--from=latex", "--to=html", "--mathjax"],
-        Some(latex),
-        timeout,
+fn run_pandoc_latex(latex: &str, options: &LatexOptions) -> Result<String, String> {
+    run_pooled(
+        options.pandoc_bin,
+        &[
+            "--from=latex",
+            "--to=html",
+            options.math_renderer.pandoc_flag(),
+        ],
+        latex,
+        options.pandoc_timeout,
+        options.pandoc_pool_size,
+    )
+}
+
+fn org_to_html(org: &str, config: &Config) -> Result<String, String> {
+    run_pooled(
+        &config.pandoc_bin,
+        &[
+            "--from=org",
+            "--to=html",
+            config.math_renderer.pandoc_flag(),
+        ],
+        org,
+        Duration::from_secs(config.pandoc_timeout_seconds),
+        config.pandoc_pool_size,
     )
 }
 
+/// Convert Typst with its (experimental) HTML export, keeping only the
+/// contents of `<body>`. Files the source uses are looked up in `root`.
+fn typst_to_html(typst: &str, root: Option<&Path>, config: &Config) -> Result<String, String> {
+    static BODY_RE: OnceLock<Regex> = OnceLock::new();
+    let mut args = vec!["compile", "--features", "html", "--format", "html"];
+    let root = root
+        .filter(|root| !root.as_os_str().is_empty())
+        .map(|root| root.to_string_lossy());
+    if let Some(root) = &root {
+        args.extend(["--root", root]);
+    }
+    args.extend(["-", "-"]);
+    let html = run_with_timeout(
+        "typst",
+        &args,
+        Some(typst),
+        Duration::from_secs(config.pandoc_timeout_seconds),
+    )?;
+    let body_re = BODY_RE
+        .get_or_init(|| Regex::new(r"(?is)<body[^>]*>(.*)</body>").expect("valid body regex"));
+    Ok(body_re
+        .captures(&html)
+        .map_or(html.as_str(), |caps| caps.get(1).map_or("", |m| m.as_str()))
+        .trim()
+        .to_string())
+}
+
 fn apply_latex_postprocessors(
     pandoc_output: &str,
     filters: &mut [Box<dyn PandocFilter>],
@@ -119,12 +523,34 @@ fn markdown_to_html(markdown: &str, config: &Config) -> Result<String, String> {
     let mut html = render_markdown_with_comrak(markdown, config);
 
     if let Some(protected_math) = protected_math {
-        html = protected_math.restore_html(&html);
+        html = match config.math_renderer {
+            MathRenderer::Native => protected_math.restore_rendered_html(&html, |segment| {
+                let mathml = render_math_segment(segment);
+                if mathml.is_none() {
+                    warn_unrendered_math(segment);
+                }
+                mathml
+            }),
+            _ => protected_math.restore_html(&html),
+        };
+    }
+    if !config.allow_raw_html {
+        html = super::sanitize::sanitize_html(&html);
     }
 
     Ok(html)
 }
 
+/// Warn that `segment` stays TeX, naming the item being rendered if known.
+fn warn_unrendered_math(segment: &str) {
+    let item = crate::logging::current_item()
+        .map(|path| format!("{}: ", path.display()))
+        .unwrap_or_default();
+    crate::logging::warn(format!(
+        "{item}cannot render {segment} as MathML; leaving it as TeX"
+    ));
+}
+
 fn preprocess_markdown(markdown: &str, expand_math_shorthand: bool) -> String {
     let markdown = preprocess_math_blocks(markdown, expand_math_shorthand);
     let markdown = preprocess_figures(&markdown);
@@ -164,7 +590,11 @@ fn protect_markdown_math(
     config: &Config,
     expand_math_shorthand: bool,
 ) -> Option<ProtectedMath> {
-    if config.escape_markdown_in_math {
+    // Sub/superscript markers would otherwise rewrite `^` and `~` inside math.
+    if config.escape_markdown_in_math
+        && !config.markdown_sub_superscript
+        && config.math_renderer != MathRenderer::Native
+    {
         None
     } else {
         Some(protect_math(markdown, expand_math_shorthand))
@@ -172,7 +602,7 @@ fn protect_markdown_math(
 }
 
 fn render_markdown_with_comrak(markdown: &str, config: &Config) -> String {
-    let options = markdown_options();
+    let options = markdown_options(config);
     let mut plugins = comrak::Plugins::default();
     let adapter = comrak::plugins::syntect::SyntectAdapterBuilder::new()
         .theme(config.syntax_highlighter_theme.as_str())
@@ -182,14 +612,21 @@ fn render_markdown_with_comrak(markdown: &str, config: &Config) -> String {
     comrak::markdown_to_html_with_plugins(markdown, &options, &plugins)
 }
 
-fn markdown_options() -> comrak::ComrakOptions<'static> {
+fn markdown_options(config: &Config) -> comrak::ComrakOptions<'static> {
     let mut options = comrak::ComrakOptions::default();
-    options.extension.tasklist = true;
-    options.extension.strikethrough = true;
-    options.extension.table = true;
-    options.extension.autolink = true;
-    options.extension.alerts = true;
-    options.parse.smart = true;
+    options.extension.superscript = config.markdown_sub_superscript;
+    options.extension.subscript = config.markdown_sub_superscript;
+    options.extension.tasklist = config.markdown.tasklist;
+    options.extension.strikethrough = config.markdown.strikethrough;
+    options.extension.table = config.markdown.table;
+    options.extension.autolink = config.markdown.autolink;
+    options.extension.alerts = config.markdown.alerts;
+    options.parse.smart = config.markdown.smart;
+    options.render.hardbreaks = config.markdown.hardbreaks;
+    options.render.width = config.markdown.width;
+    // Directives such as cards and figures expand to HTML before comrak runs,
+    // so raw HTML stays enabled; `allow_raw_html: false` cleans the output
+    // with an allow-list sanitizer instead.
     options.render.unsafe_ = true;
     options
 }
@@ -198,8 +635,19 @@ fn markdown_options() -> comrak::ComrakOptions<'static> {
 mod test_latex_to_html {
     use super::*;
 
+    fn latex_options(theorems: &[Theorem]) -> LatexOptions<'_> {
+        LatexOptions {
+            theorems,
+            verbatim_environments: &[],
+            math_renderer: MathRenderer::default(),
+            pandoc_bin: "pandoc",
+            pandoc_timeout: Duration::from_secs(10),
+            pandoc_pool_size: 0,
+        }
+    }
+
     fn latex_to_html(latex: &str, theorems: &[Theorem]) -> Result<String, String> {
-        super::latex_to_html(latex, theorems, Duration::from_secs(10))
+        super::latex_to_html(latex, &latex_options(theorems))
     }
 
     #[test]
@@ -259,6 +707,68 @@ mod test_latex_to_html {
         assert!(output.contains("<table>"));
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn cached_conversions_do_not_rerun_pandoc() -> Result<(), Box<dyn std::error::Error>> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::tempdir()?;
+        let script = temp_dir.path().join("pandoc");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\n\
+             [ -e \"$0.ran\" ] && { echo 'pandoc ran twice' >&2; exit 1; }\n\
+             touch \"$0.ran\"\ncat > /dev/null\necho '<p>converted</p>'\n",
+        )?;
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755))?;
+        let config = Config {
+            pandoc_bin: script.to_string_lossy().into_owned(),
+            render_cache_size: 8,
+            ..Default::default()
+        };
+        let text = FormattedText::Latex("Some text.".to_string());
+
+        let first = text.to_html(&config)?;
+        let second = text.to_html(&config)?;
+
+        assert_eq!(first, "<p>converted</p>\n");
+        assert_eq!(second, first);
+
+        Ok(())
+    }
+
+    #[test]
+    fn content_theorems_are_numbered_without_changing_global_list() {
+        let global = Config {
+            theorems: vec![Theorem {
+                name: "theorem".to_string(),
+                label: "Theorem".to_string(),
+                numbered: true,
+            }],
+            ..Default::default()
+        };
+        let metadata = crate::content::ContentMetadata {
+            theorems: Some(vec![Theorem {
+                name: "lemma".to_string(),
+                label: "Lemma".to_string(),
+                numbered: true,
+            }]),
+            ..Default::default()
+        };
+        let input = "\\begin{theorem}A\\end{theorem}\n\\begin{lemma}B\\end{lemma}";
+
+        let local = metadata.render_config(&global);
+        let mut filters = latex_filters(&LatexOptions::from_config(&local));
+        let output = apply_latex_preprocessors(input, &mut filters).unwrap();
+        assert!(output.contains("\\textbf{Theorem 1}"), "{}", output);
+        assert!(output.contains("\\textbf{Lemma 2}"), "{}", output);
+
+        let mut filters = latex_filters(&LatexOptions::from_config(&global));
+        let output = apply_latex_preprocessors(input, &mut filters).unwrap();
+        assert!(output.contains("\\begin{lemma}"), "{}", output);
+        assert_eq!(global.theorems.len(), 1);
+    }
+
     #[test]
     fn processes_theorems() {
         let input = r#"
@@ -303,6 +813,134 @@ mod test_latex_to_html {
         assert!(output.contains("<p>We have a problem</p>"));
     }
 
+    fn latex_batch_to_html(sources: &[&str]) -> Vec<Result<String, String>> {
+        super::latex_batch_to_html(sources, &latex_options(&[]))
+    }
+
+    #[test]
+    fn mathml_renderer_emits_math_elements() {
+        let options = LatexOptions {
+            math_renderer: MathRenderer::MathML,
+            ..latex_options(&[])
+        };
+
+        let output = super::latex_to_html("$x^2$", &options).unwrap();
+
+        assert!(output.contains("<math"));
+        assert!(!output.contains(r"\(x^2\)"));
+    }
+
+    #[test]
+    fn math_renderers_map_to_pandoc_flags() {
+        assert_eq!(MathRenderer::default().pandoc_flag(), "--mathjax");
+        assert_eq!(MathRenderer::MathML.pandoc_flag(), "--mathml");
+        assert_eq!(MathRenderer::WebTeX.pandoc_flag(), "--webtex");
+        assert_eq!(MathRenderer::Native.pandoc_flag(), "--mathml");
+    }
+
+    #[test]
+    fn batch_separator_survives_pandoc() {
+        let separator = batch_separator(&["a"]);
+        let output = latex_to_html(&format!("a\n\n{separator}\n\nb"), &[]).unwrap();
+
+        assert!(output.contains(&format!("<p>{separator}</p>\n")));
+    }
+
+    #[test]
+    fn batch_separator_avoids_source_text() {
+        assert_eq!(batch_separator(&["plain"]), "SSGBATCHSEPARATOR0");
+        assert_eq!(
+            batch_separator(&["SSGBATCHSEPARATOR0", "SSGBATCHSEPARATOR1"]),
+            "SSGBATCHSEPARATOR2"
+        );
+    }
+
+    #[test]
+    fn batched_output_matches_individual_calls() {
+        let sources = [
+            "Problem $2^5$.",
+            r"\begin{equation}\label{eq:a}x\end{equation} See \ref{eq:a}.",
+            r"\frac{1}{2}",
+            "\\begin{itemize}\n\\item one\n\\end{itemize}",
+        ];
+
+        let batched = latex_batch_to_html(&sources);
+        let individual: Vec<_> = sources
+            .iter()
+            .map(|source| latex_to_html(source, &[]))
+            .collect();
+
+        assert_eq!(batched, individual);
+    }
+
+    #[test]
+    fn batch_isolates_broken_snippets() {
+        let outputs = latex_batch_to_html(&["fine", "$2\\", "also fine"]);
+
+        assert_eq!(outputs[0], Ok("<p>fine</p>\n".to_string()));
+        assert!(outputs[1].is_err());
+        assert_eq!(outputs[2], Ok("<p>also fine</p>\n".to_string()));
+    }
+
+    #[test]
+    fn batch_keeps_unclosed_environments_from_neighbors() {
+        let sources = ["\\begin{itemize}\n\\item one", "Problem $2^5$.", "Second."];
+
+        let batched = latex_batch_to_html(&sources);
+        let individual: Vec<_> = sources
+            .iter()
+            .map(|source| latex_to_html(source, &[]))
+            .collect();
+
+        assert_eq!(batched, individual);
+    }
+
+    #[test]
+    fn detects_self_contained_snippets() {
+        assert!(is_self_contained("Problem $2^5$ costs \\$3."));
+        assert!(is_self_contained(
+            "\\begin{align*}\\begin{cases}1\\end{cases}\\end{align*}"
+        ));
+        assert!(!is_self_contained("\\begin{itemize}\n\\item one"));
+        assert!(!is_self_contained("\\begin{itemize}\\end{enumerate}"));
+        assert!(!is_self_contained("Unbalanced $x."));
+        assert!(!is_self_contained("\\newcommand{\\R}{\\mathbb{R}} $\\R$"));
+        assert!(!is_self_contained("\\def\\x{1}"));
+    }
+
+    #[test]
+    fn converts_bare_math_fragments() {
+        let output = latex_to_html(&wrap_math_fragment(r"\frac{1}{2}"), &[]).unwrap();
+        assert!(output.contains(r#"class="math inline""#));
+        assert!(output.contains(r"\frac{1}{2}"));
+        assert!(!output.contains("<html"));
+        assert!(!output.contains("<body"));
+
+        let fragment = wrap_math_fragment("\\sum_{i=1}^n i\n= \\frac{n(n+1)}{2}\n");
+        let output = latex_to_html(&fragment, &[]).unwrap();
+        assert!(output.contains(r#"class="math display""#));
+        assert!(output.contains(r"\frac{n(n+1)}{2}"));
+    }
+
+    #[test]
+    fn wraps_only_files_marked_as_math_fragments() -> std::io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let fragment = temp_dir.path().join("half.math.tex");
+        let text = temp_dir.path().join("heading.tex");
+        std::fs::write(&fragment, "\\frac{1}{2}\n")?;
+        std::fs::write(&text, "\\textbf{No.}\n")?;
+
+        assert_eq!(
+            FormattedText::read_latex(&fragment)?.source(),
+            "\\(\\frac{1}{2}\\)\n"
+        );
+        assert_eq!(
+            FormattedText::read_latex(&text)?.source(),
+            "\\textbf{No.}\n"
+        );
+        Ok(())
+    }
+
     #[test]
     fn ignores_extra_parameters_empty() {
         let input = r#"\begin{problem}{}{A Problem}Some text\end{problem}"#;
@@ -337,6 +975,20 @@ mod test_markdown_to_html {
         assert_eq!(output_2, "<h2>heading</h2>\n<p>text</p>\n");
     }
 
+    #[test]
+    fn cache_follows_config_changed_after_rendering() {
+        let mut config = Config {
+            render_cache_size: 8,
+            ..get_test_config()
+        };
+        let text = FormattedText::Markdown("a -- b".to_string());
+        assert_eq!(text.to_html(&config).unwrap(), "<p>a – b</p>\n");
+
+        config.markdown.smart = false;
+
+        assert_eq!(text.to_html(&config).unwrap(), "<p>a -- b</p>\n");
+    }
+
     #[test]
     fn test_markdown_with_math() {
         let config = get_test_config();
@@ -346,6 +998,24 @@ mod test_markdown_to_html {
         assert!(output_3.contains("$$\n2^5\n$$"));
     }
 
+    #[test]
+    fn native_math_renderer_emits_mathml_without_pandoc() {
+        let config = Config {
+            math_renderer: MathRenderer::Native,
+            pandoc_bin: "/nonexistent/pandoc".to_string(),
+            ..get_test_config()
+        };
+
+        let output = FormattedText::Markdown("Sum $a+b$ and $\\foo$.".to_string())
+            .to_html(&config)
+            .unwrap();
+
+        assert_eq!(
+            output,
+            "<p>Sum <math xmlns=\"http://www.w3.org/1998/Math/MathML\"><mrow><mi>a</mi><mo>+</mo><mi>b</mi></mrow></math> and $\\foo$.</p>\n"
+        );
+    }
+
     #[test]
     fn test_autolink() {
         let config = get_test_config();
@@ -390,6 +1060,38 @@ mod test_markdown_to_html {
         assert_eq!(output, "<p><del>strikethrough</del></p>\n");
     }
 
+    #[test]
+    fn smart_punctuation_can_be_disabled() {
+        let smart = markdown_to_html(r#"Say "hi" -- now"#, &get_test_config()).unwrap();
+        assert_eq!(smart, "<p>Say “hi” – now</p>\n");
+
+        let config = Config {
+            markdown: MarkdownOptions {
+                smart: false,
+                ..Default::default()
+            },
+            ..get_test_config()
+        };
+        let plain = markdown_to_html(r#"Say "hi" -- now"#, &config).unwrap();
+        assert_eq!(plain, "<p>Say &quot;hi&quot; -- now</p>\n");
+    }
+
+    #[test]
+    fn hardbreaks_turn_newlines_into_line_breaks() {
+        let soft = markdown_to_html("Roses are red\nViolets are blue", &get_test_config());
+        assert_eq!(soft.unwrap(), "<p>Roses are red\nViolets are blue</p>\n");
+
+        let config = Config {
+            markdown: MarkdownOptions {
+                hardbreaks: true,
+                ..Default::default()
+            },
+            ..get_test_config()
+        };
+        let hard = markdown_to_html("Roses are red\nViolets are blue", &config).unwrap();
+        assert_eq!(hard, "<p>Roses are red<br />\nViolets are blue</p>\n");
+    }
+
     #[test]
     fn test_table() {
         let config = get_test_config();
@@ -686,6 +1388,18 @@ v{x} = v{y}
         assert!(!output.contains(":::math"));
     }
 
+    #[test]
+    fn test_sub_superscript_leaves_math_alone() {
+        let mut config = get_test_config();
+        config.markdown_sub_superscript = true;
+
+        let output = markdown_to_html("x^2^ and H~2~O, but $x^2 + y^2$", &config).unwrap();
+
+        assert!(output.contains("x<sup>2</sup>"));
+        assert!(output.contains("H<sub>2</sub>O"));
+        assert!(output.contains("$x^2 + y^2$"));
+    }
+
     #[test]
     fn test_custom_html() {
         let config = get_test_config();
@@ -695,6 +1409,104 @@ v{x} = v{y}
         assert_eq!(output, "<div class=\"custom-class\">Custom HTML</div>\n");
     }
 
+    #[test]
+    fn typst_renders_to_html() {
+        if std::process::Command::new("typst")
+            .arg("--version")
+            .output()
+            .is_err()
+        {
+            eprintln!("skipping: typst is not installed");
+            return;
+        }
+        let config = get_test_config();
+        let text = FormattedText::Typst {
+            source: "Hello *world*".to_string(),
+            root: None,
+        };
+
+        let html = text.to_html(&config).unwrap();
+
+        assert!(html.contains("<strong>world</strong>"), "{html}");
+        assert!(!html.contains("<body"), "{html}");
+    }
+
+    #[test]
+    fn typst_imports_files_next_to_the_source() {
+        if std::process::Command::new("typst")
+            .arg("--version")
+            .output()
+            .is_err()
+        {
+            eprintln!("skipping: typst is not installed");
+            return;
+        }
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join("lib.typ"),
+            "#let greet(name) = [Hello *#name*]",
+        )
+        .unwrap();
+        let config = get_test_config();
+        let text = FormattedText::Typst {
+            source: "#import \"lib.typ\": greet\n#greet(\"world\")".to_string(),
+            root: Some(temp_dir.path().to_path_buf()),
+        };
+
+        let html = text.to_html(&config).unwrap();
+
+        assert!(html.contains("<strong>world</strong>"), "{html}");
+    }
+
+    #[test]
+    fn org_renders_to_html() {
+        if std::process::Command::new("pandoc")
+            .arg("--version")
+            .output()
+            .is_err()
+        {
+            eprintln!("skipping: pandoc is not installed");
+            return;
+        }
+        let config = get_test_config();
+        let text = FormattedText::Org("* Groceries\n\n- milk\n- eggs\n".to_string());
+
+        let html = text.to_html(&config).unwrap();
+
+        assert!(html.contains("Groceries</h1>"), "{html}");
+        assert!(html.contains("<li>milk</li>"), "{html}");
+        assert!(html.contains("<li>eggs</li>"), "{html}");
+    }
+
+    #[test]
+    fn markdown_plain_text_drops_markup_and_math_delimiters() {
+        let config = get_test_config();
+        let text = FormattedText::Markdown(
+            "# A *Heading*\n\nThe area is $x^2$ for [a square](sq.html).\n".to_string(),
+        );
+
+        assert_eq!(
+            text.to_plain_text(&config).unwrap(),
+            "A Heading\nThe area is x^2 for a square."
+        );
+    }
+
+    #[test]
+    fn raw_scripts_are_stripped_unless_allowed() {
+        let markdown = "Hello\n\n<script>alert(1)</script>\n\n<div onclick=\"x()\">Hi</div>\n";
+        let config = Config {
+            allow_raw_html: false,
+            ..get_test_config()
+        };
+
+        let output = markdown_to_html(markdown, &config).unwrap();
+
+        assert_eq!(output, "<p>Hello</p>\n\n<div>Hi</div>\n");
+        assert!(markdown_to_html(markdown, &get_test_config())
+            .unwrap()
+            .contains("<script>alert(1)</script>"));
+    }
+
     #[test]
     fn test_expandables() {
         let config = get_test_config();
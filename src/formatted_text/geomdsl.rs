@@ -8,7 +8,8 @@ use std::time::{Duration, Instant};
 
 use crate::config::Config;
 
-const STATIC_ASSETS_DIR: &str = "static/assets";
+use super::shell::{check_cancelled, process_permit};
+
 const DEFAULT_FORMAT: &str = "svg";
 
 const RENDER_SCRIPT: &str = r#"
@@ -137,9 +138,7 @@ fn build_asset_paths(
             content_dir.display()
         )
     })?;
-    let relative_asset_dir = PathBuf::from(STATIC_ASSETS_DIR)
-        .join(relative_source_dir)
-        .join(".geomdsl");
+    let relative_asset_dir = config.assets_dir.join(relative_source_dir).join(".geomdsl");
     let file_name = generated_file_name(source_path.as_path(), source, block_index, format);
     let relative_asset_path = relative_asset_dir.join(file_name);
     let output_path = build_dir.join(&relative_asset_path);
@@ -226,6 +225,8 @@ fn render_geomdsl_source(
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
 
+    let _permit = process_permit();
+    check_cancelled()?;
     let mut child = command
         .spawn()
         .map_err(|e| format!("Failed to start GeomDSL renderer: {e}"))?;
@@ -265,8 +266,14 @@ fn wait_for_child(child: &mut Child, timeout: Duration) -> Result<(), String> {
     loop {
         if start.elapsed() > timeout {
             let _ = child.kill();
+            let _ = child.wait();
             return Err(format!("Timeout after {:?}", timeout));
         }
+        if let Err(err) = check_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(err);
+        }
 
         if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
             let stdout = read_pipe(&mut child.stdout)?;
@@ -575,6 +582,16 @@ scene()
         assert!(first.url.starts_with("/static/assets/en/.geomdsl/page-1-"));
         assert!(first.url.ends_with(".svg"));
 
+        let config = Config {
+            assets_dir: PathBuf::from("media"),
+            ..config
+        };
+        let moved = build_asset_paths("scene()", &source_path, &config, 1, "svg")?;
+        assert!(moved.url.starts_with("/media/en/.geomdsl/page-1-"));
+        assert!(moved
+            .output_path
+            .starts_with(dir.path().join("build/media/en/.geomdsl")));
+
         Ok(())
     }
 }
@@ -148,9 +148,20 @@ impl ProtectedMath {
     }
 
     pub fn restore_html(&self, html: &str) -> String {
+        self.restore_rendered_html(html, |_| None)
+    }
+
+    /// Like [`ProtectedMath::restore_html`], using `render` for each segment
+    /// and falling back to the escaped TeX where it returns `None`.
+    pub fn restore_rendered_html(
+        &self,
+        html: &str,
+        render: impl Fn(&str) -> Option<String>,
+    ) -> String {
         let mut restored = html.to_string();
         for (idx, segment) in self.segments.iter().enumerate() {
-            restored = restored.replace(&placeholder(idx), &escape_html(segment));
+            let segment_html = render(segment).unwrap_or_else(|| escape_html(segment));
+            restored = restored.replace(&placeholder(idx), &segment_html);
         }
         restored
     }
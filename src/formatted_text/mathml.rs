@@ -0,0 +1,410 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+const MATHML_NAMESPACE: &str = "http://www.w3.org/1998/Math/MathML";
+
+/// Greek letters and other commands rendered as identifiers.
+const IDENTIFIERS: &[(&str, &str)] = &[
+    ("alpha", "α"),
+    ("beta", "β"),
+    ("gamma", "γ"),
+    ("delta", "δ"),
+    ("epsilon", "ϵ"),
+    ("varepsilon", "ε"),
+    ("zeta", "ζ"),
+    ("eta", "η"),
+    ("theta", "θ"),
+    ("vartheta", "ϑ"),
+    ("iota", "ι"),
+    ("kappa", "κ"),
+    ("lambda", "λ"),
+    ("mu", "μ"),
+    ("nu", "ν"),
+    ("xi", "ξ"),
+    ("pi", "π"),
+    ("rho", "ρ"),
+    ("sigma", "σ"),
+    ("tau", "τ"),
+    ("upsilon", "υ"),
+    ("phi", "ϕ"),
+    ("varphi", "φ"),
+    ("chi", "χ"),
+    ("psi", "ψ"),
+    ("omega", "ω"),
+    ("Gamma", "Γ"),
+    ("Delta", "Δ"),
+    ("Theta", "Θ"),
+    ("Lambda", "Λ"),
+    ("Xi", "Ξ"),
+    ("Pi", "Π"),
+    ("Sigma", "Σ"),
+    ("Phi", "Φ"),
+    ("Psi", "Ψ"),
+    ("Omega", "Ω"),
+    ("infty", "∞"),
+    ("emptyset", "∅"),
+    ("ell", "ℓ"),
+    ("partial", "∂"),
+    ("nabla", "∇"),
+];
+
+/// Commands rendered as operators.
+const OPERATORS: &[(&str, &str)] = &[
+    ("cdot", "⋅"),
+    ("times", "×"),
+    ("div", "÷"),
+    ("pm", "±"),
+    ("mp", "∓"),
+    ("le", "≤"),
+    ("leq", "≤"),
+    ("ge", "≥"),
+    ("geq", "≥"),
+    ("ne", "≠"),
+    ("neq", "≠"),
+    ("approx", "≈"),
+    ("equiv", "≡"),
+    ("sim", "∼"),
+    ("to", "→"),
+    ("rightarrow", "→"),
+    ("leftarrow", "←"),
+    ("Rightarrow", "⇒"),
+    ("implies", "⟹"),
+    ("Leftrightarrow", "⇔"),
+    ("iff", "⟺"),
+    ("mapsto", "↦"),
+    ("in", "∈"),
+    ("notin", "∉"),
+    ("subset", "⊂"),
+    ("subseteq", "⊆"),
+    ("supset", "⊃"),
+    ("cup", "∪"),
+    ("cap", "∩"),
+    ("setminus", "∖"),
+    ("forall", "∀"),
+    ("exists", "∃"),
+    ("neg", "¬"),
+    ("land", "∧"),
+    ("wedge", "∧"),
+    ("lor", "∨"),
+    ("vee", "∨"),
+    ("circ", "∘"),
+    ("mid", "∣"),
+    ("ldots", "…"),
+    ("cdots", "⋯"),
+    ("sum", "∑"),
+    ("prod", "∏"),
+    ("int", "∫"),
+    ("oint", "∮"),
+    ("langle", "⟨"),
+    ("rangle", "⟩"),
+    ("lfloor", "⌊"),
+    ("rfloor", "⌋"),
+    ("lceil", "⌈"),
+    ("rceil", "⌉"),
+    ("lvert", "|"),
+    ("rvert", "|"),
+    ("lVert", "‖"),
+    ("rVert", "‖"),
+    ("{", "{"),
+    ("}", "}"),
+    ("|", "‖"),
+];
+
+/// Named functions, rendered upright.
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "log", "ln", "exp", "lim", "max", "min", "sup", "inf", "det", "gcd", "deg", "dim", "ker", "Pr",
+];
+
+/// Spacing commands and their widths.
+const SPACES: &[(&str, &str)] = &[
+    (",", "0.1667em"),
+    (":", "0.2222em"),
+    (";", "0.2778em"),
+    (" ", "0.25em"),
+    ("quad", "1em"),
+    ("qquad", "2em"),
+];
+
+/// Render a TeX math expression to MathML, or `None` when it uses
+/// something this renderer does not know, such as environments.
+pub fn latex_to_mathml(tex: &str, display: bool) -> Option<String> {
+    let mut parser = Parser {
+        chars: tex.chars().peekable(),
+    };
+    let body = parser.parse_row(None)?;
+    if parser.chars.peek().is_some() {
+        return None;
+    }
+
+    let display = if display { r#" display="block""# } else { "" };
+    Some(format!(
+        r#"<math xmlns="{MATHML_NAMESPACE}"{display}>{body}</math>"#
+    ))
+}
+
+/// Render a protected Markdown math segment (`$...$` or `$$...$$`).
+pub fn render_math_segment(segment: &str) -> Option<String> {
+    if let Some(tex) = segment
+        .strip_prefix("$$")
+        .and_then(|rest| rest.strip_suffix("$$"))
+    {
+        latex_to_mathml(tex, true)
+    } else {
+        let tex = segment.strip_prefix('$')?.strip_suffix('$')?;
+        latex_to_mathml(tex, false)
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+    /// Parse atoms up to `end` (or the end of input) into an `<mrow>`.
+    fn parse_row(&mut self, end: Option<char>) -> Option<String> {
+        let mut nodes = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.chars.peek() {
+                None if end.is_none() => break,
+                None => return None,
+                Some(&ch) if Some(ch) == end => {
+                    self.chars.next();
+                    break;
+                }
+                Some('}') => return None,
+                Some(_) => nodes.push(self.parse_scripted()?),
+            }
+        }
+
+        Some(if nodes.len() == 1 {
+            nodes.remove(0)
+        } else {
+            format!("<mrow>{}</mrow>", nodes.concat())
+        })
+    }
+
+    /// An atom with optional `_` and `^` scripts.
+    fn parse_scripted(&mut self) -> Option<String> {
+        let base = self.parse_atom()?;
+        let mut sub = None;
+        let mut sup = None;
+        loop {
+            self.skip_whitespace();
+            match self.chars.peek() {
+                Some('_') if sub.is_none() => {
+                    self.chars.next();
+                    sub = Some(self.parse_argument()?);
+                }
+                Some('^') if sup.is_none() => {
+                    self.chars.next();
+                    sup = Some(self.parse_argument()?);
+                }
+                _ => break,
+            }
+        }
+
+        Some(match (sub, sup) {
+            (None, None) => base,
+            (Some(sub), None) => format!("<msub>{base}{sub}</msub>"),
+            (None, Some(sup)) => format!("<msup>{base}{sup}</msup>"),
+            (Some(sub), Some(sup)) => format!("<msubsup>{base}{sub}{sup}</msubsup>"),
+        })
+    }
+
+    /// A braced group or a single atom, as taken by `^`, `_` and commands.
+    fn parse_argument(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&'{') {
+            self.chars.next();
+            self.parse_row(Some('}'))
+        } else {
+            self.parse_atom()
+        }
+    }
+
+    fn parse_atom(&mut self) -> Option<String> {
+        let ch = self.chars.next()?;
+        Some(match ch {
+            '{' => self.parse_row(Some('}'))?,
+            '\\' => self.parse_command()?,
+            '0'..='9' | '.' => {
+                let mut number = ch.to_string();
+                while let Some(&next) = self.chars.peek() {
+                    if next.is_ascii_digit() || next == '.' {
+                        number.push(next);
+                        self.chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                format!("<mn>{number}</mn>")
+            }
+            ch if ch.is_alphabetic() => format!("<mi>{ch}</mi>"),
+            '&' | '#' | '%' | '$' | '^' | '_' | '}' => return None,
+            '<' => "<mo>&lt;</mo>".to_string(),
+            '>' => "<mo>&gt;</mo>".to_string(),
+            '\'' => "<mo>′</mo>".to_string(),
+            ch => format!("<mo>{ch}</mo>"),
+        })
+    }
+
+    fn parse_command(&mut self) -> Option<String> {
+        let name = self.command_name()?;
+        let name = name.as_str();
+
+        if let Some((_, symbol)) = IDENTIFIERS.iter().find(|(command, _)| *command == name) {
+            return Some(format!("<mi>{symbol}</mi>"));
+        }
+        if let Some((_, symbol)) = OPERATORS.iter().find(|(command, _)| *command == name) {
+            return Some(format!("<mo>{symbol}</mo>"));
+        }
+        if let Some((_, width)) = SPACES.iter().find(|(command, _)| *command == name) {
+            return Some(format!(r#"<mspace width="{width}"/>"#));
+        }
+        if FUNCTIONS.contains(&name) {
+            return Some(format!("<mi>{name}</mi>"));
+        }
+
+        Some(match name {
+            "frac" | "dfrac" | "tfrac" => {
+                let numerator = self.parse_argument()?;
+                let denominator = self.parse_argument()?;
+                format!("<mfrac>{numerator}{denominator}</mfrac>")
+            }
+            "sqrt" => {
+                self.skip_whitespace();
+                if self.chars.peek() == Some(&'[') {
+                    self.chars.next();
+                    let index = self.parse_row(Some(']'))?;
+                    let radicand = self.parse_argument()?;
+                    format!("<mroot>{radicand}{index}</mroot>")
+                } else {
+                    format!("<msqrt>{}</msqrt>", self.parse_argument()?)
+                }
+            }
+            "left" | "right" | "big" | "Big" | "bigg" | "Bigg" => {
+                self.skip_whitespace();
+                match self.chars.next()? {
+                    '.' => String::new(),
+                    '\\' => self.parse_command()?,
+                    '<' => "<mo>&lt;</mo>".to_string(),
+                    '>' => "<mo>&gt;</mo>".to_string(),
+                    ch => format!("<mo>{ch}</mo>"),
+                }
+            }
+            "text" | "textrm" | "mathrm" | "operatorname" => {
+                let text = self.braced_text()?;
+                if name == "text" || name == "textrm" {
+                    format!("<mtext>{}</mtext>", escape(&text))
+                } else {
+                    format!(r#"<mi mathvariant="normal">{}</mi>"#, escape(&text))
+                }
+            }
+            "mathbf" | "mathbb" | "mathcal" | "mathit" => {
+                let variant = match name {
+                    "mathbf" => "bold",
+                    "mathbb" => "double-struck",
+                    "mathcal" => "script",
+                    _ => "italic",
+                };
+                let text = self.braced_text()?;
+                format!(r#"<mi mathvariant="{variant}">{}</mi>"#, escape(&text))
+            }
+            "hat" | "bar" | "vec" | "tilde" | "dot" | "overline" => {
+                let accent = match name {
+                    "hat" => "^",
+                    "bar" | "overline" => "¯",
+                    "vec" => "→",
+                    "tilde" => "~",
+                    _ => "˙",
+                };
+                let base = self.parse_argument()?;
+                format!(r#"<mover accent="true">{base}<mo>{accent}</mo></mover>"#)
+            }
+            _ => return None,
+        })
+    }
+
+    /// The letters of `\name`, or the single symbol of `\{`, `\,` etc.
+    fn command_name(&mut self) -> Option<String> {
+        let first = self.chars.next()?;
+        if !first.is_ascii_alphabetic() {
+            return Some(first.to_string());
+        }
+
+        let mut name = first.to_string();
+        while let Some(&next) = self.chars.peek() {
+            if next.is_ascii_alphabetic() {
+                name.push(next);
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+        Some(name)
+    }
+
+    /// The raw contents of a `{...}` argument, for text-like commands.
+    fn braced_text(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        if self.chars.next()? != '{' {
+            return None;
+        }
+
+        let mut text = String::new();
+        let mut depth = 0;
+        loop {
+            match self.chars.next()? {
+                '{' => depth += 1,
+                '}' if depth == 0 => return Some(text),
+                '}' => depth -= 1,
+                ch => text.push(ch),
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().is_some_and(|ch| ch.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inline(tex: &str) -> String {
+        latex_to_mathml(tex, false).expect("supported expression")
+    }
+
+    #[test]
+    fn renders_fractions_scripts_and_symbols() {
+        assert_eq!(
+            inline(r"\frac{1}{2} + x_i^2 \le \alpha"),
+            format!(
+                r#"<math xmlns="{MATHML_NAMESPACE}"><mrow><mfrac><mn>1</mn><mn>2</mn></mfrac><mo>+</mo><msubsup><mi>x</mi><mi>i</mi><mn>2</mn></msubsup><mo>≤</mo><mi>α</mi></mrow></math>"#
+            )
+        );
+        assert!(inline(r"\sqrt[3]{x}").contains("<mroot><mi>x</mi><mn>3</mn></mroot>"));
+        assert!(inline(r"\text{if } a<b").contains("<mtext>if </mtext>"));
+    }
+
+    #[test]
+    fn gives_up_on_unknown_commands_and_environments() {
+        assert_eq!(latex_to_mathml(r"\unknowncommand{x}", false), None);
+        assert_eq!(
+            latex_to_mathml(r"\begin{aligned}a&=b\end{aligned}", true),
+            None
+        );
+        assert_eq!(latex_to_mathml("{a", false), None);
+    }
+}
@@ -4,11 +4,23 @@ mod geomdsl;
 mod learning;
 mod markdown_expandable;
 mod markdown_math;
+mod mathml;
 mod pandoc_latex_filters;
+mod plain_text;
+mod render_cache;
+mod sanitize;
 mod shell;
 
 pub use formatted_text::FormattedText;
+pub use formatted_text::MarkdownOptions;
+pub use formatted_text::MathRenderer;
 pub use formatted_text::Theorem;
 pub use geomdsl::preprocess_geomdsl_blocks;
 pub use learning::preprocess_learning_blocks;
 pub use markdown_math::{check_math_markdown, expand_math_markdown};
+pub use mathml::latex_to_mathml;
+pub use render_cache::{render_cache, ConfigFingerprint, RenderCache, RenderKey};
+pub use shell::{
+    cancel_processes_on, check_cancelled, limit_concurrent_processes, run_pooled, run_with_timeout,
+    WarmProcessPool,
+};
@@ -20,14 +20,16 @@ pub trait PandocFilter {
 
 pub struct EnvFilter {
     theorems: HashMap<String, Theorem>,
+    verbatim_environments: HashSet<String>,
     theorem_labels: HashMap<String, String>,
     equation_labels: HashSet<String>,
 }
 
 impl EnvFilter {
-    pub fn new(theorems: Vec<Theorem>) -> Self {
+    pub fn new(theorems: Vec<Theorem>, verbatim_environments: &[String]) -> Self {
         Self {
             theorems: theorems.into_iter().map(|t| (t.name.clone(), t)).collect(),
+            verbatim_environments: verbatim_environments.iter().cloned().collect(),
             theorem_labels: HashMap::new(),
             equation_labels: HashSet::new(),
         }
@@ -71,8 +73,8 @@ impl EnvFilter {
                 "\\textbf{{{}}}. ",
                 theorem.label(*theorem_counter)
             ));
-        } else if env_name == "equation" {
-            result.push_str(r"$$\begin{equation}");
+        } else if is_numbered_math(env_name) {
+            result.push_str(&format!("$$\\begin{{{}}}", env_name));
         } else if env_name == "problem" || env_name == "solution" {
             result.push_str(format!("\\begin{{{}}}", env_name).as_str());
         } else {
@@ -92,7 +94,7 @@ impl EnvFilter {
             if self.theorems.contains_key(env_name) {
                 self.theorem_labels
                     .insert(label.to_string(), format!("{}", theorem_counter));
-            } else if env_name == "equation" {
+            } else if is_numbered_math(env_name) {
                 self.equation_labels.insert(label.to_string());
             }
         }
@@ -118,20 +120,27 @@ impl EnvFilter {
         token: &str,
         env_name: &str,
         env_stack: &mut Vec<String>,
+        location: (usize, usize),
         result: &mut String,
     ) -> Result<(), String> {
-        let last_env = env_stack.pop().unwrap();
+        let (line, column) = location;
+        let last_env = env_stack.pop().ok_or_else(|| {
+            format!(
+                "Unexpected \\end{{{}}} at line {}, column {}",
+                env_name, line, column
+            )
+        })?;
         if env_name != last_env {
             return Err(format!(
-                "Mismatched environment tags: \\begin{{{}}} and \\end{{{}}}",
-                last_env, env_name
+                "Mismatched environment tags: \\begin{{{}}} and \\end{{{}}} at line {}, column {}",
+                last_env, env_name, line, column
             ));
         }
 
         if self.theorems.contains_key(env_name) {
             result.push('\n');
-        } else if env_name == "equation" {
-            result.push_str(r"\end{equation}$$");
+        } else if is_numbered_math(env_name) {
+            result.push_str(&format!("\\end{{{}}}$$", env_name));
         } else {
             result.push_str(token);
         }
@@ -140,8 +149,13 @@ impl EnvFilter {
     }
 }
 
-impl PandocFilter for EnvFilter {
-    fn preprocess(&mut self, input: &str) -> Result<String, String> {
+impl EnvFilter {
+    /// Rewrite environments, labels and references in one forward pass.
+    ///
+    /// References resolve against the labels collected so far, so this is run
+    /// twice by `preprocess`: once to collect every label, and once more to
+    /// produce the output with forward references resolved too.
+    fn rewrite(&mut self, input: &str) -> Result<String, String> {
         let mut result = "".to_string();
         let theorem_re = self.generate_theorem_regex();
         let mut theorem_counter = 0;
@@ -151,10 +165,17 @@ impl PandocFilter for EnvFilter {
 
         for caps in theorem_re.captures_iter(input) {
             let m = caps.get(0).unwrap();
+            if m.start() < processed {
+                continue; // Inside a verbatim environment copied below.
+            }
             let s = m.as_str();
             result.push_str(&input[processed..m.start()]);
             if s.starts_with(r"\begin") {
                 let env_name = begin_environment_name(s);
+                if self.verbatim_environments.contains(env_name) {
+                    processed = copy_verbatim_environment(input, m.start(), env_name, &mut result)?;
+                    continue;
+                }
                 env_stack.push(env_name.to_string());
                 self.write_begin_environment(s, env_name, &mut theorem_counter, &mut result);
             } else if s.starts_with(r"\label") {
@@ -163,7 +184,8 @@ impl PandocFilter for EnvFilter {
                 self.write_reference(s, &mut result);
             } else if s.starts_with(r"\end") {
                 let env_name = end_environment_name(s);
-                self.write_end_environment(s, env_name, &mut env_stack, &mut result)?;
+                let location = line_and_column(input, m.start());
+                self.write_end_environment(s, env_name, &mut env_stack, location, &mut result)?;
             } else {
                 result.push_str(m.as_str());
             }
@@ -172,6 +194,15 @@ impl PandocFilter for EnvFilter {
         result.push_str(&input[processed..]);
         Ok(result)
     }
+}
+
+impl PandocFilter for EnvFilter {
+    fn preprocess(&mut self, input: &str) -> Result<String, String> {
+        self.theorem_labels.clear();
+        self.equation_labels.clear();
+        self.rewrite(input)?;
+        self.rewrite(input)
+    }
 
     fn postprocess(&mut self, input: &str) -> Result<String, String> {
         let result = self
@@ -179,11 +210,45 @@ impl PandocFilter for EnvFilter {
             .replace("(EQREFBEGIN)", "\\ref{")
             .replace("(EQREFEND)", "}")
             .replace(r"$$\begin{equation}", r"\begin{equation}")
-            .replace(r"\end{equation}$$", r"\end{equation}");
+            .replace(r"\end{equation}$$", r"\end{equation}")
+            .replace(r"$$\begin{align}", r"\begin{align}")
+            .replace(r"\end{align}$$", r"\end{align}");
         Ok(result)
     }
 }
 
+/// Copy a verbatim-like environment starting at `start` unchanged and return
+/// the offset just past its `\end{...}`.
+fn copy_verbatim_environment(
+    input: &str,
+    start: usize,
+    env_name: &str,
+    result: &mut String,
+) -> Result<usize, String> {
+    let end_token = format!("\\end{{{}}}", env_name);
+    let end = input[start..]
+        .find(&end_token)
+        .map(|offset| start + offset + end_token.len())
+        .ok_or_else(|| format!("Unterminated \\begin{{{}}}", env_name))?;
+    result.push_str(&input[start..end]);
+    Ok(end)
+}
+
+/// 1-based line and column (in characters) of a byte offset in `input`.
+fn line_and_column(input: &str, offset: usize) -> (usize, usize) {
+    let before = &input[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |pos| pos + 1);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+/// Math environments numbered by MathJax, whose labels are left for MathJax
+/// to resolve.
+fn is_numbered_math(env_name: &str) -> bool {
+    env_name == "equation" || env_name == "align"
+}
+
 fn begin_environment_name(token: &str) -> &str {
     token
         .trim_start_matches(r"\begin{")
@@ -201,3 +266,120 @@ fn command_argument(token: &str) -> &str {
         .and_then(|(_, rest)| rest.strip_suffix('}'))
         .unwrap_or("")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verbatim_environments() -> Vec<String> {
+        vec!["verbatim".to_string(), "lstlisting".to_string()]
+    }
+
+    #[test]
+    fn leaves_verbatim_content_untouched() {
+        let input = r"\begin{equation}\label{x}\end{equation}
+\begin{verbatim}
+See \ref{x} and \begin{theorem}
+\end{verbatim}
+Outside \ref{x}.";
+        let mut filter = EnvFilter::new(Vec::new(), &verbatim_environments());
+
+        let output = filter.preprocess(input).unwrap();
+
+        assert!(output.contains("See \\ref{x} and \\begin{theorem}\n\\end{verbatim}"));
+        assert!(output.contains("Outside (EQREFBEGIN)x(EQREFEND)."));
+    }
+
+    #[test]
+    fn leaves_lstlisting_options_untouched() {
+        let input = "\\begin{lstlisting}[language=TeX]\n\\label{code}\n\\end{lstlisting}";
+        let mut filter = EnvFilter::new(Vec::new(), &verbatim_environments());
+
+        assert_eq!(filter.preprocess(input).unwrap(), input);
+    }
+
+    #[test]
+    fn reports_location_of_mismatched_end() {
+        let input = "Intro\n\\begin{itemize}\n\\item One\n  \\end{enumerate}\n";
+        let mut filter = EnvFilter::new(Vec::new(), &verbatim_environments());
+
+        let err = filter.preprocess(input).unwrap_err();
+
+        assert!(err.contains("\\begin{itemize} and \\end{enumerate}"));
+        assert!(err.contains("at line 4, column 3"));
+    }
+
+    #[test]
+    fn reports_unexpected_end() {
+        let input = "\\end{document}\n\\end{itemize}";
+        let mut filter = EnvFilter::new(Vec::new(), &verbatim_environments());
+
+        let err = filter.preprocess(input).unwrap_err();
+
+        assert!(err.contains("Unexpected \\end{itemize} at line 2, column 1"));
+    }
+
+    #[test]
+    fn rejects_unterminated_verbatim() {
+        let mut filter = EnvFilter::new(Vec::new(), &verbatim_environments());
+
+        let err = filter
+            .preprocess("\\begin{verbatim}\n\\ref{x}")
+            .unwrap_err();
+
+        assert!(err.contains("Unterminated \\begin{verbatim}"));
+    }
+
+    #[test]
+    fn leaves_align_numbering_to_mathjax() {
+        let input = "\\begin{align}\na &= b \\label{eq:first} \\\\\nc &= d \\label{eq:second}\n\\end{align}\nBy \\ref{eq:second}.";
+        let mut filter = EnvFilter::new(Vec::new(), &verbatim_environments());
+
+        let output = filter.preprocess(input).unwrap();
+
+        assert!(output.starts_with("$$\\begin{align}\na &= b \\label{eq:first} \\\\"));
+        assert!(output.contains("c &= d \\label{eq:second}\n\\end{align}$$"));
+        assert!(!output.contains("\\tag"));
+        assert!(output.contains("By (EQREFBEGIN)eq:second(EQREFEND)."));
+    }
+
+    #[test]
+    fn mixed_equation_and_align_share_mathjax_numbering() {
+        let input = "\\begin{equation}\\label{eq:one}x\\end{equation}\n\\begin{align}\na &= b \\label{eq:two}\n\\end{align}\nSee \\ref{eq:one} and \\ref{eq:two}.";
+        let mut filter = EnvFilter::new(Vec::new(), &verbatim_environments());
+
+        let preprocessed = filter.preprocess(input).unwrap();
+        let output = filter.postprocess(&preprocessed).unwrap();
+
+        assert!(output.contains("\\begin{equation}\\label{eq:one}x\\end{equation}"));
+        assert!(output.contains("\\begin{align}\na &= b \\label{eq:two}\n\\end{align}"));
+        assert!(!output.contains("\\tag"));
+        assert!(output.contains("See \\ref{eq:one} and \\ref{eq:two}."));
+    }
+
+    #[test]
+    fn starred_align_stays_unnumbered() {
+        let input = "\\begin{align*}\na &= b \\\\\nc &= d\n\\end{align*}";
+        let mut filter = EnvFilter::new(Vec::new(), &verbatim_environments());
+
+        let output = filter.preprocess(input).unwrap();
+
+        assert_eq!(output, input);
+    }
+
+    #[test]
+    fn resolves_forward_references() {
+        let theorems = vec![Theorem {
+            name: "theorem".to_string(),
+            label: "Theorem".to_string(),
+            numbered: true,
+        }];
+        let input = "See \\ref{lm:1}.\n\\begin{theorem}\\label{lm:1}\nBody\n\\end{theorem}";
+        let mut filter = EnvFilter::new(theorems, &verbatim_environments());
+
+        let output = filter.preprocess(input).unwrap();
+
+        assert!(output.starts_with("See \\href{#lm:1}{1}."));
+        assert!(output.contains("\\textbf{Theorem 1}. \\label{lm:1}"));
+    }
+}
@@ -0,0 +1,90 @@
+use regex::{Captures, Regex};
+use std::sync::OnceLock;
+
+static DROPPED_ELEMENT_REGEX: OnceLock<Regex> = OnceLock::new();
+static BLOCK_END_REGEX: OnceLock<Regex> = OnceLock::new();
+static TAG_REGEX: OnceLock<Regex> = OnceLock::new();
+static MATH_REGEX: OnceLock<Regex> = OnceLock::new();
+static LATEX_MARKUP_REGEX: OnceLock<Regex> = OnceLock::new();
+
+fn dropped_element_regex() -> &'static Regex {
+    DROPPED_ELEMENT_REGEX.get_or_init(|| {
+        Regex::new(r"(?is)<!--.*?-->|<script\b.*?</script\s*>|<style\b.*?</style\s*>")
+            .expect("valid dropped element regex")
+    })
+}
+
+fn block_end_regex() -> &'static Regex {
+    BLOCK_END_REGEX.get_or_init(|| {
+        Regex::new(
+            r"(?i)<br\s*/?>|</(?:p|h[1-6]|li|div|pre|blockquote|tr|figure|details|summary)\s*>",
+        )
+        .expect("valid block end regex")
+    })
+}
+
+fn tag_regex() -> &'static Regex {
+    TAG_REGEX.get_or_init(|| Regex::new(r"<[^>]*>").expect("valid tag regex"))
+}
+
+fn math_regex() -> &'static Regex {
+    MATH_REGEX.get_or_init(|| {
+        Regex::new(r"(?s)\$\$(.+?)\$\$|\$([^$\n]+?)\$|\\\((.+?)\\\)|\\\[(.+?)\\\]")
+            .expect("valid math regex")
+    })
+}
+
+fn latex_markup_regex() -> &'static Regex {
+    LATEX_MARKUP_REGEX
+        .get_or_init(|| Regex::new(r"\\[a-zA-Z]+\*?|[{}]").expect("valid latex markup regex"))
+}
+
+/// Text of rendered `html`, one line per block, with tags and comments
+/// removed, entities decoded and math reduced to its bare source.
+pub fn html_to_plain_text(html: &str) -> String {
+    let html = dropped_element_regex().replace_all(html, "");
+    let html = block_end_regex().replace_all(&html, "\n");
+    let text = tag_regex().replace_all(&html, "");
+    let text = math_regex().replace_all(&text, |caps: &Captures| {
+        let source = (1..=4)
+            .find_map(|group| caps.get(group))
+            .map_or("", |m| m.as_str());
+        latex_markup_regex().replace_all(source, " ").into_owned()
+    });
+
+    decode_entities(&text)
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_tags_comments_and_entities() {
+        let html = concat!(
+            "<!-- note --><h1 id=\"intro\">Intro</h1>\n",
+            "<p>Tom &amp; <em>Jerry</em><br>run</p><script>x()</script>\n",
+            "<ul><li>one</li><li>two &lt;3</li></ul>",
+        );
+
+        assert_eq!(
+            html_to_plain_text(html),
+            "Intro\nTom & Jerry\nrun\none\ntwo <3"
+        );
+    }
+}
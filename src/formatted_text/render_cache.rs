@@ -0,0 +1,174 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use crate::config::Config;
+
+use super::FormattedText;
+
+static RENDER_CACHE: OnceLock<Mutex<RenderCache>> = OnceLock::new();
+
+/// Identifies a conversion: the kind of text, its source (and the dir its
+/// Typst imports are read from) and the fingerprint of the settings it was
+/// rendered with.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RenderKey {
+    kind: &'static str,
+    source: String,
+    root: Option<PathBuf>,
+    config: ConfigFingerprint,
+}
+
+impl RenderKey {
+    pub fn new(text: &FormattedText, config: ConfigFingerprint) -> Self {
+        let kind = match text {
+            FormattedText::Latex(_) => "latex",
+            FormattedText::Markdown(_) => "markdown",
+            FormattedText::Html(_) => "html",
+            FormattedText::Typst { .. } => "typst",
+            FormattedText::Org(_) => "org",
+        };
+        Self {
+            kind,
+            source: text.source().to_string(),
+            root: match text {
+                FormattedText::Typst { root, .. } => root.clone(),
+                _ => None,
+            },
+            config,
+        }
+    }
+}
+
+/// Hash of a [`Config`]'s settings. Compute it once the config is final and
+/// reuse it for all the texts rendered with that config, rather than on
+/// every cache lookup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ConfigFingerprint(u64);
+
+impl ConfigFingerprint {
+    pub fn new(config: &Config) -> Self {
+        let mut hasher = DefaultHasher::new();
+        serde_json::to_string(config)
+            .unwrap_or_default()
+            .hash(&mut hasher);
+        Self(hasher.finish())
+    }
+}
+
+/// Rendered HTML by [`RenderKey`]. Once `capacity` is reached, the least
+/// recently used quarter of the entries is dropped in one go.
+#[derive(Debug, Default)]
+pub struct RenderCache {
+    entries: HashMap<RenderKey, (String, u64)>,
+    clock: u64,
+}
+
+impl RenderCache {
+    pub fn get(&mut self, key: &RenderKey) -> Option<String> {
+        self.clock += 1;
+        let clock = self.clock;
+        self.entries.get_mut(key).map(|(html, last_used)| {
+            *last_used = clock;
+            html.clone()
+        })
+    }
+
+    pub fn insert(&mut self, key: RenderKey, html: String, capacity: usize) {
+        if capacity == 0 {
+            return;
+        }
+        if self.entries.len() >= capacity && !self.entries.contains_key(&key) {
+            self.evict(self.entries.len() + 1 - capacity + capacity / 4);
+        }
+        self.clock += 1;
+        self.entries.insert(key, (html, self.clock));
+    }
+
+    /// Drop the `count` least recently used entries.
+    fn evict(&mut self, count: usize) {
+        let mut last_used: Vec<u64> = self.entries.values().map(|(_, used)| *used).collect();
+        if count >= last_used.len() {
+            self.entries.clear();
+            return;
+        }
+        let (_, &mut cutoff, _) = last_used.select_nth_unstable(count);
+        // Clock values are unique, so this keeps exactly the newer entries.
+        self.entries.retain(|_, (_, used)| *used >= cutoff);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// The process-wide cache used by [`FormattedText::to_html`] when
+/// `render_cache_size` is set.
+pub fn render_cache() -> MutexGuard<'static, RenderCache> {
+    RENDER_CACHE
+        .get_or_init(|| Mutex::new(RenderCache::default()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(source: &str) -> RenderKey {
+        RenderKey::new(
+            &FormattedText::Html(source.to_string()),
+            ConfigFingerprint::new(&Config::default()),
+        )
+    }
+
+    #[test]
+    fn evicts_least_recently_used_entry() {
+        let mut cache = RenderCache::default();
+        cache.insert(key("a"), "A".to_string(), 2);
+        cache.insert(key("b"), "B".to_string(), 2);
+        assert_eq!(cache.get(&key("a")).as_deref(), Some("A"));
+
+        cache.insert(key("c"), "C".to_string(), 2);
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&key("b")), None);
+        assert_eq!(cache.get(&key("a")).as_deref(), Some("A"));
+        assert_eq!(cache.get(&key("c")).as_deref(), Some("C"));
+    }
+
+    #[test]
+    fn evicts_a_quarter_at_a_time() {
+        let mut cache = RenderCache::default();
+        for name in ["a", "b", "c", "d", "e", "f", "g", "h"] {
+            cache.insert(key(name), name.to_uppercase(), 8);
+        }
+        assert_eq!(cache.get(&key("a")).as_deref(), Some("A"));
+
+        cache.insert(key("i"), "I".to_string(), 8);
+
+        assert_eq!(cache.len(), 6);
+        for evicted in ["b", "c", "d"] {
+            assert_eq!(cache.get(&key(evicted)), None);
+        }
+        assert_eq!(cache.get(&key("a")).as_deref(), Some("A"));
+        assert_eq!(cache.get(&key("i")).as_deref(), Some("I"));
+    }
+
+    #[test]
+    fn fingerprint_follows_config_changes() {
+        let mut config = Config::default();
+        let original = ConfigFingerprint::new(&config);
+        assert_eq!(ConfigFingerprint::new(&config.clone()), original);
+
+        config.render_cache_size = 16;
+
+        assert_ne!(ConfigFingerprint::new(&config), original);
+    }
+}
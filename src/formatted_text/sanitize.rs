@@ -0,0 +1,151 @@
+use std::sync::OnceLock;
+
+/// Elements kept on top of ammonia's defaults: those the Markdown directives
+/// expand to, task list checkboxes and the MathML of the native renderer.
+const EXTRA_TAGS: &[&str] = &[
+    "section",
+    "input",
+    "picture",
+    "source",
+    "video",
+    "math",
+    "semantics",
+    "annotation",
+    "mrow",
+    "mi",
+    "mo",
+    "mn",
+    "ms",
+    "mtext",
+    "mspace",
+    "msup",
+    "msub",
+    "msubsup",
+    "mfrac",
+    "msqrt",
+    "mroot",
+    "mover",
+    "munder",
+    "munderover",
+    "mstyle",
+    "mtable",
+    "mtr",
+    "mtd",
+];
+
+/// Attributes kept on every element.
+const GENERIC_ATTRIBUTES: &[&str] = &["class", "id", "style", "dir"];
+
+/// Attributes kept on specific elements.
+const TAG_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("input", &["type", "checked", "disabled"]),
+    ("source", &["src", "srcset", "type", "media"]),
+    ("video", &["src", "controls", "width", "height", "poster"]),
+    ("math", &["display", "xmlns"]),
+    ("annotation", &["encoding"]),
+    ("mi", &["mathvariant"]),
+    (
+        "mo",
+        &["stretchy", "fence", "separator", "lspace", "rspace"],
+    ),
+    ("mspace", &["width"]),
+    ("mover", &["accent"]),
+    ("munder", &["accentunder"]),
+    ("mstyle", &["displaystyle", "scriptlevel"]),
+];
+
+/// CSS properties kept in `style` attributes, as written by figures and
+/// syntax highlighting.
+const STYLE_PROPERTIES: &[&str] = &[
+    "color",
+    "background-color",
+    "font-weight",
+    "font-style",
+    "text-decoration",
+    "text-align",
+    "margin",
+    "width",
+    "max-width",
+];
+
+static SANITIZER: OnceLock<ammonia::Builder<'static>> = OnceLock::new();
+
+fn sanitizer() -> &'static ammonia::Builder<'static> {
+    SANITIZER.get_or_init(|| {
+        let mut builder = ammonia::Builder::default();
+        builder
+            .add_tags(EXTRA_TAGS)
+            .add_generic_attributes(GENERIC_ATTRIBUTES)
+            .add_generic_attribute_prefixes(["data-"])
+            .filter_style_properties(STYLE_PROPERTIES.iter().copied().collect())
+            .link_rel(None);
+        for (tag, attributes) in TAG_ATTRIBUTES {
+            builder.add_tag_attributes(tag, attributes.iter());
+        }
+        builder
+    })
+}
+
+/// Keep only allow-listed elements, attributes and url schemes of `html`,
+/// parsing it as a browser would. Scripts, event handlers and
+/// `javascript:`/`data:` urls, however they are spelled, do not survive.
+pub fn sanitize_html(html: &str) -> String {
+    sanitizer().clean(html).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_scripts_handlers_and_script_urls() {
+        let html = concat!(
+            "<p>Hi<script>alert(1)</script></p>\n",
+            "<img src=\"a.png\" onerror=\"alert(1)\" alt=\"A\">\n",
+            "<img/onerror=alert(1) src=\"b.png\">\n",
+            "<a href=\"javascript:alert(1)\">link</a> <a href='/ok'>ok</a>\n",
+            "<a href=\"&#106;avascript:alert(1)\">encoded</a>\n",
+            "<a href=\"data:text/html,<script>alert(1)</script>\">data</a>\n",
+            "<svg><a xlink:href=\"javascript:alert(1)\">svg</a></svg>\n",
+            "<form><button formaction=\"javascript:alert(1)\">go</button></form>\n",
+            "<meta http-equiv=\"refresh\" content=\"0;url=javascript:alert(1)\">\n",
+            "<p>online = true</p>\n",
+        );
+
+        assert_eq!(
+            sanitize_html(html),
+            concat!(
+                "<p>Hi</p>\n",
+                "<img src=\"a.png\" alt=\"A\">\n",
+                "<img src=\"b.png\">\n",
+                "<a>link</a> <a href=\"/ok\">ok</a>\n",
+                "<a>encoded</a>\n",
+                "<a>data</a>\n",
+                "\n",
+                "go\n",
+                "\n",
+                "<p>online = true</p>\n",
+            )
+        );
+    }
+
+    #[test]
+    fn keeps_directive_markup_and_mathml() {
+        let html = concat!(
+            "<aside class=\"card remark\" data-kind=\"remark\"><p>Note</p></aside>\n",
+            "<figure class=\"figure-block\" style=\"margin: 1.5rem auto; position: fixed\">",
+            "<img src=\"f.png\" alt=\"\"></figure>\n",
+            "<math display=\"block\"><mrow><mi>x</mi><mo>=</mo><mn>1</mn></mrow></math>\n",
+        );
+
+        assert_eq!(
+            sanitize_html(html),
+            concat!(
+                "<aside class=\"card remark\" data-kind=\"remark\"><p>Note</p></aside>\n",
+                "<figure class=\"figure-block\" style=\"margin:1.5rem auto\">",
+                "<img src=\"f.png\" alt=\"\"></figure>\n",
+                "<math display=\"block\"><mrow><mi>x</mi><mo>=</mo><mn>1</mn></mrow></math>\n",
+            )
+        );
+    }
+}
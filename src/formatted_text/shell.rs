@@ -1,17 +1,322 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::io::{Read, Write};
 use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
 use std::thread;
 use std::time::{Duration, Instant};
 
+static PROCESS_LIMITER: ProcessLimiter = ProcessLimiter::new(usize::MAX);
+/// How long a process waiting for a permit sleeps before looking for idle
+/// warm processes to stop again.
+const IDLE_RECLAIM_INTERVAL: Duration = Duration::from_millis(50);
+static WARM_POOLS: OnceLock<Mutex<HashMap<PoolKey, Arc<WarmProcessPool>>>> = OnceLock::new();
+
+type PoolKey = (String, Vec<String>);
+
+thread_local! {
+    static CANCELLED: RefCell<Option<Arc<AtomicBool>>> = const { RefCell::new(None) };
+}
+
+/// Tie the external processes run by the current thread to `cancelled`:
+/// once it is set, a running process is killed and no new one starts.
+pub fn cancel_processes_on(cancelled: Arc<AtomicBool>) {
+    CANCELLED.with(|flag| *flag.borrow_mut() = Some(cancelled));
+}
+
+/// Fail if the current thread's work was cancelled (see
+/// [`cancel_processes_on`]).
+pub fn check_cancelled() -> Result<(), String> {
+    let cancelled = CANCELLED.with(|flag| {
+        flag.borrow()
+            .as_ref()
+            .is_some_and(|cancelled| cancelled.load(Ordering::SeqCst))
+    });
+    if cancelled {
+        Err("Cancelled".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+/// Counting semaphore bounding how many external processes run at once.
+/// Idle processes of a [`WarmProcessPool`] hold a permit too.
+pub struct ProcessLimiter {
+    state: Mutex<LimiterState>,
+    released: Condvar,
+}
+
+struct LimiterState {
+    limit: usize,
+    running: usize,
+    waiting: usize,
+}
+
+pub struct ProcessPermit<'a> {
+    limiter: &'a ProcessLimiter,
+}
+
+impl ProcessLimiter {
+    pub const fn new(limit: usize) -> Self {
+        Self {
+            state: Mutex::new(LimiterState {
+                limit,
+                running: 0,
+                waiting: 0,
+            }),
+            released: Condvar::new(),
+        }
+    }
+
+    pub fn set_limit(&self, limit: usize) {
+        self.lock().limit = limit.max(1);
+        self.released.notify_all();
+    }
+
+    /// A permit if one is free right away.
+    pub fn try_acquire(&self) -> Option<ProcessPermit<'_>> {
+        let mut state = self.lock();
+        if state.running >= state.limit {
+            return None;
+        }
+        state.running += 1;
+        Some(ProcessPermit { limiter: self })
+    }
+
+    /// Wait up to `timeout` for a permit.
+    fn acquire_within(&self, timeout: Duration) -> Option<ProcessPermit<'_>> {
+        let mut state = self.lock();
+        state.waiting += 1;
+        let deadline = Instant::now() + timeout;
+        while state.running >= state.limit {
+            let Some(remaining) = deadline.checked_duration_since(Instant::now()) else {
+                break;
+            };
+            state = self
+                .released
+                .wait_timeout(state, remaining)
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .0;
+        }
+        state.waiting -= 1;
+        if state.running >= state.limit {
+            return None;
+        }
+        state.running += 1;
+        Some(ProcessPermit { limiter: self })
+    }
+
+    /// Whether a caller is blocked waiting for a permit.
+    fn has_waiters(&self) -> bool {
+        self.lock().waiting > 0
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LimiterState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Drop for ProcessPermit<'_> {
+    fn drop(&mut self) {
+        self.limiter.lock().running -= 1;
+        self.limiter.released.notify_one();
+    }
+}
+
+/// Bound the number of concurrently running external processes
+/// (pandoc, GeomDSL renderers) for the whole process.
+pub fn limit_concurrent_processes(limit: usize) {
+    PROCESS_LIMITER.set_limit(limit);
+}
+
+pub fn process_permit() -> ProcessPermit<'static> {
+    acquire_permit(&PROCESS_LIMITER)
+}
+
+/// Take a permit from `limiter`, stopping idle warm processes to free one
+/// when all are taken.
+fn acquire_permit(limiter: &'static ProcessLimiter) -> ProcessPermit<'static> {
+    loop {
+        if let Some(permit) = limiter.try_acquire() {
+            return permit;
+        }
+        if stop_idle_process(limiter) {
+            continue;
+        }
+        if let Some(permit) = limiter.acquire_within(IDLE_RECLAIM_INTERVAL) {
+            return permit;
+        }
+    }
+}
+
+/// Stop one idle process of the shared warm pools that holds a permit of
+/// `limiter`. Returns whether there was one.
+fn stop_idle_process(limiter: &ProcessLimiter) -> bool {
+    let Some(pools) = WARM_POOLS.get() else {
+        return false;
+    };
+    let pools = pools
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    pools
+        .values()
+        .filter(|pool| std::ptr::eq(pool.limiter, limiter))
+        .any(|pool| pool.stop_idle_process())
+}
+
 pub fn run_with_timeout(
     cmd: &str,
     args: &[&str],
     stdin_input: Option<&str>,
     timeout: Duration,
 ) -> Result<String, String> {
+    let _permit = process_permit();
+    check_cancelled()?;
     let mut child = spawn_child(cmd, args)?;
+    let output = read_output(&mut child);
     write_stdin(&mut child, stdin_input)?;
-    wait_for_child(&mut child, timeout)
+    wait_for_child(&mut child, output, timeout)
+}
+
+/// Like [`run_with_timeout`], but through a shared [`WarmProcessPool`] of
+/// `pool_size` processes for `cmd` and `args`. A `pool_size` of 0 spawns a
+/// process per call.
+pub fn run_pooled(
+    cmd: &str,
+    args: &[&str],
+    stdin_input: &str,
+    timeout: Duration,
+    pool_size: usize,
+) -> Result<String, String> {
+    if pool_size == 0 {
+        return run_with_timeout(cmd, args, Some(stdin_input), timeout);
+    }
+
+    let key = (
+        cmd.to_string(),
+        args.iter().map(|arg| arg.to_string()).collect(),
+    );
+    let pool = WARM_POOLS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .entry(key)
+        .or_insert_with(|| Arc::new(WarmProcessPool::new(cmd, args, pool_size)))
+        .clone();
+    pool.run(stdin_input, timeout)
+}
+
+/// Processes of one command line started ahead of time, so that a
+/// conversion only pays for writing its input and reading the output, not
+/// for the process (and e.g. pandoc's runtime) starting up.
+///
+/// Each process still handles a single input: after a run, a replacement is
+/// started to keep up to `size` processes waiting on their stdin. Waiting
+/// processes count against the [`limit_concurrent_processes`] limit, and
+/// are stopped when another process needs their slot.
+pub struct WarmProcessPool {
+    cmd: String,
+    args: Vec<String>,
+    size: usize,
+    limiter: &'static ProcessLimiter,
+    idle: Mutex<Vec<(Child, ProcessPermit<'static>)>>,
+}
+
+impl WarmProcessPool {
+    pub fn new(cmd: &str, args: &[&str], size: usize) -> Self {
+        Self::with_limiter(cmd, args, size, &PROCESS_LIMITER)
+    }
+
+    fn with_limiter(
+        cmd: &str,
+        args: &[&str],
+        size: usize,
+        limiter: &'static ProcessLimiter,
+    ) -> Self {
+        Self {
+            cmd: cmd.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            size,
+            limiter,
+            idle: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Feed `stdin_input` to an idle process (or a new one when none is
+    /// waiting) and return its output, killing it after `timeout`.
+    pub fn run(&self, stdin_input: &str, timeout: Duration) -> Result<String, String> {
+        let idle = self.lock().pop();
+        let (mut child, permit) = match idle {
+            Some(idle) => idle,
+            None => {
+                let permit = acquire_permit(self.limiter);
+                check_cancelled()?;
+                (self.spawn()?, permit)
+            }
+        };
+        if let Err(err) = check_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(err);
+        }
+        let mut output = read_output(&mut child);
+        if write_stdin(&mut child, Some(stdin_input)).is_err() {
+            // The waiting process died; start over with a fresh one.
+            let _ = child.kill();
+            let _ = child.wait();
+            child = self.spawn()?;
+            output = read_output(&mut child);
+            write_stdin(&mut child, Some(stdin_input))?;
+        }
+        let result = wait_for_child(&mut child, output, timeout);
+        self.refill(permit);
+        result
+    }
+
+    /// Start a replacement process holding `permit`, unless the pool is full
+    /// or someone is waiting for the permit.
+    fn refill(&self, permit: ProcessPermit<'static>) {
+        let mut idle = self.lock();
+        if idle.len() < self.size && !self.limiter.has_waiters() {
+            if let Ok(child) = self.spawn() {
+                idle.push((child, permit));
+            }
+        }
+    }
+
+    /// Stop one idle process, releasing its permit. Returns whether there
+    /// was one.
+    fn stop_idle_process(&self) -> bool {
+        let Some((mut child, _permit)) = self.lock().pop() else {
+            return false;
+        };
+        let _ = child.kill();
+        let _ = child.wait();
+        true
+    }
+
+    fn spawn(&self) -> Result<Child, String> {
+        let args: Vec<&str> = self.args.iter().map(String::as_str).collect();
+        spawn_child(&self.cmd, &args)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<(Child, ProcessPermit<'static>)>> {
+        self.idle
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Drop for WarmProcessPool {
+    fn drop(&mut self) {
+        for (mut child, _permit) in self.lock().drain(..) {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
 }
 
 fn spawn_child(cmd: &str, args: &[&str]) -> Result<Child, String> {
@@ -38,26 +343,75 @@ fn write_stdin(child: &mut Child, stdin_input: Option<&str>) -> Result<(), Strin
     Ok(())
 }
 
-fn wait_for_child(child: &mut Child, timeout: Duration) -> Result<String, String> {
+/// The child's stdout and stderr, each drained on its own thread so that a
+/// child writing more than a pipe buffer never blocks waiting for us.
+struct ChildOutput {
+    stdout: thread::JoinHandle<std::io::Result<String>>,
+    stderr: thread::JoinHandle<std::io::Result<String>>,
+}
+
+impl ChildOutput {
+    fn stdout(self) -> Result<String, String> {
+        join_pipe(self.stdout).map_err(|e| format!("Output read failed: {}", e))
+    }
+
+    fn stderr(self) -> Result<String, String> {
+        join_pipe(self.stderr).map_err(|e| format!("Error read failed: {}", e))
+    }
+}
+
+fn read_output(child: &mut Child) -> ChildOutput {
+    ChildOutput {
+        stdout: read_pipe(child.stdout.take()),
+        stderr: read_pipe(child.stderr.take()),
+    }
+}
+
+fn read_pipe<R: Read + Send + 'static>(
+    pipe: Option<R>,
+) -> thread::JoinHandle<std::io::Result<String>> {
+    thread::spawn(move || {
+        let mut text = String::new();
+        if let Some(mut pipe) = pipe {
+            pipe.read_to_string(&mut text)?;
+        }
+        Ok(text)
+    })
+}
+
+fn join_pipe(reader: thread::JoinHandle<std::io::Result<String>>) -> std::io::Result<String> {
+    reader
+        .join()
+        .unwrap_or_else(|_| Err(std::io::Error::other("reader thread panicked")))
+}
+
+fn wait_for_child(
+    child: &mut Child,
+    output: ChildOutput,
+    timeout: Duration,
+) -> Result<String, String> {
     let start = Instant::now();
 
     loop {
         if start.elapsed() > timeout {
             let _ = child.kill();
+            let _ = child.wait();
             return Err(format!("Timeout after {:?}", timeout));
         }
+        if let Err(err) = check_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(err);
+        }
 
         if let Some(exit_status) = child
             .try_wait()
             .map_err(|e| format!("Process error: {}", e))?
         {
-            let output = read_stdout(child)?;
-
             if exit_status.success() {
-                return Ok(output);
+                return output.stdout();
             } else {
-                let error = read_stderr(child)?;
-                return Err(format!("Process failed: {}", error));
+                return Err(format!("Process failed: {}", output.stderr()?));
             }
         }
 
@@ -65,28 +419,6 @@ fn wait_for_child(child: &mut Child, timeout: Duration) -> Result<String, String
     }
 }
 
-fn read_stdout(child: &mut Child) -> Result<String, String> {
-    let mut output = String::new();
-    child
-        .stdout
-        .take()
-        .unwrap()
-        .read_to_string(&mut output)
-        .map_err(|e| format!("Output read failed: {}", e))?;
-    Ok(output)
-}
-
-fn read_stderr(child: &mut Child) -> Result<String, String> {
-    let mut error = String::new();
-    child
-        .stderr
-        .take()
-        .unwrap()
-        .read_to_string(&mut error)
-        .map_err(|e| format!("Error read failed: {}", e))?;
-    Ok(error)
-}
-
 #[test]
 fn test_run_with_timeout() {
     let result_1 = run_with_timeout("echo", &["1"], None, Duration::from_millis(100));
@@ -103,3 +435,128 @@ fn test_run_with_timeout() {
     assert!(result_3.is_err());
     assert_eq!(result_3.unwrap_err(), "Timeout after 10ms");
 }
+
+#[test]
+fn test_output_larger_than_a_pipe_buffer_does_not_stall() {
+    let started = Instant::now();
+    let output = run_with_timeout(
+        "head",
+        &["-c", "200000", "/dev/zero"],
+        None,
+        Duration::from_secs(5),
+    );
+    assert_eq!(output.unwrap().len(), 200000);
+
+    let input = "line\n".repeat(40000);
+    let pool = WarmProcessPool::new("cat", &[], 1);
+    assert_eq!(pool.run(&input, Duration::from_secs(5)).unwrap(), input);
+    assert!(
+        started.elapsed() < Duration::from_secs(2),
+        "{:?}",
+        started.elapsed()
+    );
+}
+
+#[test]
+fn test_warm_pool_matches_serial_runs() {
+    let inputs: Vec<String> = (0..12)
+        .map(|i| format!("line {i}\nanother {i}\n"))
+        .collect();
+    let serial: Vec<Result<String, String>> = inputs
+        .iter()
+        .map(|input| run_with_timeout("sort", &[], Some(input), Duration::from_secs(5)))
+        .collect();
+
+    let pool = WarmProcessPool::new("sort", &[], 3);
+    let pooled: Vec<Result<String, String>> = thread::scope(|scope| {
+        let handles: Vec<_> = inputs
+            .iter()
+            .map(|input| scope.spawn(|| pool.run(input, Duration::from_secs(5))))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+
+    assert_eq!(pooled, serial);
+    assert!(pool.lock().len() <= 3);
+
+    let slow = WarmProcessPool::new("sleep", &["5"], 1);
+    assert_eq!(
+        slow.run("", Duration::from_millis(10)).unwrap_err(),
+        "Timeout after 10ms"
+    );
+}
+
+#[test]
+fn test_cancelled_thread_kills_its_process() {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&cancelled);
+    let worker = thread::spawn(move || {
+        cancel_processes_on(flag);
+        let started = Instant::now();
+        let result = run_with_timeout("sleep", &["5"], None, Duration::from_secs(10));
+        (result, started.elapsed())
+    });
+
+    thread::sleep(Duration::from_millis(50));
+    cancelled.store(true, Ordering::SeqCst);
+    let (result, elapsed) = worker.join().unwrap();
+
+    assert_eq!(result.unwrap_err(), "Cancelled");
+    assert!(elapsed < Duration::from_secs(2), "{elapsed:?}");
+    assert!(check_cancelled().is_ok());
+}
+
+#[test]
+fn test_process_limiter_bounds_peak_concurrency() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static LIMITER: ProcessLimiter = ProcessLimiter::new(usize::MAX);
+    LIMITER.set_limit(2);
+    let running = AtomicUsize::new(0);
+    let peak = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        for _ in 0..8 {
+            scope.spawn(|| {
+                let _permit = acquire_permit(&LIMITER);
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(20));
+                running.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+    });
+
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_idle_warm_processes_count_against_the_limit() {
+    static LIMITER: ProcessLimiter = ProcessLimiter::new(2);
+    let pool = Arc::new(WarmProcessPool::with_limiter("cat", &[], 3, &LIMITER));
+    let key = ("cat".to_string(), vec!["idle-limit-test".to_string()]);
+    WARM_POOLS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap()
+        .insert(key.clone(), Arc::clone(&pool));
+
+    thread::scope(|scope| {
+        for i in 0..6 {
+            let pool = &pool;
+            scope.spawn(move || pool.run(&format!("{i}"), Duration::from_secs(5)));
+        }
+    });
+    assert!(pool.lock().len() <= 2);
+    assert_eq!(LIMITER.lock().running, pool.lock().len());
+
+    // Other processes take the slots of idle ones instead of waiting.
+    let permits: Vec<_> = (0..2).map(|_| acquire_permit(&LIMITER)).collect();
+    assert_eq!(permits.len(), 2);
+    assert!(pool.lock().is_empty());
+
+    WARM_POOLS.get().unwrap().lock().unwrap().remove(&key);
+}
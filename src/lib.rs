@@ -1,5 +1,7 @@
 pub mod config;
 pub mod content;
 pub mod formatted_text;
+pub mod logging;
 pub mod render;
+pub mod site;
 pub mod version;
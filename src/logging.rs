@@ -0,0 +1,218 @@
+use std::cell::RefCell;
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How much the command line tools print.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Only errors.
+    Quiet,
+    /// A short summary, warnings and errors.
+    #[default]
+    Normal,
+    /// Everything, including progress details and per-file timings.
+    Verbose,
+}
+
+impl Verbosity {
+    /// The level selected by `--quiet` and `--verbose`; quiet wins if both
+    /// are given.
+    pub fn from_flags(quiet: bool, verbose: bool) -> Self {
+        if quiet {
+            Verbosity::Quiet
+        } else if verbose {
+            Verbosity::Verbose
+        } else {
+            Verbosity::Normal
+        }
+    }
+}
+
+type Sink = Mutex<Box<dyn Write + Send>>;
+
+/// How much library code reports through [`warn`].
+static LIBRARY_VERBOSITY: Mutex<Verbosity> = Mutex::new(Verbosity::Normal);
+
+/// Set how much library code reports, from the `--quiet` and `--verbose`
+/// flags of the running tool.
+pub fn set_library_verbosity(verbosity: Verbosity) {
+    *LIBRARY_VERBOSITY
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = verbosity;
+}
+
+/// Report a warning from library code on stderr, unless `--quiet` was given.
+pub fn warn(message: impl Display) {
+    let verbosity = *LIBRARY_VERBOSITY
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if verbosity >= Verbosity::Normal {
+        eprintln!("Warning: {message}");
+    }
+}
+
+thread_local! {
+    static CURRENT_ITEM: RefCell<Option<PathBuf>> = const { RefCell::new(None) };
+}
+
+/// Run `render` with `path` as the [`current_item`] of this thread.
+pub fn with_current_item<T>(path: &Path, render: impl FnOnce() -> T) -> T {
+    let previous = CURRENT_ITEM.with(|item| item.replace(Some(path.to_path_buf())));
+    let result = render();
+    CURRENT_ITEM.with(|item| *item.borrow_mut() = previous);
+    result
+}
+
+/// The content item this thread is rendering, for warnings raised where
+/// the item is not known, such as in text conversion.
+pub fn current_item() -> Option<PathBuf> {
+    CURRENT_ITEM.with(|item| item.borrow().clone())
+}
+
+/// Writes messages at or below a [`Verbosity`]. Informational messages go
+/// to stdout, warnings and errors to stderr.
+pub struct Logger {
+    verbosity: Verbosity,
+    out: Sink,
+    err: Sink,
+}
+
+impl Logger {
+    pub fn new(verbosity: Verbosity) -> Self {
+        Self::with_writers(verbosity, Box::new(io::stdout()), Box::new(io::stderr()))
+    }
+
+    pub fn with_writers(
+        verbosity: Verbosity,
+        out: Box<dyn Write + Send>,
+        err: Box<dyn Write + Send>,
+    ) -> Self {
+        Self {
+            verbosity,
+            out: Mutex::new(out),
+            err: Mutex::new(err),
+        }
+    }
+
+    pub fn verbosity(&self) -> Verbosity {
+        self.verbosity
+    }
+
+    /// Progress details, shown with `--verbose`.
+    pub fn debug(&self, message: impl Display) {
+        if self.verbosity >= Verbosity::Verbose {
+            write_line(&self.out, message);
+        }
+    }
+
+    /// Summary lines, hidden by `--quiet`.
+    pub fn info(&self, message: impl Display) {
+        if self.verbosity >= Verbosity::Normal {
+            write_line(&self.out, message);
+        }
+    }
+
+    /// Warnings, hidden by `--quiet`.
+    pub fn warn(&self, message: impl Display) {
+        if self.verbosity >= Verbosity::Normal {
+            write_line(&self.err, message);
+        }
+    }
+
+    /// Always printed.
+    pub fn error(&self, message: impl Display) {
+        write_line(&self.err, message);
+    }
+
+    /// Run `step` and report how long it took at the verbose level.
+    pub fn timed<T>(&self, label: impl Display, step: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = step();
+        self.debug(format_args!(
+            "{label} took {}",
+            format_duration(start.elapsed())
+        ));
+        result
+    }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
+
+fn write_line(sink: &Sink, message: impl Display) {
+    let mut writer = sink.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    // There is nowhere left to report a failed write to stdout or stderr.
+    let _ = writeln!(writer, "{message}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Buffer {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    fn log_everything(verbosity: Verbosity) -> (String, String) {
+        let (out, err) = (Buffer::default(), Buffer::default());
+        let logger = Logger::with_writers(verbosity, Box::new(out.clone()), Box::new(err.clone()));
+
+        logger.debug("details");
+        logger.info("summary");
+        logger.warn("careful");
+        logger.error("failed");
+        logger.timed("step", || ());
+
+        (out.contents(), err.contents())
+    }
+
+    #[test]
+    fn quiet_suppresses_information_but_not_errors() {
+        let (out, err) = log_everything(Verbosity::from_flags(true, false));
+
+        assert_eq!(out, "");
+        assert_eq!(err, "failed\n");
+    }
+
+    #[test]
+    fn verbosity_levels_add_output() {
+        let (out, err) = log_everything(Verbosity::from_flags(false, false));
+        assert_eq!(out, "summary\n");
+        assert_eq!(err, "careful\nfailed\n");
+
+        let (out, _) = log_everything(Verbosity::from_flags(false, true));
+        assert!(out.starts_with("details\nsummary\nstep took "));
+    }
+
+    #[test]
+    fn current_item_is_scoped_to_the_render() {
+        assert_eq!(current_item(), None);
+
+        let inner = with_current_item(Path::new("content/p1"), || {
+            with_current_item(Path::new("content/p2"), current_item)
+        });
+
+        assert_eq!(inner, Some(PathBuf::from("content/p2")));
+        assert_eq!(current_item(), None);
+    }
+}
@@ -0,0 +1,48 @@
+use regex::{Captures, Regex};
+use std::sync::OnceLock;
+
+fn comment_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?s)<!--(.*?)-->").expect("valid HTML comment regex"))
+}
+
+/// Remove HTML comments from rendered output.
+///
+/// Conditional comments (`<!--[if IE]>...<![endif]-->`) and the livereload
+/// marker are kept since they affect how the page behaves.
+pub fn strip_html_comments(html: &str) -> String {
+    comment_regex()
+        .replace_all(html, |caps: &Captures| {
+            if should_keep_comment(&caps[1]) {
+                caps[0].to_string()
+            } else {
+                String::new()
+            }
+        })
+        .into_owned()
+}
+
+fn should_keep_comment(body: &str) -> bool {
+    let body = body.trim_start();
+    body.starts_with("[if") || body.ends_with("<![endif]") || body.contains("livereload")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_editorial_comments() {
+        let html = "<p>Visible<!-- TODO: rewrite --> text</p>\n<!--\nmulti\nline\n-->";
+
+        assert_eq!(strip_html_comments(html), "<p>Visible text</p>\n");
+    }
+
+    #[test]
+    fn keeps_conditional_and_livereload_comments() {
+        let html =
+            "<!--[if lt IE 9]><script src=\"shiv.js\"></script><![endif]--><!-- livereload -->";
+
+        assert_eq!(strip_html_comments(html), html);
+    }
+}
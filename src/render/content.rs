@@ -1,6 +1,11 @@
 use crate::content::Content;
 use crate::content::ContentMetadata;
+use crate::content::ContentNeighbors;
+use crate::content::{related_content, ContentLink, Solution};
 use crate::formatted_text::FormattedText;
+use crate::render::{
+    build_toc, problem_json_ld, reading_time, static_asset_url, TocEntry, DEFAULT_TOC_DEPTH,
+};
 use serde_json::json;
 use std::collections::HashMap;
 use std::error::Error;
@@ -20,18 +25,76 @@ fn merge_additional_context(
     }
 }
 
-fn context_with_title(metadata: &ContentMetadata) -> HashMap<String, serde_json::Value> {
+fn context_with_title(
+    renderer: &crate::render::Renderer,
+    metadata: &ContentMetadata,
+    neighbors: &ContentNeighbors,
+    config: &crate::config::Config,
+) -> Result<HashMap<String, serde_json::Value>, Box<dyn Error>> {
     let mut context = HashMap::new();
     context.insert("title".to_string(), json!(metadata.title.clone()));
+    context.insert("url".to_string(), json!(metadata.url));
+    context.insert(
+        "canonical".to_string(),
+        json!(metadata.canonical_url(config)),
+    );
+    context.insert(
+        "description".to_string(),
+        json!(metadata.description.as_ref().or(metadata.excerpt.as_ref())),
+    );
+    let source_root = crate::content::source_root(&metadata.source_path, config)?;
+    let image_url = metadata.image.as_ref().and_then(|image| {
+        static_asset_url(
+            &source_root,
+            &config.assets_dir,
+            &metadata.source_path,
+            &image.to_string_lossy(),
+        )
+        .ok()
+    });
+    context.insert("image_url".to_string(), json!(image_url));
+    context.insert("prev".to_string(), json!(neighbors.prev));
+    context.insert("next".to_string(), json!(neighbors.next));
+    context.insert(
+        "related".to_string(),
+        json!(related_links(renderer, metadata, config)?),
+    );
     merge_additional_context(&mut context, &metadata.context);
-    context
+    Ok(context)
 }
 
-fn rendered_sections(sections: &[FormattedText], config: &crate::config::Config) -> Vec<String> {
-    sections
-        .iter()
-        .filter_map(|section| section.to_html(config).ok())
-        .collect()
+/// The `related_items` items sharing the most tags with `metadata`; the
+/// content dir is only scanned for items that have tags.
+fn related_links(
+    renderer: &crate::render::Renderer,
+    metadata: &ContentMetadata,
+    config: &crate::config::Config,
+) -> Result<Vec<ContentLink>, Box<dyn Error>> {
+    if config.related_items == 0 || metadata.tags.iter().flatten().next().is_none() {
+        return Ok(Vec::new());
+    }
+    let items = renderer.content_items(config)?;
+    Ok(related_content(items, metadata, config.related_items))
+}
+
+/// Render `body` and add ids to its headings, returning the HTML together
+/// with the table of contents limited to the page's `toc_depth`.
+fn body_with_toc(
+    metadata: &ContentMetadata,
+    body: &FormattedText,
+    config: &crate::config::Config,
+) -> Result<(String, Vec<TocEntry>), Box<dyn Error>> {
+    let html = body.to_html(config)?;
+    Ok(build_toc(
+        &html,
+        metadata.toc_depth.unwrap_or(DEFAULT_TOC_DEPTH),
+    ))
+}
+
+fn successful_sections(
+    sections: impl Iterator<Item = Result<String, Box<dyn Error>>>,
+) -> Vec<String> {
+    sections.filter_map(Result::ok).collect()
 }
 
 impl Content {
@@ -40,16 +103,34 @@ impl Content {
         renderer: &crate::render::Renderer,
         config: &crate::config::Config,
     ) -> Result<String, Box<dyn Error>> {
-        match self {
+        self.render_html_with_neighbors(renderer, config, &ContentNeighbors::default())
+    }
+
+    /// Render the item with `prev`/`next` links to its neighbors in the
+    /// template context.
+    pub fn render_html_with_neighbors(
+        &self,
+        renderer: &crate::render::Renderer,
+        config: &crate::config::Config,
+        neighbors: &ContentNeighbors,
+    ) -> Result<String, Box<dyn Error>> {
+        let config = &*self.metadata().render_config(config);
+        crate::logging::with_current_item(&self.metadata().source_path, || match self {
             Content::Problem {
                 metadata,
                 statement,
                 solutions,
                 hints,
-            } => render_problem(renderer, config, metadata, statement, solutions, hints),
-            Content::Blog { metadata, body } => render_blog(renderer, config, metadata, body),
-            Content::Page { metadata, body } => render_page(renderer, config, metadata, body),
-        }
+            } => render_problem(
+                renderer, config, metadata, neighbors, statement, solutions, hints,
+            ),
+            Content::Blog { metadata, body } => {
+                render_blog(renderer, config, metadata, neighbors, body)
+            }
+            Content::Page { metadata, body } => {
+                render_page(renderer, config, metadata, neighbors, body)
+            }
+        })
     }
 }
 
@@ -57,11 +138,47 @@ fn render_problem(
     renderer: &crate::render::Renderer,
     config: &crate::config::Config,
     metadata: &ContentMetadata,
+    neighbors: &ContentNeighbors,
     statement: &FormattedText,
-    solutions: &[FormattedText],
+    solutions: &[Solution],
     hints: &[FormattedText],
 ) -> Result<String, Box<dyn Error>> {
-    let mut context = context_with_title(metadata);
+    let solutions: &[Solution] = if metadata.solutions_visible_at(super::functions::build_time()?) {
+        solutions
+    } else {
+        &[]
+    };
+
+    let sections: Vec<&FormattedText> = std::iter::once(statement)
+        .chain(solutions.iter().map(|solution| &solution.text))
+        .chain(hints)
+        .collect();
+    let mut rendered = FormattedText::to_html_batch(&sections, config).into_iter();
+    let statement = rendered
+        .next()
+        .ok_or("Missing rendered problem statement")??;
+    let titled_solutions: Vec<serde_json::Value> = solutions
+        .iter()
+        .enumerate()
+        .zip(rendered.by_ref().take(solutions.len()))
+        .filter_map(|((i, solution), html)| {
+            Some(json!({
+                "title": solution.label(i, renderer.translations()),
+                "html": html.ok()?,
+            }))
+        })
+        .collect();
+    let solutions: Vec<&serde_json::Value> = titled_solutions
+        .iter()
+        .map(|solution| &solution["html"])
+        .collect();
+    let hints = successful_sections(rendered);
+
+    let mut context = context_with_title(renderer, metadata, neighbors, config)?;
+    context.insert(
+        "json_ld".to_string(),
+        json!(problem_json_ld(metadata, &statement, config)),
+    );
     context.insert(
         "problem".to_string(),
         json!({
@@ -69,9 +186,13 @@ fn render_problem(
             "id": metadata.id,
             "tags": metadata.tags,
             "timestamp": metadata.timestamp,
-            "statement": statement.to_html(config)?,
-            "solutions": rendered_sections(solutions, config),
-            "hints": rendered_sections(hints, config),
+            "updated": metadata.updated,
+            "statement": statement,
+            "solutions": solutions,
+            "titled_solutions": titled_solutions,
+            "solutions_visible_after": metadata.solutions_visible_after,
+            "difficulty": metadata.difficulty,
+            "hints": hints,
             "image": metadata.image,
         }),
     );
@@ -86,9 +207,16 @@ fn render_blog(
     renderer: &crate::render::Renderer,
     config: &crate::config::Config,
     metadata: &ContentMetadata,
+    neighbors: &ContentNeighbors,
     body: &FormattedText,
 ) -> Result<String, Box<dyn Error>> {
-    let mut context = context_with_title(metadata);
+    let (body, toc) = body_with_toc(metadata, body, config)?;
+    let mut context = context_with_title(renderer, metadata, neighbors, config)?;
+    context.insert("toc".to_string(), json!(toc));
+    context.insert(
+        "reading_time".to_string(),
+        json!(reading_time(&body, config)),
+    );
     context.insert(
         "blog".to_string(),
         json!({
@@ -96,8 +224,10 @@ fn render_blog(
             "id": metadata.id,
             "tags": metadata.tags,
             "timestamp": metadata.timestamp,
-            "body": body.to_html(config)?,
-            "author": metadata.author,
+            "updated": metadata.updated,
+            "body": body,
+            "author": metadata.author(),
+            "authors": metadata.authors,
         }),
     );
 
@@ -108,15 +238,22 @@ fn render_page(
     renderer: &crate::render::Renderer,
     config: &crate::config::Config,
     metadata: &ContentMetadata,
+    neighbors: &ContentNeighbors,
     body: &FormattedText,
 ) -> Result<String, Box<dyn Error>> {
-    let mut context = context_with_title(metadata);
+    let (body, toc) = body_with_toc(metadata, body, config)?;
+    let mut context = context_with_title(renderer, metadata, neighbors, config)?;
+    context.insert("toc".to_string(), json!(toc));
+    context.insert(
+        "reading_time".to_string(),
+        json!(reading_time(&body, config)),
+    );
     context.insert(
         "page".to_string(),
         json!({
             "title": metadata.title,
             "id": metadata.id,
-            "body": body.to_html(config)?,
+            "body": body,
         }),
     );
 
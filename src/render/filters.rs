@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use tera::{Tera, Value};
+
+use super::slugify as slugify_text;
+use crate::config::Config;
+use crate::content::parse_date;
+use crate::formatted_text::{ConfigFingerprint, FormattedText};
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// An engine with only Tera's built-in filters and functions, for the
+/// overrides below to fall back on.
+pub(super) fn tera_builtins() -> &'static Tera {
+    static BUILTINS: OnceLock<Tera> = OnceLock::new();
+    BUILTINS.get_or_init(Tera::default)
+}
+
+/// `{{ blog.timestamp | date(format="%B %d, %Y") }}`
+///
+/// Accepts the same dates as metadata (RFC 3339 or `YYYY-MM-DD`). Values that
+/// do not parse are returned unchanged so one odd date cannot fail a page.
+/// Timestamps and the `timezone` and `locale` arguments are left to Tera's
+/// built-in `date`.
+pub fn date(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    if value.is_null() {
+        return Ok(Value::Null);
+    }
+    if !value.is_string() || args.keys().any(|key| key != "format") {
+        return tera_builtins().get_filter("date")?.filter(value, args);
+    }
+
+    let format = match args.get("format") {
+        Some(format) => format
+            .as_str()
+            .ok_or_else(|| tera::Error::msg("The `format` argument of `date` must be a string"))?,
+        None => DEFAULT_DATE_FORMAT,
+    };
+
+    let formatted = value
+        .as_str()
+        .and_then(|text| parse_date(text).ok())
+        .map(|date| date.format(format).to_string());
+
+    Ok(formatted.map_or_else(|| value.clone(), Value::String))
+}
+
+/// `{{ tag | slugify }}`, matching the ids given to headings.
+pub fn slugify(value: &Value, _args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let text = match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    };
+    Ok(Value::String(slugify_text(&text)))
+}
+
+/// `{{ context.bio | markdown }}`, rendered with the site's Markdown
+/// settings. The output is marked safe so Tera does not escape it again.
+pub struct Markdown {
+    config: Config,
+    fingerprint: ConfigFingerprint,
+}
+
+impl Markdown {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            config: config.clone(),
+            fingerprint: ConfigFingerprint::new(config),
+        }
+    }
+}
+
+impl tera::Filter for Markdown {
+    fn filter(&self, value: &Value, _args: &HashMap<String, Value>) -> tera::Result<Value> {
+        let markdown = value
+            .as_str()
+            .ok_or_else(|| tera::Error::msg("The `markdown` filter expects a string"))?;
+        FormattedText::Markdown(markdown.to_string())
+            .to_html_keyed(&self.config, self.fingerprint)
+            .map(Value::String)
+            .map_err(|e| tera::Error::msg(format!("Failed to render Markdown: {}", e)))
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format_args(format: &str) -> HashMap<String, Value> {
+        HashMap::from([("format".to_string(), Value::String(format.to_string()))])
+    }
+
+    #[test]
+    fn date_formats_rfc3339_and_date_only_values() {
+        let args = format_args("%B %d, %Y");
+
+        assert_eq!(
+            date(&Value::from("2025-03-06T12:00:00Z"), &args).unwrap(),
+            "March 06, 2025"
+        );
+        assert_eq!(
+            date(&Value::from("2025-03-06"), &args).unwrap(),
+            "March 06, 2025"
+        );
+    }
+
+    #[test]
+    fn date_returns_unparseable_input_unchanged() {
+        assert_eq!(
+            date(&Value::from("someday"), &HashMap::new()).unwrap(),
+            "someday"
+        );
+        assert_eq!(date(&Value::Null, &HashMap::new()).unwrap(), Value::Null);
+    }
+
+    #[test]
+    fn date_leaves_timestamps_and_timezones_to_the_builtin() {
+        assert_eq!(
+            date(&Value::from(1_700_000_000), &format_args("%Y")).unwrap(),
+            "2023"
+        );
+
+        let mut args = format_args("%Y-%m-%d %H:%M");
+        args.insert("timezone".to_string(), Value::from("Asia/Tokyo"));
+        assert_eq!(
+            date(&Value::from("2025-03-06T23:30:00Z"), &args).unwrap(),
+            "2025-03-07 08:30"
+        );
+    }
+
+    #[test]
+    fn slugify_handles_unicode() {
+        assert_eq!(
+            slugify(&Value::from("  Ünïcode — Täg  "), &HashMap::new()).unwrap(),
+            "ünïcode-täg"
+        );
+    }
+}
@@ -0,0 +1,321 @@
+use chrono::{DateTime, Local, TimeZone, Utc};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use tera::Value;
+
+use crate::config::Config;
+use crate::content::{find_content_files, join_base_url, TrailingSlash};
+
+/// `{{ og_tags(title=title, description=description, url=url, image=image_url) }}`
+///
+/// Emits the OpenGraph and Twitter card `<meta>` tags for a page, with its
+/// url and image made absolute against `base_url`. The output is marked safe
+/// so Tera does not escape it again.
+pub struct OgTags {
+    base_url: Option<String>,
+    trailing_slash: TrailingSlash,
+}
+
+impl OgTags {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            base_url: config.base_url.clone(),
+            trailing_slash: config.trailing_slash,
+        }
+    }
+
+    fn absolute_url(&self, url: &str) -> String {
+        match &self.base_url {
+            Some(base_url) => join_base_url(base_url, url, self.trailing_slash),
+            None => url.to_string(),
+        }
+    }
+}
+
+impl tera::Function for OgTags {
+    fn call(&self, args: &HashMap<String, Value>) -> tera::Result<Value> {
+        let arg = |name: &str| -> tera::Result<Option<String>> {
+            match args.get(name) {
+                None | Some(Value::Null) => Ok(None),
+                Some(Value::String(value)) => Ok(Some(value.clone())),
+                Some(_) => Err(tera::Error::msg(format!(
+                    "The `{name}` argument of `og_tags` must be a string"
+                ))),
+            }
+        };
+        let url = arg("url")?.map(|url| self.absolute_url(&url));
+        let image = arg("image")?.map(|image| self.absolute_url(&image));
+        let og_type = arg("type")?.unwrap_or_else(|| "website".to_string());
+
+        let mut tags = Vec::new();
+        let mut push = |attribute: &str, name: &str, content: &Option<String>| {
+            if let Some(content) = content {
+                tags.push(format!(
+                    r#"<meta {attribute}="{name}" content="{}">"#,
+                    escape_attribute(content)
+                ));
+            }
+        };
+        let title = arg("title")?;
+        let description = arg("description")?;
+        let card = if image.is_some() {
+            "summary_large_image"
+        } else {
+            "summary"
+        };
+
+        push("property", "og:type", &Some(og_type));
+        push("property", "og:title", &title);
+        push("property", "og:description", &description);
+        push("property", "og:url", &url);
+        push("property", "og:image", &image);
+        push("name", "twitter:card", &Some(card.to_string()));
+        push("name", "twitter:title", &title);
+        push("name", "twitter:description", &description);
+        push("name", "twitter:image", &image);
+
+        Ok(Value::String(tags.join("\n")))
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
+
+/// `{{ asset(path="css/site.css") }}`
+///
+/// Returns the rooted url of a file in the build dir with a `?v=` query
+/// string derived from its contents, so browsers fetch it again once it
+/// changes. Each file is hashed once per renderer.
+pub struct Asset {
+    build_dir: PathBuf,
+    hashes: Mutex<HashMap<String, String>>,
+}
+
+impl Asset {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            build_dir: config.build_dir.clone(),
+            hashes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn hash(&self, path: &str) -> tera::Result<String> {
+        let mut hashes = self.hashes.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(hash) = hashes.get(path) {
+            return Ok(hash.clone());
+        }
+
+        let file = self.build_dir.join(path);
+        let contents = fs::read(&file).map_err(|e| {
+            tera::Error::msg(format!("Cannot read asset {}: {}", file.display(), e))
+        })?;
+        let hash = format!("{:016x}", fnv1a(&contents));
+        hashes.insert(path.to_string(), hash.clone());
+        Ok(hash)
+    }
+}
+
+impl tera::Function for Asset {
+    fn call(&self, args: &HashMap<String, Value>) -> tera::Result<Value> {
+        let path = args
+            .get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| tera::Error::msg("`asset` needs a string `path` argument"))?
+            .trim_start_matches('/');
+        let hash = self.hash(path)?;
+        Ok(Value::String(format!("/{path}?v={hash}")))
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
+
+/// `{{ url_for(id="sample-problem-001") }}`
+///
+/// Returns the url of the content item with the given `id`. The content dir
+/// is scanned on first use; unknown ids are an error so broken links fail
+/// the render.
+pub struct UrlFor {
+    config: Config,
+    urls: OnceLock<Result<HashMap<String, String>, String>>,
+}
+
+impl UrlFor {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            config: config.clone(),
+            urls: OnceLock::new(),
+        }
+    }
+
+    fn urls(&self) -> tera::Result<&HashMap<String, String>> {
+        self.urls
+            .get_or_init(|| {
+                let items = find_content_files(&self.config.content_dir, None, &self.config)
+                    .map_err(|e| format!("Cannot scan content for `url_for`: {e}"))?;
+                Ok(items
+                    .into_iter()
+                    .filter_map(|item| Some((item.id?, item.url)))
+                    .collect())
+            })
+            .as_ref()
+            .map_err(|e| tera::Error::msg(e.clone()))
+    }
+}
+
+impl tera::Function for UrlFor {
+    fn call(&self, args: &HashMap<String, Value>) -> tera::Result<Value> {
+        let id = args
+            .get("id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| tera::Error::msg("`url_for` needs a string `id` argument"))?;
+        self.urls()?
+            .get(id)
+            .map(|url| Value::String(url.clone()))
+            .ok_or_else(|| tera::Error::msg(format!("`url_for`: no content with id `{id}`")))
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
+
+/// `{{ now(format="%Y") }}`
+///
+/// Tera's built-in `now`, the local time as RFC 3339 (`utc=true` for UTC,
+/// `timestamp=true` for seconds since the epoch), with an added `format`
+/// argument. For reproducible builds the time is [`build_time`].
+pub fn now(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    if std::env::var_os("SOURCE_DATE_EPOCH").is_none() && !args.contains_key("format") {
+        return super::filters::tera_builtins()
+            .get_function("now")?
+            .call(args);
+    }
+    now_at(build_time().map_err(tera::Error::msg)?, args)
+}
+
+fn now_at(time: DateTime<Utc>, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    if bool_arg(args, "timestamp")? {
+        return Ok(Value::from(time.timestamp()));
+    }
+    let format =
+        match args.get("format") {
+            Some(format) => Some(format.as_str().ok_or_else(|| {
+                tera::Error::msg("The `format` argument of `now` must be a string")
+            })?),
+            None => None,
+        };
+    let formatted = if bool_arg(args, "utc")? {
+        format_time(&time, format)
+    } else {
+        format_time(&time.with_timezone(&Local), format)
+    };
+    Ok(Value::String(formatted))
+}
+
+fn format_time<Tz: TimeZone>(time: &DateTime<Tz>, format: Option<&str>) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    match format {
+        Some(format) => time.format(format).to_string(),
+        None => time.to_rfc3339(),
+    }
+}
+
+fn bool_arg(args: &HashMap<String, Value>, name: &str) -> tera::Result<bool> {
+    match args.get(name) {
+        None => Ok(false),
+        Some(Value::Bool(value)) => Ok(*value),
+        Some(other) => Err(tera::Error::msg(format!(
+            "Function `now` received {name}={other} but `{name}` can only be a boolean"
+        ))),
+    }
+}
+
+/// The time of the build: `SOURCE_DATE_EPOCH` (seconds since the epoch)
+/// when that is set, for reproducible builds, or else the current time.
+pub fn build_time() -> Result<DateTime<Utc>, String> {
+    parse_build_time(std::env::var("SOURCE_DATE_EPOCH").ok().as_deref())
+}
+
+fn parse_build_time(source_date_epoch: Option<&str>) -> Result<DateTime<Utc>, String> {
+    let Some(epoch) = source_date_epoch else {
+        return Ok(Utc::now());
+    };
+    epoch
+        .trim()
+        .parse()
+        .ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .ok_or_else(|| format!("Invalid SOURCE_DATE_EPOCH `{epoch}`"))
+}
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` is the same across builds.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+
+    #[test]
+    fn now_formats_the_current_or_pinned_time() -> tera::Result<()> {
+        let args = HashMap::from([("format".to_string(), Value::from("%Y"))]);
+        if std::env::var_os("SOURCE_DATE_EPOCH").is_none() {
+            assert_eq!(now(&args)?, Value::from(Local::now().year().to_string()));
+        }
+
+        let pinned = parse_build_time(Some("1700000000")).map_err(tera::Error::msg)?;
+        assert_eq!(pinned.to_rfc3339(), "2023-11-14T22:13:20+00:00");
+        assert!(parse_build_time(Some("soon")).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn now_keeps_the_builtin_arguments() -> tera::Result<()> {
+        let pinned = parse_build_time(Some("1700000000")).map_err(tera::Error::msg)?;
+        let args = |pairs: &[(&str, Value)]| -> HashMap<String, Value> {
+            pairs
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect()
+        };
+
+        assert_eq!(
+            now_at(pinned, &args(&[("timestamp", Value::Bool(true))]))?,
+            Value::from(1_700_000_000)
+        );
+        assert_eq!(
+            now_at(pinned, &args(&[("utc", Value::Bool(true))]))?,
+            Value::from("2023-11-14T22:13:20+00:00")
+        );
+        assert_eq!(
+            now_at(
+                pinned,
+                &args(&[("utc", Value::Bool(true)), ("format", Value::from("%H:%M"))])
+            )?,
+            Value::from("22:13")
+        );
+        assert!(now_at(pinned, &args(&[("utc", Value::from("yes"))])).is_err());
+
+        Ok(())
+    }
+}
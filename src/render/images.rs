@@ -1,16 +1,24 @@
 use regex::{Captures, Regex};
+use std::collections::HashMap;
 use std::error::Error;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
-use walkdir::{DirEntry, WalkDir};
+use walkdir::WalkDir;
+
+use crate::config::Config;
 
 static IMG_REGEX: OnceLock<Regex> = OnceLock::new();
 static CSS_URL_REGEX: OnceLock<Regex> = OnceLock::new();
+static ANCHOR_REGEX: OnceLock<Regex> = OnceLock::new();
+static SRCSET_REGEX: OnceLock<Regex> = OnceLock::new();
+static MEDIA_TAG_REGEX: OnceLock<Regex> = OnceLock::new();
+static MEDIA_ATTRIBUTE_REGEX: OnceLock<Regex> = OnceLock::new();
+static NON_MARKUP_REGEX: OnceLock<Regex> = OnceLock::new();
 
-const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "bmp", "tiff", "svg"];
-const STATIC_ASSETS_DIR: &str = "static/assets";
-
+/// Raster formats that get a WebP copy with `convert_webp`. The WebP encoder
+/// is lossless only, which rarely beats a JPEG, so JPEGs are left alone.
+const WEBP_SOURCE_EXTENSIONS: &[&str] = &["png"];
 fn absolute_path(path: PathBuf) -> Result<PathBuf, Box<dyn Error>> {
     if path.is_absolute() {
         Ok(path)
@@ -25,45 +33,176 @@ fn img_regex() -> &'static Regex {
     })
 }
 
+/// Comments and the contents of elements whose text is not parsed as
+/// markup, where `<img ...>` is not an image.
+fn non_markup_regex() -> &'static Regex {
+    NON_MARKUP_REGEX.get_or_init(|| {
+        Regex::new(concat!(
+            r"(?is)<!--.*?-->",
+            r"|<script\b.*?</script\s*>",
+            r"|<style\b.*?</style\s*>",
+            r"|<textarea\b.*?</textarea\s*>",
+            r"|<template\b.*?</template\s*>",
+        ))
+        .expect("valid non-markup regex")
+    })
+}
+
+fn srcset_regex() -> &'static Regex {
+    SRCSET_REGEX
+        .get_or_init(|| Regex::new(r#"(\ssrcset=["'])([^"']+)(["'])"#).expect("valid srcset regex"))
+}
+
+fn media_tag_regex() -> &'static Regex {
+    MEDIA_TAG_REGEX
+        .get_or_init(|| Regex::new(r"<(?:source|video)\s[^>]*>").expect("valid media tag regex"))
+}
+
+fn media_attribute_regex() -> &'static Regex {
+    MEDIA_ATTRIBUTE_REGEX.get_or_init(|| {
+        Regex::new(r#"(\s(?:src|poster)=)(["'])([^"']+)["']"#).expect("valid media attribute regex")
+    })
+}
+
+fn anchor_regex() -> &'static Regex {
+    ANCHOR_REGEX.get_or_init(|| {
+        Regex::new(r#"<a\s+[^>]*href=["']([^"']+)["'][^>]*>"#).expect("valid anchor regex")
+    })
+}
+
 fn css_url_regex() -> &'static Regex {
     CSS_URL_REGEX
         .get_or_init(|| Regex::new(r#"url\(['"]?([^'"\)]+)['"]?\)"#).expect("valid css url regex"))
 }
 
-fn find_images(root: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
-    let mut images = Vec::new();
+/// Files below `root` with one of the extensions of each of
+/// `extension_sets`, relative to `root`: one list per set, found in a single
+/// walk, each file in the first set that has its extension. Symlinked directories are entered when `follow_links` is set; links
+/// that loop back to an ancestor are skipped.
+fn find_files_with_extensions<const N: usize>(
+    root: &Path,
+    extension_sets: [&[String]; N],
+    follow_links: bool,
+) -> Result<[Vec<PathBuf>; N], Box<dyn Error>> {
+    let mut found = std::array::from_fn(|_| Vec::new());
+    if extension_sets
+        .iter()
+        .all(|extensions| extensions.is_empty())
+    {
+        return Ok(found);
+    }
 
-    for entry in WalkDir::new(root) {
-        let entry = entry?;
-        if is_image_file(&entry) {
-            images.push(entry.path().strip_prefix(root)?.to_path_buf());
+    for entry in WalkDir::new(root).follow_links(follow_links) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) if err.loop_ancestor().is_some() => {
+                crate::logging::warn(format_args!("Skipping symlink loop: {}", err));
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        };
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Some(ext) = entry.path().extension().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let set = extension_sets.iter().position(|extensions| {
+            extensions
+                .iter()
+                .any(|allowed| ext.eq_ignore_ascii_case(allowed))
+        });
+        if let Some(set) = set {
+            found[set].push(entry.path().strip_prefix(root)?.to_path_buf());
         }
     }
 
-    Ok(images)
-}
-
-fn is_image_file(entry: &DirEntry) -> bool {
-    entry.file_type().is_file()
-        && entry
-            .path()
-            .extension()
-            .and_then(|s| s.to_str())
-            .map(|ext| {
-                IMAGE_EXTENSIONS
-                    .iter()
-                    .any(|allowed| ext.eq_ignore_ascii_case(allowed))
-            })
-            .unwrap_or(false)
+    Ok(found)
 }
 
 fn prefix_image_urls(html: &str, image_paths: &[PathBuf], root_url: &str) -> String {
     let normalized_paths: Vec<String> = image_paths.iter().map(normalize_path).collect();
     let html = prefix_img_tags(html, &normalized_paths, root_url);
     let html = prefix_css_urls(&html, &normalized_paths, root_url);
+    let html = prefix_srcsets(&html, &normalized_paths, root_url);
+    let html = prefix_media_tags(&html, &normalized_paths, root_url);
     html.to_string()
 }
 
+/// Prefix `<a href>` links to copied assets, the way image urls are.
+fn prefix_anchor_links(html: &str, assets: &[PathBuf], root_url: &str) -> String {
+    if assets.is_empty() {
+        return html.to_string();
+    }
+
+    let normalized_paths: Vec<String> = assets.iter().map(normalize_path).collect();
+    anchor_regex()
+        .replace_all(html, |caps: &Captures| {
+            let full_match = &caps[0];
+            let href = &caps[1];
+            if should_prefix(href, &normalized_paths) {
+                full_match.replace(href, &format!("{}{}", root_url, href))
+            } else {
+                full_match.to_string()
+            }
+        })
+        .to_string()
+}
+
+/// Prefix every candidate url of `srcset` attributes, keeping the width or
+/// density descriptors.
+fn prefix_srcsets<'a>(
+    html: &'a str,
+    normalized_paths: &'a [String],
+    root_url: &'a str,
+) -> std::borrow::Cow<'a, str> {
+    srcset_regex().replace_all(html, |caps: &Captures| {
+        let candidates: Vec<String> = caps[2]
+            .split(',')
+            .map(|candidate| {
+                let candidate = candidate.trim();
+                let (url, descriptor) = candidate
+                    .split_once(char::is_whitespace)
+                    .map_or((candidate, ""), |(url, descriptor)| {
+                        (url, descriptor.trim())
+                    });
+                let url = if should_prefix(url, normalized_paths) {
+                    format!("{}{}", root_url, url)
+                } else {
+                    url.to_string()
+                };
+                if descriptor.is_empty() {
+                    url
+                } else {
+                    format!("{} {}", url, descriptor)
+                }
+            })
+            .collect();
+        format!("{}{}{}", &caps[1], candidates.join(", "), &caps[3])
+    })
+}
+
+/// Prefix the `src` of `<source>` and the `src`/`poster` of `<video>` tags.
+fn prefix_media_tags<'a>(
+    html: &'a str,
+    normalized_paths: &'a [String],
+    root_url: &'a str,
+) -> std::borrow::Cow<'a, str> {
+    media_tag_regex().replace_all(html, |caps: &Captures| {
+        media_attribute_regex()
+            .replace_all(&caps[0], |attribute: &Captures| {
+                let url = &attribute[3];
+                if should_prefix(url, normalized_paths) {
+                    let quote = &attribute[2];
+                    format!("{}{quote}{root_url}{url}{quote}", &attribute[1])
+                } else {
+                    attribute[0].to_string()
+                }
+            })
+            .to_string()
+    })
+}
+
 fn prefix_img_tags<'a>(
     html: &'a str,
     normalized_paths: &'a [String],
@@ -126,28 +265,79 @@ pub struct ImageProcessor {
     path: PathBuf,
     content_dir: PathBuf,
     build_dir: PathBuf,
+    /// Where the images go, relative to the build dir.
+    assets_dir: PathBuf,
     images: Vec<PathBuf>,
+    /// Pixel size of each raster image, by normalized path.
+    dimensions: HashMap<String, (u32, u32)>,
     url_prefix: Option<String>,
+    convert_webp: bool,
+    lazy_images: bool,
+    /// Images that got a WebP copy, by their original path.
+    webp_images: Vec<PathBuf>,
+    /// Other files copied along with the images, such as PDFs.
+    assets: Vec<PathBuf>,
 }
 
 impl ImageProcessor {
+    /// Processor for the content at `path`, finding images with one of
+    /// `image_extensions` and copying them to `assets_dir` in `build_dir`.
     pub fn new(
         path: PathBuf,
         content_dir: PathBuf,
         build_dir: PathBuf,
+        image_extensions: &[String],
+        assets_dir: PathBuf,
     ) -> Result<Self, Box<dyn Error>> {
-        let path = absolute_path(path)?;
-        let content_dir = absolute_path(content_dir)?;
-        let path = content_root(path)?;
+        let path = content_root(absolute_path(path)?)?;
+        let [images] = find_files_with_extensions(&path, [image_extensions], false)?;
+        Self::with_files(path, content_dir, build_dir, assets_dir, images, Vec::new())
+    }
 
-        let images = find_images(&path)?;
+    /// Processor for the content at `path` with the image options of `config`.
+    pub fn for_content(path: PathBuf, config: &Config) -> Result<Self, Box<dyn Error>> {
+        let content_dir = crate::content::source_root(&path, config)?;
+        let path = content_root(absolute_path(path)?)?;
+        let [images, assets] = find_files_with_extensions(
+            &path,
+            [&config.image_extensions, &config.asset_extensions],
+            config.follow_symlinks,
+        )?;
+        let mut processor = Self::with_files(
+            path,
+            content_dir,
+            config.build_dir.clone(),
+            config.assets_dir.clone(),
+            images,
+            assets,
+        )?;
+        processor.convert_webp = config.convert_webp;
+        processor.lazy_images = config.lazy_images;
+        Ok(processor)
+    }
+
+    fn with_files(
+        path: PathBuf,
+        content_dir: PathBuf,
+        build_dir: PathBuf,
+        assets_dir: PathBuf,
+        images: Vec<PathBuf>,
+        assets: Vec<PathBuf>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let dimensions = image_dimensions(&path, &images);
 
         Ok(Self {
             path,
-            content_dir,
+            content_dir: absolute_path(content_dir)?,
             build_dir,
+            assets_dir,
             images,
+            dimensions,
             url_prefix: None,
+            convert_webp: false,
+            lazy_images: false,
+            webp_images: Vec::new(),
+            assets,
         })
     }
 
@@ -155,28 +345,62 @@ impl ImageProcessor {
         !self.images.is_empty()
     }
 
+    /// Whether there are non-image assets to copy.
+    pub fn has_assets(&self) -> bool {
+        !self.assets.is_empty()
+    }
+
     pub fn image_count(&self) -> usize {
         self.images.len()
     }
 
+    /// Local `<img>` sources in `html` that are not among the images found
+    /// next to the content. Urls, `data:` and rooted paths are not checked,
+    /// nor is `<img` text inside comments, scripts and the like.
+    pub fn broken_image_references(&self, html: &str) -> Vec<String> {
+        let image_paths: Vec<String> = self.images.iter().map(normalize_path).collect();
+        let markup = non_markup_regex().replace_all(html, "");
+        img_regex()
+            .captures_iter(&markup)
+            .map(|caps| caps[1].to_string())
+            .filter(|src| !is_external_or_rooted_path(src))
+            .filter(|src| {
+                let path = src.split(['?', '#']).next().unwrap_or_default();
+                let path = path.trim_start_matches("./");
+                !image_paths.iter().any(|image| image == path)
+            })
+            .collect()
+    }
+
+    /// Copy the images, and any other assets, to `assets_dir` in the build
+    /// dir.
     pub fn copy_images_to_build_dir(&mut self) -> Result<(), Box<dyn Error>> {
-        if self.images.is_empty() {
+        if self.images.is_empty() && self.assets.is_empty() {
             return Ok(());
         }
 
-        let rel_path = self.path.strip_prefix(&self.content_dir)?;
-        let static_assets_dir = self.build_dir.join(STATIC_ASSETS_DIR).join(rel_path);
+        let rel_path = self.path.strip_prefix(&self.content_dir)?.to_path_buf();
+        let static_assets_dir = self.build_dir.join(&self.assets_dir).join(&rel_path);
 
         fs::create_dir_all(&static_assets_dir)?;
         self.copy_images(&static_assets_dir)?;
-        self.url_prefix = Some(format!("/{STATIC_ASSETS_DIR}/{}/", rel_path.display()));
+        self.copy_assets(&static_assets_dir)?;
+        self.url_prefix = Some(static_assets_prefix(&self.assets_dir, &rel_path));
 
         Ok(())
     }
 
     pub fn update_html_with_image_urls(&self, html: &str) -> String {
         if let Some(ref prefix) = self.url_prefix {
-            prefix_image_urls(html, &self.images, prefix)
+            let html = prefix_image_urls(html, &self.images, prefix);
+            let html = add_img_dimensions(&html, &self.dimensions, prefix);
+            let html = if self.lazy_images {
+                add_lazy_loading(&html, prefix)
+            } else {
+                html
+            };
+            let html = prefix_anchor_links(&html, &self.assets, prefix);
+            point_img_tags_at_webp(&html, &self.webp_images, prefix)
         } else {
             html.to_string()
         }
@@ -189,7 +413,21 @@ impl ImageProcessor {
             .collect()
     }
 
-    fn copy_images(&self, static_assets_dir: &Path) -> Result<(), Box<dyn Error>> {
+    fn copy_assets(&self, static_assets_dir: &Path) -> Result<(), Box<dyn Error>> {
+        for asset in &self.assets {
+            let target_path = static_assets_dir.join(asset);
+            if let Some(parent) = target_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            copy_if_changed(&self.path.join(asset), &target_path)?;
+        }
+
+        Ok(())
+    }
+
+    fn copy_images(&mut self, static_assets_dir: &Path) -> Result<(), Box<dyn Error>> {
+        let mut webp_images = Vec::new();
+
         for image in &self.images {
             let source_path = self.path.join(image);
             let target_path = static_assets_dir.join(image);
@@ -198,13 +436,201 @@ impl ImageProcessor {
                 fs::create_dir_all(parent)?;
             }
 
-            fs::copy(source_path, target_path)?;
+            copy_if_changed(&source_path, &target_path)?;
+
+            if self.convert_webp && is_webp_source(image) {
+                let webp_path = webp_copy_path(&target_path);
+                if is_newer(&source_path, &webp_path)? {
+                    write_webp(&source_path, &webp_path)?;
+                }
+                webp_images.push(image.clone());
+            }
         }
 
+        self.webp_images = webp_images;
         Ok(())
     }
 }
 
+/// Read the pixel size of each image from its header. SVGs and files that
+/// cannot be read are left out.
+fn image_dimensions(root: &Path, images: &[PathBuf]) -> HashMap<String, (u32, u32)> {
+    images
+        .iter()
+        .filter_map(|image| {
+            let dimensions = image::image_dimensions(root.join(image)).ok()?;
+            Some((normalize_path(image), dimensions))
+        })
+        .collect()
+}
+
+fn has_attribute(tag: &str, name: &str) -> bool {
+    let prefix = format!("{name}=");
+    tag.split_whitespace()
+        .any(|part| part.to_ascii_lowercase().starts_with(&prefix))
+}
+
+/// Add the intrinsic `width` and `height` to `<img>` tags pointing at an
+/// image below `root_url`, unless the tag sets either already.
+fn add_img_dimensions(
+    html: &str,
+    dimensions: &HashMap<String, (u32, u32)>,
+    root_url: &str,
+) -> String {
+    img_regex()
+        .replace_all(html, |caps: &Captures| {
+            let full_match = &caps[0];
+            let size = caps[1]
+                .strip_prefix(root_url)
+                .and_then(|image| dimensions.get(&normalize_path(Path::new(image))));
+
+            match size {
+                Some((width, height))
+                    if !has_attribute(full_match, "width")
+                        && !has_attribute(full_match, "height") =>
+                {
+                    full_match.replacen(
+                        "<img",
+                        &format!(r#"<img width="{width}" height="{height}""#),
+                        1,
+                    )
+                }
+                _ => full_match.to_string(),
+            }
+        })
+        .to_string()
+}
+
+/// Add `loading="lazy"` and `decoding="async"` to `<img>` tags pointing at
+/// an image below `root_url`, keeping values the tag already sets.
+fn add_lazy_loading(html: &str, root_url: &str) -> String {
+    img_regex()
+        .replace_all(html, |caps: &Captures| {
+            let full_match = &caps[0];
+            if !caps[1].starts_with(root_url) {
+                return full_match.to_string();
+            }
+
+            let mut attributes = String::new();
+            if !has_attribute(full_match, "loading") {
+                attributes.push_str(r#" loading="lazy""#);
+            }
+            if !has_attribute(full_match, "decoding") {
+                attributes.push_str(r#" decoding="async""#);
+            }
+            full_match.replacen("<img", &format!("<img{attributes}"), 1)
+        })
+        .to_string()
+}
+
+/// Copy `source` to `target` unless `target` already has the same size and is
+/// at least as new. Returns whether a copy was made.
+fn copy_if_changed(source: &Path, target: &Path) -> Result<bool, Box<dyn Error>> {
+    let source_size = fs::metadata(source)?.len();
+    let same_size = fs::metadata(target).is_ok_and(|meta| meta.len() == source_size);
+    if same_size && !is_newer(source, target)? {
+        return Ok(false);
+    }
+
+    fs::copy(source, target)?;
+    Ok(true)
+}
+
+/// Whether `source` was modified after `target`, or `target` is missing.
+fn is_newer(source: &Path, target: &Path) -> Result<bool, Box<dyn Error>> {
+    let Ok(target_modified) = fs::metadata(target).and_then(|meta| meta.modified()) else {
+        return Ok(true);
+    };
+    Ok(fs::metadata(source)?.modified()? > target_modified)
+}
+
+fn is_webp_source(image: &Path) -> bool {
+    image
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            WEBP_SOURCE_EXTENSIONS
+                .iter()
+                .any(|allowed| ext.eq_ignore_ascii_case(allowed))
+        })
+}
+
+/// Where the WebP copy of `image` goes: `photo.png` becomes `photo.png.webp`,
+/// so it cannot clash with another image of the same stem.
+fn webp_copy_path(image: &Path) -> PathBuf {
+    let mut path = image.as_os_str().to_owned();
+    path.push(".webp");
+    PathBuf::from(path)
+}
+
+fn write_webp(source_path: &Path, target_path: &Path) -> Result<(), Box<dyn Error>> {
+    let image = image::open(source_path)
+        .map_err(|e| format!("Failed to read image {}: {}", source_path.display(), e))?;
+    image
+        .save_with_format(target_path, image::ImageFormat::WebP)
+        .map_err(|e| format!("Failed to write {}: {}", target_path.display(), e).into())
+}
+
+/// Point the `src` of `<img>` tags already prefixed with `root_url` at the
+/// WebP copy of the image, when one was written.
+fn point_img_tags_at_webp(html: &str, webp_images: &[PathBuf], root_url: &str) -> String {
+    if webp_images.is_empty() {
+        return html.to_string();
+    }
+
+    img_regex()
+        .replace_all(html, |caps: &Captures| {
+            let full_match = &caps[0];
+            let src = &caps[1];
+            let webp = src.strip_prefix(root_url).and_then(|image| {
+                webp_images.iter().find(|webp_image| {
+                    normalize_path(webp_image) == normalize_path(Path::new(image))
+                })
+            });
+
+            match webp {
+                Some(image) => {
+                    let webp_src = format!("{}{}", root_url, normalize_path(webp_copy_path(image)));
+                    full_match.replace(src, &webp_src)
+                }
+                None => full_match.to_string(),
+            }
+        })
+        .to_string()
+}
+
+fn static_assets_prefix(assets_dir: &Path, rel_path: &Path) -> String {
+    let assets_dir = normalize_path(assets_dir);
+    format!(
+        "/{}/{}/",
+        assets_dir.trim_matches('/'),
+        normalize_path(rel_path)
+    )
+}
+
+/// Site-absolute URL that an image referenced as `image` by the content at
+/// `content_path` gets once copied to the build dir. External and rooted
+/// URLs are returned unchanged.
+pub fn static_asset_url(
+    content_dir: &Path,
+    assets_dir: &Path,
+    content_path: &Path,
+    image: &str,
+) -> Result<String, Box<dyn Error>> {
+    if is_external_or_rooted_path(image) {
+        return Ok(image.to_string());
+    }
+
+    let content_dir = absolute_path(content_dir.to_path_buf())?;
+    let root = content_root(absolute_path(content_path.to_path_buf())?)?;
+    let rel_path = root.strip_prefix(&content_dir)?;
+    Ok(format!(
+        "{}{}",
+        static_assets_prefix(assets_dir, rel_path),
+        image
+    ))
+}
+
 fn content_root(path: PathBuf) -> Result<PathBuf, Box<dyn Error>> {
     if path.is_dir() {
         Ok(path)
@@ -222,13 +648,30 @@ mod tests {
     use std::path::Path;
     use tempfile::tempdir;
 
+    fn test_processor(
+        path: PathBuf,
+        content_dir: PathBuf,
+        build_dir: PathBuf,
+    ) -> Result<ImageProcessor, Box<dyn Error>> {
+        let config = Config::default();
+        ImageProcessor::new(
+            path,
+            content_dir,
+            build_dir,
+            &config.image_extensions,
+            config.assets_dir,
+        )
+    }
+
     #[test]
     fn test_find_images_in_test_assets() {
         // Define the root directory for the test assets.
         let root = Path::new("src/test_assets/problems/p1");
 
         // Call the function to find images.
-        let mut images = find_images(root).expect("Failed to find images");
+        let [mut images] =
+            find_files_with_extensions(root, [&Config::default().image_extensions], false)
+                .expect("Failed to find images");
         images.sort();
 
         assert_eq!(
@@ -241,6 +684,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn one_walk_sorts_files_into_extension_sets() {
+        let root = Path::new("src/test_assets/problems/p1");
+        let images = ["PNG".to_string()];
+        let sources = ["tex".to_string(), "png".to_string()];
+
+        let [mut images, mut sources] =
+            find_files_with_extensions(root, [&images, &sources], false).unwrap();
+        images.sort();
+        sources.sort();
+
+        assert_eq!(images.len(), 3);
+        assert_eq!(
+            sources,
+            ["hint.tex", "problem.tex", "solution.tex"].map(PathBuf::from)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_image_directory_is_found_only_with_follow_symlinks() -> Result<(), Box<dyn Error>>
+    {
+        let temp_dir = tempdir()?;
+        let shared_dir = temp_dir.path().join("shared/figs");
+        let problem_dir = temp_dir.path().join("content/p1");
+        fs::create_dir_all(&shared_dir)?;
+        fs::create_dir_all(&problem_dir)?;
+        fs::write(shared_dir.join("plot.png"), "png")?;
+        fs::write(problem_dir.join("metadata.yaml"), "title: P1\n")?;
+        std::os::unix::fs::symlink(&shared_dir, problem_dir.join("figs"))?;
+        // A loop back to the problem directory must not hang the walk.
+        std::os::unix::fs::symlink(&problem_dir, problem_dir.join("figs-loop"))?;
+        let mut config = Config {
+            content_dir: temp_dir.path().join("content"),
+            build_dir: temp_dir.path().join("build"),
+            ..Default::default()
+        };
+
+        let processor = ImageProcessor::for_content(problem_dir.clone(), &config)?;
+        assert!(!processor.has_images());
+
+        config.follow_symlinks = true;
+        let processor = ImageProcessor::for_content(problem_dir, &config)?;
+        assert_eq!(processor.images, [PathBuf::from("figs/plot.png")]);
+
+        Ok(())
+    }
+
     #[test]
     fn test_prefix_image_urls_basic() {
         let html = r#"<img src="figs/image.png" alt="An image">"#;
@@ -314,7 +805,7 @@ mod tests {
 
         // Create an image processor
         let mut processor =
-            ImageProcessor::new(path.clone(), content_dir.clone(), build_dir.clone()).unwrap();
+            test_processor(path.clone(), content_dir.clone(), build_dir.clone()).unwrap();
 
         // Check if images were found
         assert!(processor.has_images());
@@ -342,7 +833,7 @@ mod tests {
         let content_dir = cwd.join("src");
         let path = cwd.join("src/test_assets/problems/p1/problem.tex");
 
-        let mut processor = ImageProcessor::new(path, content_dir, build_dir.clone()).unwrap();
+        let mut processor = test_processor(path, content_dir, build_dir.clone()).unwrap();
 
         processor.copy_images_to_build_dir().unwrap();
 
@@ -353,4 +844,305 @@ mod tests {
         let updated = processor.update_html_with_image_urls(html);
         assert!(updated.contains("/static/assets/test_assets/problems/p1/figs/blue.png"));
     }
+
+    #[test]
+    fn static_asset_url_follows_copy_layout() {
+        let temp_dir = tempdir().unwrap();
+        let content_dir = temp_dir.path().join("content");
+        let post_dir = content_dir.join("blog/post");
+        fs::create_dir_all(&post_dir).unwrap();
+
+        assert_eq!(
+            static_asset_url(
+                &content_dir,
+                Path::new("static/assets"),
+                &post_dir,
+                "cover.png"
+            )
+            .unwrap(),
+            "/static/assets/blog/post/cover.png"
+        );
+        assert_eq!(
+            static_asset_url(
+                &content_dir,
+                Path::new("static/assets"),
+                &post_dir,
+                "https://cdn.example.com/a.png"
+            )
+            .unwrap(),
+            "https://cdn.example.com/a.png"
+        );
+    }
+
+    #[test]
+    fn convert_webp_writes_webp_copies_of_raster_images() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let problem_dir = content_dir.join("p1");
+        let build_dir = temp_dir.path().join("build");
+        fs::create_dir_all(problem_dir.join("figs"))?;
+        fs::copy(
+            "src/test_assets/problems/p1/figs/red.png",
+            problem_dir.join("figs/red.png"),
+        )?;
+        image::open(problem_dir.join("figs/red.png"))?
+            .to_rgb8()
+            .save(problem_dir.join("figs/red.jpg"))?;
+        fs::write(problem_dir.join("figs/shape.svg"), "<svg></svg>")?;
+        let config = Config {
+            content_dir,
+            build_dir: build_dir.clone(),
+            convert_webp: true,
+            ..Default::default()
+        };
+
+        let mut processor = ImageProcessor::for_content(problem_dir, &config)?;
+        processor.copy_images_to_build_dir()?;
+        let html = processor.update_html_with_image_urls(
+            r#"<img src="figs/red.png"><img src="figs/red.jpg"><img src="figs/shape.svg">"#,
+        );
+
+        let assets_dir = build_dir.join("static/assets/p1/figs");
+        let webp = image::open(assets_dir.join("red.png.webp"))?;
+        let png = image::open(assets_dir.join("red.png"))?;
+        assert_eq!(webp.to_rgba8(), png.to_rgba8());
+        assert!(!assets_dir.join("red.jpg.webp").exists());
+        assert!(!assets_dir.join("shape.svg.webp").exists());
+        assert_eq!(
+            fs::read_to_string(assets_dir.join("shape.svg"))?,
+            "<svg></svg>"
+        );
+        assert_eq!(
+            html,
+            concat!(
+                r#"<img loading="lazy" decoding="async" width="50" height="50" src="/static/assets/p1/figs/red.png.webp">"#,
+                r#"<img loading="lazy" decoding="async" width="50" height="50" src="/static/assets/p1/figs/red.jpg">"#,
+                r#"<img loading="lazy" decoding="async" src="/static/assets/p1/figs/shape.svg">"#,
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn draft_images_are_copied_like_content_images() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let drafts_dir = temp_dir.path().join("drafts");
+        let draft_dir = drafts_dir.join("blog/post");
+        let build_dir = temp_dir.path().join("build");
+        fs::create_dir_all(&draft_dir)?;
+        fs::write(draft_dir.join("shape.svg"), "<svg></svg>")?;
+        let config = Config {
+            content_dir: temp_dir.path().join("content"),
+            drafts_dir: Some(drafts_dir),
+            build_dir: build_dir.clone(),
+            ..Default::default()
+        };
+
+        let mut processor = ImageProcessor::for_content(draft_dir, &config)?;
+        processor.copy_images_to_build_dir()?;
+        let html = processor.update_html_with_image_urls(r#"<img src="shape.svg">"#);
+
+        assert!(build_dir.join("static/assets/blog/post/shape.svg").exists());
+        assert!(html.contains(r#"src="/static/assets/blog/post/shape.svg""#));
+
+        Ok(())
+    }
+
+    #[test]
+    fn rewritten_images_get_intrinsic_dimensions() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let problem_dir = content_dir.join("p1");
+        fs::create_dir_all(problem_dir.join("figs"))?;
+        for name in ["red", "green"] {
+            fs::copy(
+                format!("src/test_assets/problems/p1/figs/{name}.png"),
+                problem_dir.join(format!("figs/{name}.png")),
+            )?;
+        }
+
+        let mut processor =
+            test_processor(problem_dir, content_dir, temp_dir.path().join("build"))?;
+        processor.copy_images_to_build_dir()?;
+        let html = processor.update_html_with_image_urls(
+            r#"<img src="figs/red.png" alt="Red"><img src="figs/green.png" width="10">"#,
+        );
+
+        assert_eq!(
+            html,
+            r#"<img width="50" height="50" src="/static/assets/p1/figs/red.png" alt="Red"><img src="/static/assets/p1/figs/green.png" width="10">"#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn lazy_images_get_loading_attributes_once() {
+        let html = r#"<img src="/static/p1/a.png"><img loading="eager" src="/static/p1/b.png"><img src="https://cdn/c.png">"#;
+
+        let once = add_lazy_loading(html, "/static/p1/");
+        let twice = add_lazy_loading(&once, "/static/p1/");
+
+        assert_eq!(
+            once,
+            r#"<img loading="lazy" decoding="async" src="/static/p1/a.png"><img decoding="async" loading="eager" src="/static/p1/b.png"><img src="https://cdn/c.png">"#
+        );
+        assert_eq!(twice, once);
+        assert_eq!(once.matches(r#"loading="lazy""#).count(), 1);
+    }
+
+    #[test]
+    fn test_prefix_image_urls_srcset() {
+        let html = r#"<img src="a.png" srcset="a.png 1x, figs/a@2x.png 2x">"#;
+        let image_paths = vec![PathBuf::from("a.png"), PathBuf::from("figs/a@2x.png")];
+
+        let result = prefix_image_urls(html, &image_paths, "/static/p1/");
+
+        assert_eq!(
+            result,
+            r#"<img src="/static/p1/a.png" srcset="/static/p1/a.png 1x, /static/p1/figs/a@2x.png 2x">"#
+        );
+    }
+
+    #[test]
+    fn test_prefix_image_urls_source_and_poster() {
+        let html = r#"<picture><source srcset="wide.png 800w, https://cdn/x.png 400w" media="(min-width: 600px)"><img src="wide.png"></picture><video poster="wide.png"><source src="clip.png"></video>"#;
+        let image_paths = vec![PathBuf::from("wide.png"), PathBuf::from("clip.png")];
+
+        let result = prefix_image_urls(html, &image_paths, "/s/");
+
+        assert_eq!(
+            result,
+            r#"<picture><source srcset="/s/wide.png 800w, https://cdn/x.png 400w" media="(min-width: 600px)"><img src="/s/wide.png"></picture><video poster="/s/wide.png"><source src="/s/clip.png"></video>"#
+        );
+    }
+
+    #[test]
+    fn linked_assets_are_copied_and_rewritten() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let problem_dir = content_dir.join("p1");
+        let build_dir = temp_dir.path().join("build");
+        fs::create_dir_all(&problem_dir)?;
+        fs::write(problem_dir.join("starter.pdf"), "%PDF-1.4")?;
+        fs::write(problem_dir.join("notes.txt"), "not copied")?;
+        let config = Config {
+            content_dir,
+            build_dir: build_dir.clone(),
+            ..Default::default()
+        };
+
+        let mut processor = ImageProcessor::for_content(problem_dir, &config)?;
+        assert!(processor.has_assets());
+        processor.copy_images_to_build_dir()?;
+        let html = processor.update_html_with_image_urls(
+            r#"<a href="starter.pdf">Starter</a> <a href="notes.txt">Notes</a>"#,
+        );
+
+        assert_eq!(
+            fs::read_to_string(build_dir.join("static/assets/p1/starter.pdf"))?,
+            "%PDF-1.4"
+        );
+        assert!(!build_dir.join("static/assets/p1/notes.txt").exists());
+        assert_eq!(
+            html,
+            r#"<a href="/static/assets/p1/starter.pdf">Starter</a> <a href="notes.txt">Notes</a>"#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn unchanged_images_are_not_copied_again() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let source = temp_dir.path().join("red.png");
+        let target = temp_dir.path().join("build/red.png");
+        fs::create_dir_all(temp_dir.path().join("build"))?;
+        fs::copy("src/test_assets/problems/p1/figs/red.png", &source)?;
+
+        assert!(copy_if_changed(&source, &target)?);
+        let copied_at = fs::metadata(&target)?.modified()?;
+        std::thread::sleep(std::time::Duration::from_millis(20));
+
+        assert!(!copy_if_changed(&source, &target)?);
+        assert_eq!(fs::metadata(&target)?.modified()?, copied_at);
+
+        fs::write(&source, "changed")?;
+        assert!(copy_if_changed(&source, &target)?);
+        assert_eq!(fs::read_to_string(&target)?, "changed");
+
+        Ok(())
+    }
+
+    #[test]
+    fn dangling_image_references_are_reported() {
+        let processor = test_processor(
+            PathBuf::from("src/test_assets/problems/p1"),
+            PathBuf::from("src/test_assets"),
+            PathBuf::from("build"),
+        )
+        .unwrap();
+        let html = concat!(
+            r#"<img src="figs/red.png"><img src="./figs/red.png?v=2">"#,
+            r#"<img src="figs/missing.png"><img src="/static/logo.png">"#,
+            r#"<img src="https://example.com/a.png"><img src="data:image/png;base64,AA==">"#,
+        );
+
+        assert_eq!(
+            processor.broken_image_references(html),
+            ["figs/missing.png"]
+        );
+    }
+
+    #[test]
+    fn image_references_outside_markup_are_not_reported() {
+        let processor = test_processor(
+            PathBuf::from("src/test_assets/problems/p1"),
+            PathBuf::from("src/test_assets"),
+            PathBuf::from("build"),
+        )
+        .unwrap();
+        let html = concat!(
+            r#"<!-- <img src="figs/old.png"> -->"#,
+            r#"<script>const tag = '<img src="figs/lazy.png">';</script>"#,
+            r#"<TEMPLATE><img src="figs/slot.png"></TEMPLATE>"#,
+            "<pre><code>&lt;img src=\"figs/example.png\"&gt;</code></pre>",
+            r#"<img src="figs/missing.png">"#,
+        );
+
+        assert_eq!(
+            processor.broken_image_references(html),
+            ["figs/missing.png"]
+        );
+    }
+
+    #[test]
+    fn configured_image_extensions_and_assets_dir_are_used() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let problem_dir = content_dir.join("p1");
+        let build_dir = temp_dir.path().join("build");
+        fs::create_dir_all(&problem_dir)?;
+        fs::write(problem_dir.join("photo.webp"), "webp")?;
+        fs::write(problem_dir.join("photo.png"), "png")?;
+        let config = Config {
+            content_dir,
+            build_dir: build_dir.clone(),
+            image_extensions: vec!["webp".to_string()],
+            assets_dir: PathBuf::from("media"),
+            ..Default::default()
+        };
+
+        let mut processor = ImageProcessor::for_content(problem_dir, &config)?;
+        assert_eq!(processor.image_count(), 1);
+        processor.copy_images_to_build_dir()?;
+        let html = processor.update_html_with_image_urls(r#"<img src="photo.webp">"#);
+
+        assert!(build_dir.join("media/p1/photo.webp").exists());
+        assert!(!build_dir.join("media/p1/photo.png").exists());
+        assert!(html.contains(r#"src="/media/p1/photo.webp""#), "{html}");
+
+        Ok(())
+    }
 }
@@ -1,5 +1,16 @@
+mod comments;
 mod content;
+mod filters;
+mod functions;
 mod images;
+mod reading_time;
 mod renderer;
-pub use images::ImageProcessor;
-pub use renderer::Renderer;
+mod structured_data;
+mod toc;
+pub use comments::strip_html_comments;
+pub use functions::build_time;
+pub use images::{static_asset_url, ImageProcessor};
+pub use reading_time::reading_time;
+pub use renderer::{Renderer, Translations};
+pub use structured_data::problem_json_ld;
+pub use toc::{build_toc, slugify, TocEntry, DEFAULT_TOC_DEPTH};
@@ -0,0 +1,54 @@
+use regex::Regex;
+use std::sync::OnceLock;
+
+use crate::config::Config;
+use crate::formatted_text::FormattedText;
+
+/// Words of code are skimmed, so each counts as this fraction of a word.
+const CODE_WORD_WEIGHT: f64 = 0.5;
+
+fn code_block_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?is)<pre\b.*?</pre\s*>").expect("valid code block regex"))
+}
+
+/// Estimated minutes to read the rendered `html` at `words_per_minute`,
+/// rounded up. Words in code blocks count at a reduced rate.
+pub fn reading_time(html: &str, config: &Config) -> usize {
+    let code_words: usize = code_block_regex()
+        .find_iter(html)
+        .map(|block| word_count(block.as_str(), config))
+        .sum();
+    let prose = code_block_regex().replace_all(html, "");
+    let words = word_count(&prose, config) as f64 + code_words as f64 * CODE_WORD_WEIGHT;
+
+    (words / config.words_per_minute.max(1) as f64).ceil() as usize
+}
+
+fn word_count(html: &str, config: &Config) -> usize {
+    FormattedText::Html(html.to_string())
+        .to_plain_text(config)
+        .map_or(0, |text| text.split_whitespace().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rounds_up_and_discounts_code() {
+        let config = Config {
+            words_per_minute: 200,
+            ..Default::default()
+        };
+        let prose = |words: usize| format!("<p>{}</p>", vec!["word"; words].join(" "));
+
+        assert_eq!(reading_time("", &config), 0);
+        assert_eq!(reading_time(&prose(200), &config), 1);
+        assert_eq!(reading_time(&prose(201), &config), 2);
+        assert_eq!(reading_time(&prose(1000), &config), 5);
+
+        let code = format!("<pre><code>{}</code></pre>", vec!["x"; 400].join(" "));
+        assert_eq!(reading_time(&format!("{}{code}", prose(800)), &config), 5);
+    }
+}
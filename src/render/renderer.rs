@@ -1,29 +1,105 @@
 use std::collections::HashMap;
 use std::error::Error;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
 use tera::{Context, Function, Tera, Value};
 
+use super::strip_html_comments;
 use crate::config::Config;
+use crate::content::{find_content_files, ContentMetadata};
+
+/// Translations of the site language, backed by those of the fallback language.
+/// The translations of `config.language`, backed by those of
+/// `fallback_language`, as used by the `translate` template function.
+#[derive(Debug, Default)]
+pub struct Translations {
+    primary: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+impl Translations {
+    pub fn load(config: &Config) -> Result<Self, Box<dyn Error>> {
+        load_configured_translations(config)
+    }
+
+    fn get(&self, key: &str) -> Option<&String> {
+        self.primary.get(key).or_else(|| self.fallback.get(key))
+    }
+
+    /// The translation of `key`, or `key` itself when there is none, with
+    /// `{name}` placeholders filled from `args`.
+    pub fn translate(&self, key: &str, args: &HashMap<String, Value>) -> String {
+        let translation = match self.get(key) {
+            Some(translation) => translation.to_string(),
+            None => key.to_string(),
+        };
+        fill_placeholders(translation, args)
+    }
+}
 
 pub struct Renderer {
     tera: Tera,
     default_context: Context,
+    strip_comments: bool,
+    translations: Arc<Translations>,
+    /// Every published item of the content dir, scanned on first use.
+    content_items: OnceLock<Result<Vec<ContentMetadata>, String>>,
 }
 
 impl Renderer {
     pub fn new(config: &Config) -> Result<Self, Box<dyn Error>> {
         let mut tera = load_templates(config)?;
-        let translations = load_configured_translations(config)?;
-        tera.register_function("translate", translate_to_tera(translations));
+        let translations = Arc::new(Translations::load(config)?);
+        tera.register_function("translate", translate_to_tera(Arc::clone(&translations)));
+        tera.register_function("og_tags", super::functions::OgTags::new(config));
+        tera.register_function("asset", super::functions::Asset::new(config));
+        tera.register_function("url_for", super::functions::UrlFor::new(config));
+        tera.register_function("now", super::functions::now);
+        tera.register_filter("date", super::filters::date);
+        tera.register_filter("slugify", super::filters::slugify);
+        tera.register_filter("markdown", super::filters::Markdown::new(config));
 
         Ok(Self {
             tera,
-            default_context: build_default_context(config),
+            default_context: build_default_context(config)?,
+            strip_comments: config.strip_comments,
+            translations,
+            content_items: OnceLock::new(),
         })
     }
 
+    /// The published items of `config.content_dir`, loaded once per renderer
+    /// and shared by the pages it renders.
+    pub fn content_items(&self, config: &Config) -> Result<&[ContentMetadata], Box<dyn Error>> {
+        self.content_items
+            .get_or_init(|| {
+                find_content_files(&config.content_dir, None, config).map_err(|e| e.to_string())
+            })
+            .as_deref()
+            .map_err(|e| e.clone().into())
+    }
+
+    /// The translations behind the `translate` template function.
+    pub fn translations(&self) -> &Translations {
+        &self.translations
+    }
+
+    /// Whether a template named `name` was loaded.
+    pub fn has_template(&self, name: &str) -> bool {
+        self.tera
+            .get_template_names()
+            .any(|template| template == name)
+    }
+
+    /// Re-read the templates, translations and data dir, e.g. after they
+    /// changed on disk. On error the renderer keeps its previous state.
+    pub fn reload(&mut self, config: &Config) -> Result<(), Box<dyn Error>> {
+        *self = Self::new(config)?;
+        Ok(())
+    }
+
     pub fn render(
         &self,
         template_name: &str,
@@ -33,6 +109,7 @@ impl Renderer {
         merge_render_context(&mut context, custom_context);
 
         match self.tera.render(template_name, &context) {
+            Ok(s) if self.strip_comments => Ok(strip_html_comments(&s)),
             Ok(s) => Ok(s),
             Err(e) => Err(Box::new(std::io::Error::other(format!(
                 "Error rendering template: {:#?}",
@@ -48,21 +125,58 @@ fn load_templates(config: &Config) -> Result<Tera, Box<dyn Error>> {
         .map_err(|e| std::io::Error::other(format!("Error parsing templates: {}", e)).into())
 }
 
-fn load_configured_translations(
-    config: &Config,
-) -> Result<HashMap<String, String>, Box<dyn Error>> {
-    match &config.translations_csv {
-        Some(translations_file) => load_translations(translations_file).map_err(|e| {
-            std::io::Error::other(format!("Error loading translations: {}", e)).into()
-        }),
-        None => Ok(HashMap::new()),
+fn load_configured_translations(config: &Config) -> Result<Translations, Box<dyn Error>> {
+    let primary = match language_file(config, &config.language) {
+        Some(translations_file) => load_translations_file(&translations_file)?,
+        None => HashMap::new(),
+    };
+    let fallback = match fallback_translations_file(config) {
+        Some(translations_file) => load_translations_file(&translations_file)?,
+        None => HashMap::new(),
+    };
+
+    Ok(Translations { primary, fallback })
+}
+
+fn load_translations_file(path: &Path) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    load_translations(path).map_err(|e| {
+        std::io::Error::other(format!(
+            "Error loading translations from {}: {}",
+            path.display(),
+            e
+        ))
+        .into()
+    })
+}
+
+/// `<translations_dir>/<fallback_language>.csv`, when configured and present.
+fn fallback_translations_file(config: &Config) -> Option<PathBuf> {
+    let fallback_language = config.fallback_language.as_ref()?;
+    if *fallback_language == config.language {
+        return None;
     }
+    language_file(config, fallback_language)
 }
 
-fn build_default_context(config: &Config) -> Context {
+/// `<translations_dir>/<language>.csv`, when configured and present.
+fn language_file(config: &Config, language: &str) -> Option<PathBuf> {
+    let translations_dir = config.translations_dir.as_ref()?;
+    let language_file = translations_dir.join(format!("{}.csv", language));
+    language_file.is_file().then_some(language_file)
+}
+
+fn build_default_context(config: &Config) -> Result<Context, Box<dyn Error>> {
     let mut context = Context::new();
     context.insert("text_direction", &config.text_direction);
     context.insert("language", &config.language);
+    context.insert("base_url", &config.base_url);
+    context.insert("config", &template_config(config));
+
+    if let Some(data_dir) = &config.data_dir {
+        for (name, value) in load_data_dir(data_dir)? {
+            context.insert(name, &value);
+        }
+    }
 
     if let Some(extra_context) = &config.context {
         for (key, value) in extra_context {
@@ -70,7 +184,51 @@ fn build_default_context(config: &Config) -> Context {
         }
     }
 
-    context
+    Ok(context)
+}
+
+/// The settings templates may read as `config`. Filesystem paths and
+/// process settings are left out.
+fn template_config(config: &Config) -> Value {
+    serde_json::json!({
+        "base_url": config.base_url,
+        "trailing_slash": config.trailing_slash,
+        "language": config.language,
+        "fallback_language": config.fallback_language,
+        "text_direction": config.text_direction,
+        "output_extension": config.output_extension,
+        "clean_urls": config.clean_urls,
+        "math_renderer": config.math_renderer,
+        "syntax_highlighter_theme": config.syntax_highlighter_theme,
+        "fallback_image": config.fallback_image,
+        "convert_webp": config.convert_webp,
+        "lazy_images": config.lazy_images,
+    })
+}
+
+/// Every `*.yaml`/`*.yml`/`*.json` file directly in `data_dir`, keyed by its
+/// file stem.
+fn load_data_dir(data_dir: &Path) -> Result<Vec<(String, Value)>, Box<dyn Error>> {
+    let entries = fs::read_dir(data_dir)
+        .map_err(|e| format!("Error reading data dir {}: {}", data_dir.display(), e))?;
+    let mut data = Vec::new();
+
+    for entry in entries {
+        let path = entry?.path();
+        let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let value: Value = match path.extension().and_then(|s| s.to_str()) {
+            Some("yaml" | "yml") => serde_yaml::from_str(&fs::read_to_string(&path)?)
+                .map_err(|e| format!("Error parsing {}: {}", path.display(), e))?,
+            Some("json") => serde_json::from_str(&fs::read_to_string(&path)?)
+                .map_err(|e| format!("Error parsing {}: {}", path.display(), e))?,
+            _ => continue,
+        };
+        data.push((stem.to_string(), value));
+    }
+
+    Ok(data)
 }
 
 fn merge_render_context(context: &mut Context, custom_context: HashMap<String, Value>) {
@@ -79,15 +237,52 @@ fn merge_render_context(context: &mut Context, custom_context: HashMap<String, V
     }
 }
 
-fn strip_csv_quotes(s: &str) -> String {
-    let mut s = s.trim();
-    if s.starts_with('"') {
-        s = &s[1..];
+/// Split one CSV line into a key and a value at the first comma outside
+/// quotes; the value is everything after it, commas included. Either field
+/// may be double-quoted, with `""` standing for a literal quote. Quoted
+/// text is kept exactly as written, unquoted text is trimmed.
+fn parse_csv_line(line: &str) -> Option<(String, String)> {
+    let (key, rest) = read_csv_field(line);
+    let value = rest?;
+    let value = match value.trim_start().strip_prefix('"') {
+        Some(quoted) => read_quoted_csv_field(quoted).0,
+        None => value.trim().to_string(),
+    };
+    Some((key, value))
+}
+
+/// Read the field at the start of `input`, returning it and the text after
+/// the comma that ends it, if any.
+fn read_csv_field(input: &str) -> (String, Option<&str>) {
+    if let Some(quoted) = input.trim_start().strip_prefix('"') {
+        let (field, after) = read_quoted_csv_field(quoted);
+        let rest = after.find(',').map(|pos| &after[pos + 1..]);
+        return (field, rest);
     }
-    if s.ends_with('"') {
-        s = &s[..s.len() - 1];
+    match input.find(',') {
+        Some(pos) => (input[..pos].trim().to_string(), Some(&input[pos + 1..])),
+        None => (input.trim().to_string(), None),
+    }
+}
+
+/// Read a quoted field from `input`, which starts just after its opening
+/// quote, returning its text and what follows the closing quote.
+fn read_quoted_csv_field(input: &str) -> (String, &str) {
+    let mut field = String::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if c != '"' {
+            field.push(c);
+        } else if chars.peek().is_some_and(|&(_, next)| next == '"') {
+            chars.next();
+            field.push('"');
+        } else {
+            return (field, &input[i + 1..]);
+        }
     }
-    s.to_string()
+
+    (field, "")
 }
 
 fn load_translations(path: &Path) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
@@ -101,9 +296,7 @@ fn load_translations(path: &Path) -> Result<HashMap<String, String>, Box<dyn std
             continue; // Skip empty lines and comments
         }
 
-        if let Some(pos) = line.find(',') {
-            let key = strip_csv_quotes(&line[..pos]);
-            let value = strip_csv_quotes(&line[(pos + 1)..]);
+        if let Some((key, value)) = parse_csv_line(&line) {
             translations.insert(key, value);
         }
     }
@@ -111,7 +304,7 @@ fn load_translations(path: &Path) -> Result<HashMap<String, String>, Box<dyn std
     Ok(translations)
 }
 
-fn translate_to_tera(translations: HashMap<String, String>) -> impl Function {
+fn translate_to_tera(translations: Arc<Translations>) -> impl Function {
     Box::new(
         move |args: &HashMap<String, Value>| -> tera::Result<Value> {
             let key = args
@@ -119,16 +312,27 @@ fn translate_to_tera(translations: HashMap<String, String>) -> impl Function {
                 .and_then(|v| v.as_str())
                 .ok_or_else(|| tera::Error::msg("Missing or invalid key for translation"))?;
 
-            let translation = match translations.get(key) {
-                Some(translation) => translation.to_string(),
-                None => key.to_string(),
-            };
-
-            Ok(Value::String(translation))
+            Ok(Value::String(translations.translate(key, args)))
         },
     )
 }
 
+/// Replace `{name}` in `translation` with each named argument other than
+/// `key`; placeholders without a matching argument are left as they are.
+fn fill_placeholders(mut translation: String, args: &HashMap<String, Value>) -> String {
+    for (name, value) in args {
+        if name == "key" {
+            continue;
+        }
+        let value = match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        translation = translation.replace(&format!("{{{}}}", name), &value);
+    }
+    translation
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -155,19 +359,22 @@ mod tests {
     }
 
     #[test]
-    fn new_returns_error_for_missing_translations() {
+    fn new_returns_error_for_unreadable_translations() {
         let temp_dir = tempdir().unwrap();
         let template_dir = temp_dir.path().join("templates");
+        let translations_dir = temp_dir.path().join("translations");
         fs::create_dir_all(&template_dir).unwrap();
+        fs::create_dir_all(&translations_dir).unwrap();
         fs::write(template_dir.join("page.html"), "{{ title }}").unwrap();
+        fs::write(translations_dir.join("en.csv"), b"Home,\xff\xfe\n").unwrap();
         let config = Config {
             template_dir,
-            translations_csv: Some(temp_dir.path().join("missing.csv")),
+            translations_dir: Some(translations_dir),
             ..Default::default()
         };
 
         let err = match Renderer::new(&config) {
-            Ok(_) => panic!("missing translations should return an error"),
+            Ok(_) => panic!("unreadable translations should return an error"),
             Err(err) => err,
         };
 
@@ -193,4 +400,472 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn render_strips_comments_when_configured() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(
+            template_dir.join("page.html"),
+            "<p>{{ title }}</p><!-- TODO: add footer -->",
+        )?;
+        let config = Config {
+            template_dir,
+            strip_comments: true,
+            ..Default::default()
+        };
+
+        let renderer = Renderer::new(&config)?;
+        let mut context = HashMap::new();
+        context.insert("title".to_string(), Value::String("Hello".to_string()));
+
+        assert_eq!(renderer.render("page.html", context)?, "<p>Hello</p>");
+
+        Ok(())
+    }
+
+    #[test]
+    fn render_formats_dates_with_filter() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(
+            template_dir.join("blog.html"),
+            r#"{{ blog.timestamp | date(format="%B %d, %Y") }} / {{ blog.updated | date }}"#,
+        )?;
+        let config = Config {
+            template_dir,
+            ..Default::default()
+        };
+
+        let renderer = Renderer::new(&config)?;
+        let mut context = HashMap::new();
+        context.insert(
+            "blog".to_string(),
+            serde_json::json!({"timestamp": "2025-03-06T12:00:00Z", "updated": "2025-04-01"}),
+        );
+
+        assert_eq!(
+            renderer.render("blog.html", context)?,
+            "March 06, 2025 / 2025-04-01"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn render_slugifies_with_filter() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(
+            template_dir.join("page.html"),
+            r#"{{ "Hello, World!" | slugify }}"#,
+        )?;
+        let config = Config {
+            template_dir,
+            ..Default::default()
+        };
+
+        let renderer = Renderer::new(&config)?;
+
+        assert_eq!(renderer.render("page.html", HashMap::new())?, "hello-world");
+
+        Ok(())
+    }
+
+    #[test]
+    fn render_converts_markdown_with_filter() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(template_dir.join("page.html"), r#"{{ "*hi*" | markdown }}"#)?;
+        let config = Config {
+            template_dir,
+            ..Default::default()
+        };
+
+        let renderer = Renderer::new(&config)?;
+
+        assert_eq!(
+            renderer.render("page.html", HashMap::new())?,
+            "<p><em>hi</em></p>\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn translate_uses_language_file_from_translations_dir() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        let translations_dir = temp_dir.path().join("translations");
+        fs::create_dir_all(&template_dir)?;
+        fs::create_dir_all(&translations_dir)?;
+        fs::write(
+            template_dir.join("page.html"),
+            r#"{{ translate(key="Home") }} {{ translate(key="Missing") }}"#,
+        )?;
+        fs::write(translations_dir.join("fa.csv"), "Home,خانه\n")?;
+        let config = Config {
+            template_dir,
+            translations_dir: Some(translations_dir),
+            language: "fa".to_string(),
+            ..Default::default()
+        };
+
+        let renderer = Renderer::new(&config)?;
+
+        assert_eq!(
+            renderer.render("page.html", HashMap::new())?,
+            "خانه Missing"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn missing_language_file_leaves_keys_untranslated() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(
+            template_dir.join("page.html"),
+            r#"{{ translate(key="Home") }}"#,
+        )?;
+        let config = Config {
+            template_dir,
+            translations_dir: Some(temp_dir.path().join("translations")),
+            ..Default::default()
+        };
+
+        let renderer = Renderer::new(&config)?;
+
+        assert_eq!(renderer.render("page.html", HashMap::new())?, "Home");
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_translations_honors_quoted_fields() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let path = temp_dir.path().join("fa.csv");
+        fs::write(
+            &path,
+            "# comment\n\nGreeting,\"Hello, world\"\n\"Yes, please\",\"بله، لطفاً\"\n\"Say \"\"hi\"\"\", Salam \n",
+        )?;
+
+        let translations = load_translations(&path)?;
+
+        assert_eq!(translations.len(), 3);
+        assert_eq!(translations["Greeting"], "Hello, world");
+        assert_eq!(translations["Yes, please"], "بله، لطفاً");
+        assert_eq!(translations["Say \"hi\""], "Salam");
+
+        Ok(())
+    }
+
+    #[test]
+    fn load_translations_keeps_commas_in_values() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let path = temp_dir.path().join("en.csv");
+        fs::write(&path, "Welcome,Hello, friend\nPadded,\"  spaced, out  \"\n")?;
+
+        let translations = load_translations(&path)?;
+
+        assert_eq!(translations["Welcome"], "Hello, friend");
+        assert_eq!(translations["Padded"], "  spaced, out  ");
+
+        Ok(())
+    }
+
+    #[test]
+    fn translate_fills_placeholders_from_arguments() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        let translations_dir = temp_dir.path().join("translations");
+        fs::create_dir_all(&template_dir)?;
+        fs::create_dir_all(&translations_dir)?;
+        fs::write(
+            template_dir.join("page.html"),
+            r#"{{ translate(key="greeting", name="Sam") }} / {{ translate(key="messages", count=3) }}"#,
+        )?;
+        fs::write(
+            translations_dir.join("en.csv"),
+            "greeting,Hello {name}\nmessages,You have {count} messages from {sender}\n",
+        )?;
+        let config = Config {
+            template_dir,
+            translations_dir: Some(translations_dir),
+            ..Default::default()
+        };
+
+        let renderer = Renderer::new(&config)?;
+
+        assert_eq!(
+            renderer.render("page.html", HashMap::new())?,
+            "Hello Sam / You have 3 messages from {sender}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn translate_falls_back_to_fallback_language() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        let translations_dir = temp_dir.path().join("translations");
+        fs::create_dir_all(&template_dir)?;
+        fs::create_dir_all(&translations_dir)?;
+        fs::write(
+            template_dir.join("page.html"),
+            r#"{{ translate(key="home") }} {{ translate(key="about") }} {{ translate(key="other") }}"#,
+        )?;
+        fs::write(translations_dir.join("fa.csv"), "home,خانه\n")?;
+        fs::write(translations_dir.join("en.csv"), "home,Home\nabout,About\n")?;
+        let config = Config {
+            template_dir,
+            translations_dir: Some(translations_dir),
+            language: "fa".to_string(),
+            fallback_language: Some("en".to_string()),
+            ..Default::default()
+        };
+
+        let renderer = Renderer::new(&config)?;
+
+        assert_eq!(
+            renderer.render("page.html", HashMap::new())?,
+            "خانه About other"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn og_tags_use_absolute_urls() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(
+            template_dir.join("page.html"),
+            r#"{{ og_tags(title=title, description=description, url=url, image=image_url) }}"#,
+        )?;
+        let config = Config {
+            template_dir,
+            base_url: Some("https://example.com/".to_string()),
+            ..Default::default()
+        };
+
+        let renderer = Renderer::new(&config)?;
+        let context = serde_json::from_value(serde_json::json!({
+            "title": "Tom & Jerry",
+            "description": "A post",
+            "url": "/blog/post.html",
+            "image_url": "/static/assets/blog/post/cover.png",
+        }))?;
+
+        assert_eq!(
+            renderer.render("page.html", context)?,
+            [
+                r#"<meta property="og:type" content="website">"#,
+                r#"<meta property="og:title" content="Tom &amp; Jerry">"#,
+                r#"<meta property="og:description" content="A post">"#,
+                r#"<meta property="og:url" content="https://example.com/blog/post.html">"#,
+                r#"<meta property="og:image" content="https://example.com/static/assets/blog/post/cover.png">"#,
+                r#"<meta name="twitter:card" content="summary_large_image">"#,
+                r#"<meta name="twitter:title" content="Tom &amp; Jerry">"#,
+                r#"<meta name="twitter:description" content="A post">"#,
+                r#"<meta name="twitter:image" content="https://example.com/static/assets/blog/post/cover.png">"#,
+            ]
+            .join("\n")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn asset_urls_carry_a_content_hash() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        let build_dir = temp_dir.path().join("build");
+        fs::create_dir_all(&template_dir)?;
+        fs::create_dir_all(build_dir.join("css"))?;
+        fs::write(
+            template_dir.join("page.html"),
+            r#"<link href="{{ asset(path='css/site.css') }}">"#,
+        )?;
+        fs::write(
+            template_dir.join("missing.html"),
+            r#"{{ asset(path='nope.css') }}"#,
+        )?;
+        fs::write(build_dir.join("css/site.css"), "body {}")?;
+        let config = Config {
+            template_dir,
+            build_dir: build_dir.clone(),
+            ..Default::default()
+        };
+        let render = || -> Result<String, Box<dyn Error>> {
+            Renderer::new(&config)?.render("page.html", HashMap::new())
+        };
+
+        let first = render()?;
+        assert!(
+            first.starts_with(r#"<link href="/css/site.css?v="#),
+            "{first}"
+        );
+        assert_eq!(render()?, first);
+
+        fs::write(build_dir.join("css/site.css"), "body { margin: 0 }")?;
+        assert_ne!(render()?, first);
+
+        let err = Renderer::new(&config)?
+            .render("missing.html", HashMap::new())
+            .unwrap_err();
+        assert!(format!("{err:?}").contains("Cannot read asset"), "{err:?}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn url_for_resolves_content_ids() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(
+            template_dir.join("page.html"),
+            r#"{{ url_for(id="sample-problem-001") }}"#,
+        )?;
+        fs::write(
+            template_dir.join("broken.html"),
+            r#"{{ url_for(id="nope") }}"#,
+        )?;
+        let config = Config {
+            template_dir,
+            content_dir: PathBuf::from("src/test_assets"),
+            build_dir: PathBuf::from("build"),
+            ..Default::default()
+        };
+
+        let renderer = Renderer::new(&config)?;
+
+        assert_eq!(
+            renderer.render("page.html", HashMap::new())?,
+            "/problems/p1.html"
+        );
+        let err = renderer.render("broken.html", HashMap::new()).unwrap_err();
+        assert!(
+            format!("{err:?}").contains("no content with id `nope`"),
+            "{err:?}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn data_dir_files_are_in_the_default_context() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        let data_dir = temp_dir.path().join("data");
+        fs::create_dir_all(&template_dir)?;
+        fs::create_dir_all(&data_dir)?;
+        fs::write(
+            template_dir.join("page.html"),
+            "{% for item in nav.items %}{{ item.title }} {% endfor %}{{ authors.sam.bio }}",
+        )?;
+        fs::write(
+            data_dir.join("nav.yaml"),
+            "items:\n  - title: Home\n  - title: Blog\n",
+        )?;
+        fs::write(
+            data_dir.join("authors.json"),
+            r#"{"sam": {"bio": "Writes"}}"#,
+        )?;
+        fs::write(data_dir.join("notes.txt"), "ignored")?;
+        let config = Config {
+            template_dir,
+            data_dir: Some(data_dir),
+            ..Default::default()
+        };
+
+        let renderer = Renderer::new(&config)?;
+
+        assert_eq!(
+            renderer.render("page.html", HashMap::new())?,
+            "Home Blog Writes"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn reload_picks_up_template_changes() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(template_dir.join("page.html"), "page")?;
+        let config = Config {
+            template_dir: template_dir.clone(),
+            ..Default::default()
+        };
+        let mut renderer = Renderer::new(&config)?;
+        assert!(renderer.render("post.html", HashMap::new()).is_err());
+
+        fs::write(template_dir.join("post.html"), "post")?;
+        renderer.reload(&config)?;
+        assert_eq!(renderer.render("post.html", HashMap::new())?, "post");
+
+        fs::write(template_dir.join("post.html"), "{% if %}")?;
+        assert!(renderer.reload(&config).is_err());
+        assert_eq!(renderer.render("post.html", HashMap::new())?, "post");
+
+        Ok(())
+    }
+
+    #[test]
+    fn broken_templates_are_an_error() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(template_dir.join("page.html"), "{% for %}")?;
+        let config = Config {
+            template_dir,
+            ..Default::default()
+        };
+
+        let err = Renderer::new(&config).err().expect("broken template");
+
+        assert!(
+            err.to_string().starts_with("Error parsing templates"),
+            "{err}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn templates_can_read_config_settings() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(
+            template_dir.join("page.html"),
+            "{{ config.language }} {{ config.text_direction }} {{ config.math_renderer }} {{ config.template_dir | default(value='hidden') }}",
+        )?;
+        let config = Config {
+            template_dir,
+            language: "fa".to_string(),
+            text_direction: "rtl".to_string(),
+            ..Default::default()
+        };
+
+        let renderer = Renderer::new(&config)?;
+
+        assert_eq!(
+            renderer.render("page.html", HashMap::new())?,
+            "fa rtl mathjax hidden"
+        );
+
+        Ok(())
+    }
 }
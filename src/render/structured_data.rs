@@ -0,0 +1,102 @@
+use serde_json::{json, Map, Value};
+
+use crate::config::Config;
+use crate::content::{absolute_url, derive_excerpt, ContentMetadata};
+use crate::formatted_text::FormattedText;
+
+/// Characters of the statement kept as the question's `text`.
+const QUESTION_TEXT_LENGTH: usize = 500;
+
+/// schema.org `Question`/`LearningResource` JSON-LD for a problem, wrapped in
+/// a `<script type="application/ld+json">` block ready for the page head.
+pub fn problem_json_ld(
+    metadata: &ContentMetadata,
+    statement_html: &str,
+    config: &Config,
+) -> String {
+    let mut data = Map::new();
+    data.insert("@context".to_string(), json!("https://schema.org"));
+    data.insert("@type".to_string(), json!(["Question", "LearningResource"]));
+    data.insert("name".to_string(), json!(metadata.title));
+    data.insert(
+        "text".to_string(),
+        json!(derive_excerpt(
+            &FormattedText::Html(statement_html.to_string())
+                .to_plain_text(config)
+                .unwrap_or_default(),
+            QUESTION_TEXT_LENGTH
+        )),
+    );
+    data.insert("learningResourceType".to_string(), json!("Problem"));
+
+    if let Some(id) = &metadata.id {
+        data.insert("identifier".to_string(), json!(id));
+    }
+    if let Some(tags) = metadata.tags.as_ref().filter(|tags| !tags.is_empty()) {
+        data.insert("keywords".to_string(), json!(tags.join(", ")));
+    }
+    if let Some(timestamp) = metadata.timestamp {
+        data.insert("datePublished".to_string(), json!(timestamp.to_rfc3339()));
+    }
+    if let Some(updated) = metadata.updated {
+        data.insert("dateModified".to_string(), json!(updated.to_rfc3339()));
+    }
+    let authors: Vec<Value> = metadata
+        .authors
+        .iter()
+        .map(|author| json!({"@type": "Person", "name": author}))
+        .collect();
+    match authors.len() {
+        0 => {}
+        1 => {
+            data.insert("author".to_string(), authors[0].clone());
+        }
+        _ => {
+            data.insert("author".to_string(), Value::Array(authors));
+        }
+    }
+    if let Some(url) = absolute_url(&metadata.url, config) {
+        data.insert("url".to_string(), json!(url));
+    }
+
+    // `</` inside the JSON would end the script element early.
+    let json = Value::Object(data).to_string().replace("</", "<\\/");
+    format!(r#"<script type="application/ld+json">{json}</script>"#)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn problem_json_ld_describes_question() {
+        let metadata = ContentMetadata {
+            title: "Sum of Two".to_string(),
+            id: Some("p1".to_string()),
+            tags: Some(vec!["math".to_string(), "greedy".to_string()]),
+            timestamp: Some(Utc.with_ymd_and_hms(2025, 3, 6, 12, 0, 0).unwrap()),
+            url: "/problems/p1.html".to_string(),
+            ..Default::default()
+        };
+        let config = Config {
+            base_url: Some("https://example.com".to_string()),
+            ..Default::default()
+        };
+
+        let script = problem_json_ld(&metadata, "<p>Add <em>a</em> and b.</p>", &config);
+
+        let json = script
+            .strip_prefix(r#"<script type="application/ld+json">"#)
+            .and_then(|rest| rest.strip_suffix("</script>"))
+            .expect("wrapped in a JSON-LD script tag");
+        let data: Value = serde_json::from_str(json).expect("valid JSON");
+        assert_eq!(data["@context"], "https://schema.org");
+        assert_eq!(data["@type"], json!(["Question", "LearningResource"]));
+        assert_eq!(data["name"], "Sum of Two");
+        assert_eq!(data["text"], "Add a and b.");
+        assert_eq!(data["keywords"], "math, greedy");
+        assert_eq!(data["datePublished"], "2025-03-06T12:00:00+00:00");
+        assert_eq!(data["url"], "https://example.com/problems/p1.html");
+    }
+}
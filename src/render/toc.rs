@@ -0,0 +1,130 @@
+use regex::{Captures, Regex};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+/// Deepest heading level included in a ToC when a page does not set
+/// `toc_depth`.
+pub const DEFAULT_TOC_DEPTH: u8 = 3;
+
+fn heading_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?s)<h([1-6])([^>]*)>(.*?)</h[1-6]>").expect("valid heading regex")
+    })
+}
+
+fn id_attribute_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r#"\bid="([^"]*)""#).expect("valid id attribute regex"))
+}
+
+fn tag_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"<[^>]*>").expect("valid tag regex"))
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TocEntry {
+    pub level: u8,
+    pub id: String,
+    pub title: String,
+}
+
+/// Give every heading in `html` an `id` and collect the headings up to
+/// `max_depth` (e.g. 2 keeps `h1` and `h2`) into a table of contents.
+///
+/// Existing ids, such as the ones pandoc writes, are kept.
+pub fn build_toc(html: &str, max_depth: u8) -> (String, Vec<TocEntry>) {
+    let mut entries = Vec::new();
+    let mut used_ids = HashSet::new();
+
+    let html = heading_regex().replace_all(html, |caps: &Captures| {
+        let level: u8 = caps[1].parse().unwrap_or(6);
+        let attributes = &caps[2];
+        let inner = &caps[3];
+        let title = tag_regex().replace_all(inner, "").trim().to_string();
+
+        let (id, attributes) = match id_attribute_regex().captures(attributes) {
+            Some(id) => (id[1].to_string(), attributes.to_string()),
+            None => {
+                let id = unique_id(&slugify(&title), &mut used_ids);
+                (id.clone(), format!(r#" id="{}"{}"#, id, attributes))
+            }
+        };
+        used_ids.insert(id.clone());
+
+        if level <= max_depth {
+            entries.push(TocEntry { level, id, title });
+        }
+        format!("<h{level}{attributes}>{inner}</h{level}>")
+    });
+
+    (html.into_owned(), entries)
+}
+
+fn unique_id(base: &str, used_ids: &mut HashSet<String>) -> String {
+    let base = if base.is_empty() { "section" } else { base };
+    let mut id = base.to_string();
+    let mut suffix = 1;
+    while used_ids.contains(&id) {
+        id = format!("{}-{}", base, suffix);
+        suffix += 1;
+    }
+    id
+}
+
+/// Lowercase `text` and join its words with `-`, keeping non-ASCII letters
+/// so that Farsi headings still get readable ids.
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if ch.is_alphanumeric() {
+            slug.extend(ch.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HTML: &str =
+        "<h2>Intro</h2><p>a</p><h3>Details</h3><h4>Fine <em>print</em></h4><h2>Intro</h2>";
+
+    #[test]
+    fn depth_two_excludes_deeper_headings() {
+        let (_, toc) = build_toc(HTML, 2);
+
+        let ids: Vec<&str> = toc.iter().map(|entry| entry.id.as_str()).collect();
+        assert_eq!(ids, ["intro", "intro-1"]);
+    }
+
+    #[test]
+    fn depth_three_includes_h3() {
+        let (html, toc) = build_toc(HTML, 3);
+
+        let levels: Vec<u8> = toc.iter().map(|entry| entry.level).collect();
+        assert_eq!(levels, [2, 3, 2]);
+        assert_eq!(toc[1].title, "Details");
+        assert!(html.contains(r#"<h3 id="details">Details</h3>"#));
+        assert!(html.contains(r#"<h4 id="fine-print">Fine <em>print</em></h4>"#));
+    }
+
+    #[test]
+    fn keeps_existing_ids() {
+        let (html, toc) = build_toc(r#"<h2 id="custom" class="x">Title</h2>"#, 3);
+
+        assert_eq!(toc[0].id, "custom");
+        assert_eq!(html, r#"<h2 id="custom" class="x">Title</h2>"#);
+    }
+
+    #[test]
+    fn slugify_keeps_unicode_letters() {
+        assert_eq!(slugify("Hello, World!"), "hello-world");
+        assert_eq!(slugify("  مقدمه  اول "), "مقدمه-اول");
+    }
+}
@@ -0,0 +1,699 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rayon::prelude::*;
+
+use super::incremental::{content_input_stamps, shared_input_stamps, BuildManifest, InputStamps};
+use super::{
+    check_duplicate_ids, run_item_with_timeout, write_not_found_page, write_output,
+    write_redirect_pages, write_site_manifest, PathFilter,
+};
+use crate::config::Config;
+use crate::content::{find_content_paths, Content, ContentNeighbors};
+use crate::formatted_text::{check_cancelled, limit_concurrent_processes};
+use crate::render::{build_time, ImageProcessor, Renderer};
+
+/// What building one content item did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ItemOutcome {
+    /// The page was written to this path.
+    Built(PathBuf),
+    /// The item is a draft and drafts were not requested.
+    SkippedDraft,
+}
+
+/// Result of [`build_site`]. One item failing does not stop the others, so
+/// every failure is collected here.
+#[derive(Debug, Default)]
+pub struct BuildReport {
+    pub built: Vec<PathBuf>,
+    /// Items whose inputs did not change since their output was written.
+    pub unchanged: Vec<PathBuf>,
+    pub skipped_drafts: Vec<PathBuf>,
+    /// Items left out by [`BuildOptions::filter`].
+    pub excluded: Vec<PathBuf>,
+    pub errors: Vec<(PathBuf, String)>,
+}
+
+/// How [`build_site`] builds.
+#[derive(Debug, Clone, Default)]
+pub struct BuildOptions {
+    pub include_drafts: bool,
+    /// Rebuild every item, even those that look unchanged.
+    pub force: bool,
+    /// Which items to build, by their path relative to `content_dir` (or
+    /// `drafts_dir`).
+    pub filter: PathFilter,
+}
+
+enum PlannedItem {
+    Unchanged(PathBuf),
+    Excluded(PathBuf, bool),
+    Build(PathBuf, Option<InputStamps>),
+}
+
+/// Load, render and write the content item at `path`, copying its images
+/// to the build dir.
+pub fn build_item(
+    path: &Path,
+    renderer: &Renderer,
+    config: &Config,
+    include_drafts: bool,
+) -> Result<ItemOutcome, Box<dyn Error>> {
+    let content = load_content(path, config)?;
+    if !content.metadata().should_build(config, include_drafts) {
+        return Ok(ItemOutcome::SkippedDraft);
+    }
+    let html = render_with_images(path, &content, renderer, config)?;
+
+    let output_path = content.metadata().output_path.clone();
+    write_output(&output_path, &html, config)?;
+    write_redirect_pages(content.metadata(), config)?;
+    Ok(ItemOutcome::Built(output_path))
+}
+
+/// Render the content item at `path` to HTML without writing it or copying
+/// its images. Drafts that would be skipped by [`build_item`] give `None`.
+pub fn render_item(
+    path: &Path,
+    renderer: &Renderer,
+    config: &Config,
+    include_drafts: bool,
+) -> Result<Option<String>, Box<dyn Error>> {
+    let content = load_content(path, config)?;
+    if !content.metadata().should_build(config, include_drafts) {
+        return Ok(None);
+    }
+    render_content(&content, renderer, config).map(Some)
+}
+
+fn load_content(path: &Path, config: &Config) -> Result<Content, Box<dyn Error>> {
+    Content::load(path, config)
+        .map_err(|e| format!("Failed to load content from {}: {e}", path.display()).into())
+}
+
+/// Build every content item under `content_dir` (and `drafts_dir` with
+/// `include_drafts`) in parallel.
+///
+/// At most `max_concurrency` items render at once, and the external
+/// processes they spawn share the same bound. Items whose inputs and the
+/// shared inputs (config, templates, translations, data dir and the metadata
+/// of every item) are unchanged since the last build are skipped unless
+/// `force` is set, as are problems whose `solutions_visible_after` has
+/// passed since the last build. Items outside `filter` are left alone; the
+/// inputs seen and the build time are recorded in a manifest in the build
+/// dir.
+/// Every page now in the build dir is listed in `manifest.json`.
+/// The `not_found_template`, when present, is rendered to `404.html`.
+pub fn build_site(config: &Config, options: BuildOptions) -> Result<BuildReport, Box<dyn Error>> {
+    let mut paths = find_content_paths(&config.content_dir, config);
+    if options.include_drafts {
+        if let Some(drafts_dir) = &config.drafts_dir {
+            paths.extend(find_content_paths(drafts_dir, config));
+        }
+    }
+
+    let previous = BuildManifest::load(config);
+    let shared = shared_input_stamps(&paths, config)?;
+    let now = build_time()?;
+    let planned = paths.into_iter().map(|path| {
+        // An item whose inputs cannot be listed is built, so that the build
+        // reports what is wrong with it.
+        let inputs = content_input_stamps(&path, config).ok();
+        let fresh = inputs
+            .as_ref()
+            .is_some_and(|inputs| previous.is_fresh(&path, inputs, &shared))
+            && !solutions_revealed(&path, &previous, now, config);
+        if !options.filter.matches(relative_content_path(&path, config)) {
+            PlannedItem::Excluded(path, fresh)
+        } else if fresh && !options.force {
+            PlannedItem::Unchanged(path)
+        } else {
+            PlannedItem::Build(path, inputs)
+        }
+    });
+
+    let mut report = BuildReport::default();
+    let mut manifest = BuildManifest::default();
+    let mut to_build = Vec::new();
+    let mut pages = Vec::new();
+    for item in planned {
+        match item {
+            PlannedItem::Unchanged(path) => {
+                manifest.keep(&path, &previous);
+                pages.push(path.clone());
+                report.unchanged.push(path);
+            }
+            // An excluded item's output stays listed while it is current;
+            // otherwise the next build that includes it rebuilds it.
+            PlannedItem::Excluded(path, fresh) => {
+                if fresh {
+                    manifest.keep(&path, &previous);
+                    pages.push(path.clone());
+                }
+                report.excluded.push(path);
+            }
+            PlannedItem::Build(path, inputs) => to_build.push((path, inputs)),
+        }
+    }
+    manifest.set_shared(shared);
+    manifest.set_built_at(now);
+
+    check_duplicate_ids(config)?;
+    let renderer = Arc::new(Renderer::new(config)?);
+    write_not_found_page(&renderer, config)?;
+    let shared_config = Arc::new(config.clone());
+    let timeout = Duration::from_secs(config.render_timeout_seconds);
+    let include_drafts = options.include_drafts;
+    limit_concurrent_processes(config.max_concurrency());
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(config.max_concurrency())
+        .build()?;
+
+    let outcomes: Vec<(PathBuf, Option<InputStamps>, Result<ItemOutcome, String>)> =
+        pool.install(|| {
+            to_build
+                .into_par_iter()
+                .map(|(path, inputs)| {
+                    let renderer = Arc::clone(&renderer);
+                    let config = Arc::clone(&shared_config);
+                    let item_path = path.clone();
+                    let outcome =
+                        run_item_with_timeout(&path.display().to_string(), timeout, move || {
+                            build_item(&item_path, &renderer, &config, include_drafts)
+                                .map_err(|e| e.to_string())
+                        })
+                        .map_err(|e| e.to_string());
+                    (path, inputs, outcome)
+                })
+                .collect()
+        });
+
+    for (path, inputs, outcome) in outcomes {
+        match outcome {
+            Ok(ItemOutcome::Built(output_path)) => {
+                if let Some(inputs) = inputs {
+                    manifest.record(path.clone(), output_path.clone(), inputs);
+                }
+                pages.push(path);
+                report.built.push(output_path);
+            }
+            Ok(ItemOutcome::SkippedDraft) => report.skipped_drafts.push(path),
+            Err(err) => report.errors.push((path, err)),
+        }
+    }
+
+    manifest.save(config)?;
+    write_site_manifest(&pages, config)?;
+    Ok(report)
+}
+
+/// Whether the solutions of the item at `path` became visible since the
+/// last build, which left them out.
+fn solutions_revealed(
+    path: &Path,
+    previous: &BuildManifest,
+    now: DateTime<Utc>,
+    config: &Config,
+) -> bool {
+    Content::load_metadata(path, config)
+        .ok()
+        .and_then(|metadata| metadata.solutions_visible_after)
+        .is_some_and(|visible_after| previous.passed_since_last_build(visible_after, now))
+}
+
+fn relative_content_path<'a>(path: &'a Path, config: &Config) -> &'a Path {
+    path.strip_prefix(&config.content_dir)
+        .ok()
+        .or_else(|| {
+            let drafts_dir = config.drafts_dir.as_ref()?;
+            path.strip_prefix(drafts_dir).ok()
+        })
+        .unwrap_or(path)
+}
+
+fn render_with_images(
+    path: &Path,
+    content: &Content,
+    renderer: &Renderer,
+    config: &Config,
+) -> Result<String, Box<dyn Error>> {
+    let mut html = render_content(content, renderer, config)?;
+    // A timed-out item writes nothing to the build dir.
+    check_cancelled()?;
+    let mut image_processor = ImageProcessor::for_content(path.to_path_buf(), config)?;
+
+    let broken = image_processor.broken_image_references(&html);
+    if !broken.is_empty() {
+        let message = format!(
+            "{} references missing images: {}",
+            path.display(),
+            broken.join(", ")
+        );
+        if config.strict_images {
+            return Err(message.into());
+        }
+        crate::logging::warn(message);
+    }
+
+    if image_processor.has_images() || image_processor.has_assets() {
+        image_processor.copy_images_to_build_dir()?;
+        html = image_processor.update_html_with_image_urls(&html);
+    }
+
+    Ok(html)
+}
+
+fn render_content(
+    content: &Content,
+    renderer: &Renderer,
+    config: &Config,
+) -> Result<String, Box<dyn Error>> {
+    let items = renderer.content_items(config)?;
+    let neighbors = ContentNeighbors::load(content.metadata(), items, config)?;
+    content.render_html_with_neighbors(renderer, config, &neighbors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn builds_items_in_parallel_and_collects_errors() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let build_dir = temp_dir.path().join("build");
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(template_dir.join("page.html"), "{{ page.body | safe }}")?;
+        for i in 0..6 {
+            let page_dir = content_dir.join(format!("page{i}"));
+            fs::create_dir_all(&page_dir)?;
+            fs::write(
+                page_dir.join("metadata.yaml"),
+                format!("title: Page {i}\ntype: page\n"),
+            )?;
+            fs::write(page_dir.join("body.md"), format!("Page *{i}*\n"))?;
+        }
+        fs::create_dir_all(content_dir.join("broken"))?;
+        fs::write(
+            content_dir.join("broken/metadata.yaml"),
+            "title: [unclosed\n",
+        )?;
+        fs::create_dir_all(content_dir.join("draft"))?;
+        fs::write(
+            content_dir.join("draft/metadata.yaml"),
+            "title: Draft\ntype: page\ndraft: true\n",
+        )?;
+        fs::write(content_dir.join("draft/body.md"), "Soon\n")?;
+        let config = Config {
+            content_dir: content_dir.clone(),
+            build_dir: build_dir.clone(),
+            template_dir,
+            max_concurrency: Some(3),
+            ..Default::default()
+        };
+
+        let report = build_site(&config, BuildOptions::default())?;
+
+        assert_eq!(report.built.len(), 6);
+        assert_eq!(report.skipped_drafts, [content_dir.join("draft")]);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].0, content_dir.join("broken"));
+        for i in 0..6 {
+            assert_eq!(
+                fs::read_to_string(build_dir.join(format!("page{i}.html")))?,
+                format!("<p>Page <em>{i}</em></p>\n")
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn rebuilds_only_items_with_changed_inputs() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(template_dir.join("page.html"), "{{ page.body | safe }}")?;
+        for name in ["one", "two"] {
+            let page_dir = content_dir.join(name);
+            fs::create_dir_all(&page_dir)?;
+            fs::write(page_dir.join("metadata.yaml"), "title: Page\ntype: page\n")?;
+            fs::write(page_dir.join("body.md"), "Intro\n\n#include \"part.md\"\n")?;
+            fs::write(page_dir.join("part.md"), "Part\n")?;
+        }
+        let config = Config {
+            content_dir: content_dir.clone(),
+            build_dir: temp_dir.path().join("build"),
+            template_dir,
+            ..Default::default()
+        };
+
+        let first = build_site(&config, BuildOptions::default())?;
+        assert_eq!(first.built.len(), 2);
+
+        let untouched = build_site(&config, BuildOptions::default())?;
+        assert!(untouched.built.is_empty());
+        assert_eq!(untouched.unchanged.len(), 2);
+
+        std::thread::sleep(Duration::from_millis(20));
+        fs::write(content_dir.join("two/part.md"), "Part, edited\n")?;
+        let touched = build_site(&config, BuildOptions::default())?;
+        assert_eq!(touched.built, [config.build_dir.join("two.html")]);
+        assert_eq!(touched.unchanged, [content_dir.join("one")]);
+        assert_eq!(
+            fs::read_to_string(config.build_dir.join("two.html"))?,
+            "<p>Intro</p>\n<p>Part, edited</p>\n"
+        );
+
+        let forced = build_site(
+            &config,
+            BuildOptions {
+                force: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(forced.built.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn rebuilds_items_whose_solutions_became_visible() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(template_dir.join("page.html"), "{{ page.body | safe }}")?;
+        for (name, extra) in [
+            ("contest", "solutions_visible_after: 2020-01-01T00:00:00Z\n"),
+            ("plain", ""),
+        ] {
+            let page_dir = content_dir.join(name);
+            fs::create_dir_all(&page_dir)?;
+            fs::write(
+                page_dir.join("metadata.yaml"),
+                format!("title: Page\ntype: page\n{extra}"),
+            )?;
+            fs::write(page_dir.join("body.md"), "Body\n")?;
+        }
+        let config = Config {
+            content_dir: content_dir.clone(),
+            build_dir: temp_dir.path().join("build"),
+            template_dir,
+            ..Default::default()
+        };
+
+        build_site(&config, BuildOptions::default())?;
+        let untouched = build_site(&config, BuildOptions::default())?;
+        assert_eq!(untouched.unchanged.len(), 2);
+
+        // Pretend the last build ran before the solutions became visible.
+        let manifest_path = config.build_dir.join(".ssg-build-manifest.json");
+        let mut manifest: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&manifest_path)?)?;
+        manifest["built_at"] = serde_json::json!("2019-12-31T00:00:00Z");
+        fs::write(&manifest_path, manifest.to_string())?;
+
+        let revealed = build_site(&config, BuildOptions::default())?;
+        assert_eq!(revealed.built, [config.build_dir.join("contest.html")]);
+        assert_eq!(revealed.unchanged, [content_dir.join("plain")]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn shared_input_changes_rebuild_every_item() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let template_dir = temp_dir.path().join("templates");
+        let data_dir = temp_dir.path().join("data");
+        fs::create_dir_all(&template_dir)?;
+        fs::create_dir_all(&data_dir)?;
+        fs::write(template_dir.join("page.html"), "{{ page.body | safe }}")?;
+        fs::write(data_dir.join("site.yaml"), "name: One\n")?;
+        for name in ["one", "two"] {
+            let page_dir = content_dir.join(name);
+            fs::create_dir_all(&page_dir)?;
+            fs::write(page_dir.join("metadata.yaml"), "title: Page\ntype: page\n")?;
+            fs::write(page_dir.join("body.md"), "Text\n")?;
+        }
+        let mut config = Config {
+            content_dir: content_dir.clone(),
+            build_dir: temp_dir.path().join("build"),
+            template_dir,
+            data_dir: Some(data_dir.clone()),
+            ..Default::default()
+        };
+        build_site(&config, BuildOptions::default())?;
+
+        std::thread::sleep(Duration::from_millis(20));
+        fs::write(data_dir.join("site.yaml"), "name: Two\n")?;
+        assert_eq!(build_site(&config, BuildOptions::default())?.built.len(), 2);
+
+        std::thread::sleep(Duration::from_millis(20));
+        fs::write(
+            content_dir.join("two/metadata.yaml"),
+            "title: Renamed\ntype: page\n",
+        )?;
+        assert_eq!(build_site(&config, BuildOptions::default())?.built.len(), 2);
+
+        config.language = "fa".to_string();
+        assert_eq!(build_site(&config, BuildOptions::default())?.built.len(), 2);
+        assert_eq!(
+            build_site(&config, BuildOptions::default())?
+                .unchanged
+                .len(),
+            2
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn writes_manifest_of_built_pages() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(template_dir.join("page.html"), "{{ page.body | safe }}")?;
+        let post_dir = content_dir.join("blog/post");
+        fs::create_dir_all(&post_dir)?;
+        fs::write(
+            post_dir.join("metadata.yaml"),
+            "title: Post\ntype: page\nid: first-post\ntimestamp: 2025-01-02T00:00:00Z\n",
+        )?;
+        fs::write(post_dir.join("body.md"), "Hello\n")?;
+        let config = Config {
+            content_dir: content_dir.clone(),
+            build_dir: temp_dir.path().join("build"),
+            template_dir,
+            ..Default::default()
+        };
+
+        build_site(&config, BuildOptions::default())?;
+
+        let manifest: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(config.build_dir.join("manifest.json"))?)?;
+        assert_eq!(
+            manifest,
+            serde_json::json!([{
+                "source": post_dir,
+                "id": "first-post",
+                "url": "/blog/post.html",
+                "output_path": "blog/post.html",
+                "timestamp": "2025-01-02T00:00:00Z",
+            }])
+        );
+        assert!(config.build_dir.join("blog/post.html").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn manifest_lists_bare_pages() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(template_dir.join("page.html"), "{{ page.body | safe }}")?;
+        fs::create_dir_all(&content_dir)?;
+        fs::write(content_dir.join("about.md"), "# About\n\nHello\n")?;
+        let config = Config {
+            content_dir: content_dir.clone(),
+            build_dir: temp_dir.path().join("build"),
+            template_dir,
+            ..Default::default()
+        };
+
+        let report = build_site(&config, BuildOptions::default())?;
+
+        assert_eq!(report.built, [config.build_dir.join("about.html")]);
+        let manifest: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(config.build_dir.join("manifest.json"))?)?;
+        assert_eq!(
+            manifest,
+            serde_json::json!([{
+                "source": content_dir.join("about.md"),
+                "id": null,
+                "url": "/about.html",
+                "output_path": "about.html",
+                "timestamp": null,
+            }])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn reports_broken_item_with_its_error() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(template_dir.join("page.html"), "{{ page.body | safe }}")?;
+        fs::create_dir_all(content_dir.join("good"))?;
+        fs::write(
+            content_dir.join("good/metadata.yaml"),
+            "title: Good\ntype: page\n",
+        )?;
+        fs::write(content_dir.join("good/body.md"), "Fine\n")?;
+        fs::create_dir_all(content_dir.join("broken"))?;
+        fs::write(
+            content_dir.join("broken/metadata.yaml"),
+            "title: Broken\ntype: page\n",
+        )?;
+        fs::write(
+            content_dir.join("broken/body.md"),
+            "#include \"missing.md\"\n",
+        )?;
+        let config = Config {
+            content_dir: content_dir.clone(),
+            build_dir: temp_dir.path().join("build"),
+            template_dir,
+            ..Default::default()
+        };
+
+        let report = build_site(&config, BuildOptions::default())?;
+
+        assert_eq!(report.built, [config.build_dir.join("good.html")]);
+        assert_eq!(report.errors.len(), 1);
+        let (path, err) = &report.errors[0];
+        assert_eq!(path, &content_dir.join("broken"));
+        assert!(err.contains("missing.md"), "{err}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn render_item_writes_nothing() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(template_dir.join("page.html"), "{{ page.body | safe }}")?;
+        let page_dir = content_dir.join("page");
+        fs::create_dir_all(&page_dir)?;
+        fs::write(page_dir.join("metadata.yaml"), "title: Page\ntype: page\n")?;
+        fs::write(page_dir.join("body.md"), "Hello ![dot](dot.png)\n")?;
+        fs::write(page_dir.join("dot.png"), b"png")?;
+        let config = Config {
+            content_dir,
+            build_dir: temp_dir.path().join("build"),
+            template_dir,
+            ..Default::default()
+        };
+        let renderer = Renderer::new(&config)?;
+
+        let html = render_item(&page_dir, &renderer, &config, false)?;
+
+        assert_eq!(
+            html.as_deref(),
+            Some("<p>Hello <img src=\"dot.png\" alt=\"dot\" /></p>\n")
+        );
+        assert!(!config.build_dir.exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn include_and_exclude_globs_select_items() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(template_dir.join("page.html"), "{{ page.body | safe }}")?;
+        for name in ["blog/one", "blog/two", "notes/three"] {
+            let page_dir = content_dir.join(name);
+            fs::create_dir_all(&page_dir)?;
+            fs::write(page_dir.join("metadata.yaml"), "title: Page\ntype: page\n")?;
+            fs::write(page_dir.join("body.md"), "Text\n")?;
+        }
+        let config = Config {
+            content_dir: content_dir.clone(),
+            build_dir: temp_dir.path().join("build"),
+            template_dir,
+            ..Default::default()
+        };
+        let build = |include: &[&str], exclude: &[&str]| {
+            let owned = |globs: &[&str]| globs.iter().map(|g| g.to_string()).collect::<Vec<_>>();
+            let options = BuildOptions {
+                force: true,
+                filter: PathFilter::new(&owned(include), &owned(exclude))?,
+                ..Default::default()
+            };
+            build_site(&config, options)
+        };
+
+        let blog = build(&["blog/**"], &[])?;
+        assert_eq!(
+            blog.built,
+            [
+                config.build_dir.join("blog/one.html"),
+                config.build_dir.join("blog/two.html")
+            ]
+        );
+        assert_eq!(blog.excluded, [content_dir.join("notes/three")]);
+        assert!(!config.build_dir.join("notes/three.html").exists());
+
+        let without_two = build(&["blog/**"], &["**/two"])?;
+        assert_eq!(without_two.built, [config.build_dir.join("blog/one.html")]);
+        assert_eq!(without_two.excluded.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn build_item_reports_missing_directory() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&template_dir)?;
+        let config = Config {
+            content_dir: temp_dir.path().join("content"),
+            build_dir: temp_dir.path().join("build"),
+            template_dir,
+            ..Default::default()
+        };
+        let renderer = Renderer::new(&config)?;
+        let missing = config.content_dir.join("missing");
+
+        let err = build_item(&missing, &renderer, &config, false).unwrap_err();
+
+        assert!(
+            err.to_string().starts_with(&format!(
+                "Failed to load content from {}",
+                missing.display()
+            )),
+            "{err}"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+/// Remove `build_dir`, or with `dry_run` only list what would go. Returns the
+/// removed (or removable) files and directories, deepest first.
+///
+/// Refuses to touch a `build_dir` that is empty, the filesystem root, the
+/// project dir itself, anywhere outside `project_dir` or that holds one of the
+/// `source_dirs`, such as the content and template dirs.
+pub fn clean_build_dir(
+    build_dir: &Path,
+    project_dir: &Path,
+    source_dirs: &[&Path],
+    dry_run: bool,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    if build_dir.as_os_str().is_empty() {
+        return Err("Refusing to clean: build_dir is empty".into());
+    }
+    if !build_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let build_dir = build_dir.canonicalize()?;
+    let project_dir = project_dir.canonicalize()?;
+    if build_dir.parent().is_none() {
+        return Err(format!(
+            "Refusing to clean {}: it is the filesystem root",
+            build_dir.display()
+        )
+        .into());
+    }
+    if build_dir == project_dir || !build_dir.starts_with(&project_dir) {
+        return Err(format!(
+            "Refusing to clean {}: it is not inside the project directory {}",
+            build_dir.display(),
+            project_dir.display()
+        )
+        .into());
+    }
+    for source_dir in source_dirs {
+        let Ok(source_dir) = source_dir.canonicalize() else {
+            continue;
+        };
+        if source_dir.starts_with(&build_dir) {
+            return Err(format!(
+                "Refusing to clean {}: it contains {}",
+                build_dir.display(),
+                source_dir.display()
+            )
+            .into());
+        }
+    }
+
+    let mut removed = Vec::new();
+    for entry in WalkDir::new(&build_dir).contents_first(true) {
+        removed.push(entry?.into_path());
+    }
+
+    if !dry_run {
+        fs::remove_dir_all(&build_dir)?;
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn dry_run_lists_without_removing() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let project_dir = temp_dir.path().canonicalize()?;
+        let build_dir = project_dir.join("build");
+        fs::create_dir_all(build_dir.join("blog"))?;
+        fs::write(build_dir.join("blog/post.html"), "post")?;
+
+        let listed = clean_build_dir(&build_dir, &project_dir, &[], true)?;
+
+        assert_eq!(
+            listed,
+            [
+                build_dir.join("blog/post.html"),
+                build_dir.join("blog"),
+                build_dir.clone(),
+            ]
+        );
+        assert!(build_dir.join("blog/post.html").is_file());
+
+        clean_build_dir(&build_dir, &project_dir, &[], false)?;
+        assert!(!build_dir.exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn refuses_dangerous_build_dirs() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let project_dir = temp_dir.path().join("project");
+        let outside_dir = temp_dir.path().join("elsewhere");
+        fs::create_dir_all(&project_dir)?;
+        fs::create_dir_all(&outside_dir)?;
+
+        for build_dir in [
+            PathBuf::new(),
+            PathBuf::from("/"),
+            project_dir.clone(),
+            outside_dir.clone(),
+            project_dir.join(".."),
+        ] {
+            let err = clean_build_dir(&build_dir, &project_dir, &[], false).unwrap_err();
+            assert!(err.to_string().starts_with("Refusing to clean"), "{err}");
+        }
+        assert!(outside_dir.is_dir());
+
+        Ok(())
+    }
+
+    #[test]
+    fn refuses_build_dirs_holding_sources() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let project_dir = temp_dir.path().join("project");
+        let content_dir = project_dir.join("site/content");
+        let template_dir = project_dir.join("templates");
+        fs::create_dir_all(&content_dir)?;
+        fs::create_dir_all(&template_dir)?;
+        let source_dirs = [content_dir.as_path(), template_dir.as_path()];
+
+        for build_dir in [
+            project_dir.join("site"),
+            content_dir.clone(),
+            template_dir.clone(),
+        ] {
+            let err = clean_build_dir(&build_dir, &project_dir, &source_dirs, false).unwrap_err();
+            assert!(err.to_string().starts_with("Refusing to clean"), "{err}");
+        }
+        assert!(content_dir.is_dir());
+        assert!(template_dir.is_dir());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,223 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::content::{find_content_paths, Content, ContentKind};
+
+const CONTENT_INDEX_FILE: &str = "content-index.json";
+const HEADERS_FILE: &str = "_headers";
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ContentIndexEntry {
+    pub kind: ContentKind,
+    pub title: String,
+    pub id: Option<String>,
+    pub url: String,
+    pub tags: Vec<String>,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub word_count: usize,
+    pub draft: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<String>,
+}
+
+/// Describe every content item under `base_path`, drafts included, sorted
+/// by url. Items that fail to load are reported as warnings and skipped.
+pub fn build_content_index(
+    base_path: &Path,
+    config: &Config,
+) -> Result<Vec<ContentIndexEntry>, Box<dyn Error>> {
+    let mut entries = Vec::new();
+
+    for path in find_content_paths(base_path, config) {
+        let content = match Content::load(&path, config) {
+            Ok(content) => content,
+            Err(err) => {
+                crate::logging::warn(format_args!(
+                    "Failed to load content from {}: {}",
+                    path.display(),
+                    err
+                ));
+                continue;
+            }
+        };
+        let word_count = content.word_count();
+        let metadata = content.metadata();
+
+        entries.push(ContentIndexEntry {
+            kind: metadata.kind,
+            title: metadata.title.clone(),
+            id: metadata.id.clone(),
+            url: metadata.url.clone(),
+            tags: metadata.tags.clone().unwrap_or_default(),
+            timestamp: metadata.timestamp,
+            word_count,
+            draft: metadata.is_draft(config),
+            cache_control: metadata.cache_control.clone(),
+        });
+    }
+
+    entries.sort_by(|a, b| a.url.cmp(&b.url));
+    Ok(entries)
+}
+
+/// Describe the items of the content dir and, if set, the drafts dir.
+fn site_content_index(config: &Config) -> Result<Vec<ContentIndexEntry>, Box<dyn Error>> {
+    let mut entries = build_content_index(&config.content_dir, config)?;
+    if let Some(drafts_dir) = &config.drafts_dir {
+        entries.extend(build_content_index(drafts_dir, config)?);
+        entries.sort_by(|a, b| a.url.cmp(&b.url));
+    }
+    Ok(entries)
+}
+
+/// Write `content-index.json` for the whole content tree into the build dir.
+pub fn write_content_index(config: &Config) -> Result<PathBuf, Box<dyn Error>> {
+    let entries = site_content_index(config)?;
+    let output_path = config.build_dir.join(CONTENT_INDEX_FILE);
+
+    fs::create_dir_all(&config.build_dir)?;
+    fs::write(&output_path, serde_json::to_string_pretty(&entries)?)?;
+
+    Ok(output_path)
+}
+
+/// Render a `_headers` file, as read by Netlify or Cloudflare Pages, with a
+/// `Cache-Control` rule for every published entry that declares one.
+pub fn headers_file(entries: &[ContentIndexEntry]) -> String {
+    entries
+        .iter()
+        .filter(|entry| !entry.draft)
+        .filter_map(|entry| {
+            let cache_control = entry.cache_control.as_ref()?;
+            Some(format!(
+                "{}\n  Cache-Control: {}\n",
+                entry.url, cache_control
+            ))
+        })
+        .collect()
+}
+
+/// Write the `_headers` file for the whole content tree into the build dir.
+pub fn write_headers_file(config: &Config) -> Result<PathBuf, Box<dyn Error>> {
+    let entries = build_content_index(&config.content_dir, config)?;
+    let output_path = config.build_dir.join(HEADERS_FILE);
+
+    fs::create_dir_all(&config.build_dir)?;
+    fs::write(&output_path, headers_file(&entries))?;
+
+    Ok(output_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn content_index_lists_every_item() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let post_dir = content_dir.join("blog/post");
+        fs::create_dir_all(&post_dir)?;
+        fs::write(
+            post_dir.join("metadata.yaml"),
+            "title: Post\ntype: blog\ntags: [news]\ntimestamp: 2025-01-02T00:00:00Z\n",
+        )?;
+        fs::write(post_dir.join("body.md"), "One two three *four*.\n")?;
+        fs::write(content_dir.join("about.md"), "# About\n\nHello there.\n")?;
+
+        let config = Config {
+            content_dir: content_dir.clone(),
+            build_dir: temp_dir.path().join("build"),
+            ..Default::default()
+        };
+
+        let output_path = write_content_index(&config)?;
+        let entries: serde_json::Value = serde_json::from_str(&fs::read_to_string(output_path)?)?;
+        let entries = entries.as_array().unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["kind"], "page");
+        assert_eq!(entries[0]["title"], "About");
+        assert_eq!(entries[0]["word_count"], 3);
+        assert_eq!(entries[1]["kind"], "blog");
+        assert_eq!(entries[1]["url"], "/blog/post.html");
+        assert_eq!(entries[1]["tags"], serde_json::json!(["news"]));
+        assert_eq!(entries[1]["timestamp"], "2025-01-02T00:00:00Z");
+        assert_eq!(entries[1]["word_count"], 4);
+        assert_eq!(entries[1]["draft"], false);
+
+        Ok(())
+    }
+
+    #[test]
+    fn content_index_marks_drafts_and_skips_broken_items() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let drafts_dir = temp_dir.path().join("drafts");
+        let marked_dir = content_dir.join("marked");
+        let broken_dir = content_dir.join("broken");
+        fs::create_dir_all(&marked_dir)?;
+        fs::create_dir_all(&broken_dir)?;
+        fs::create_dir_all(&drafts_dir)?;
+        fs::write(
+            marked_dir.join("metadata.yaml"),
+            "title: Marked\ntype: page\ndraft: true\n",
+        )?;
+        fs::write(marked_dir.join("body.md"), "Soon.\n")?;
+        fs::write(broken_dir.join("metadata.yaml"), "title: [unclosed\n")?;
+        fs::write(drafts_dir.join("idea.md"), "# Idea\n")?;
+        fs::write(content_dir.join("about.md"), "# About\n")?;
+
+        let config = Config {
+            content_dir,
+            drafts_dir: Some(drafts_dir),
+            build_dir: temp_dir.path().join("build"),
+            ..Default::default()
+        };
+
+        let entries = site_content_index(&config)?;
+        let drafts: Vec<(&str, bool)> = entries
+            .iter()
+            .map(|entry| (entry.title.as_str(), entry.draft))
+            .collect();
+
+        assert_eq!(drafts, [("About", false), ("Idea", true), ("Marked", true)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cache_control_reaches_index_and_headers() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let page_dir = content_dir.join("news");
+        fs::create_dir_all(&page_dir)?;
+        fs::write(
+            page_dir.join("metadata.yaml"),
+            "title: News\ntype: page\ncache_control: max-age=60\n",
+        )?;
+        fs::write(page_dir.join("body.md"), "Fresh.\n")?;
+        fs::write(content_dir.join("about.md"), "# About\n")?;
+
+        let config = Config {
+            content_dir: content_dir.clone(),
+            build_dir: temp_dir.path().join("build"),
+            ..Default::default()
+        };
+
+        let entries = build_content_index(&content_dir, &config)?;
+        let news = entries.iter().find(|entry| entry.title == "News").unwrap();
+        assert_eq!(news.cache_control.as_deref(), Some("max-age=60"));
+
+        let headers = fs::read_to_string(write_headers_file(&config)?)?;
+        assert_eq!(headers, "/news.html\n  Cache-Control: max-age=60\n");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::content::find_content_files;
+
+/// An `id` used by more than one content item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateId {
+    pub id: String,
+    /// Source paths of the items sharing the id, sorted.
+    pub paths: Vec<PathBuf>,
+}
+
+/// Find every `id` shared by several content items below `base_path`,
+/// sorted by id.
+pub fn find_duplicate_ids(
+    base_path: &Path,
+    config: &Config,
+) -> Result<Vec<DuplicateId>, Box<dyn Error>> {
+    let mut paths_by_id: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for metadata in find_content_files(base_path, None, config)? {
+        if let Some(id) = metadata.id {
+            paths_by_id
+                .entry(id)
+                .or_default()
+                .push(metadata.source_path);
+        }
+    }
+
+    Ok(paths_by_id
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .map(|(id, mut paths)| {
+            paths.sort();
+            DuplicateId { id, paths }
+        })
+        .collect())
+}
+
+/// Warn about duplicated ids in the content dir, or fail when
+/// `strict_ids` is set.
+pub fn check_duplicate_ids(config: &Config) -> Result<(), Box<dyn Error>> {
+    let duplicates = find_duplicate_ids(&config.content_dir, config)?;
+    if duplicates.is_empty() {
+        return Ok(());
+    }
+
+    let messages: Vec<String> = duplicates
+        .iter()
+        .map(|duplicate| {
+            let paths: Vec<String> = duplicate
+                .paths
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect();
+            format!("id `{}` is used by {}", duplicate.id, paths.join(", "))
+        })
+        .collect();
+
+    if config.strict_ids {
+        return Err(format!("Duplicate content ids: {}", messages.join("; ")).into());
+    }
+    for message in messages {
+        crate::logging::warn(format_args!("Duplicate content {}", message));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn reports_directories_sharing_an_id() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        for name in ["first", "second"] {
+            let dir = content_dir.join(name);
+            fs::create_dir_all(&dir)?;
+            fs::write(
+                dir.join("metadata.yaml"),
+                format!("title: {}\ntype: page\nid: dup\n", name),
+            )?;
+        }
+        let unique_dir = content_dir.join("third");
+        fs::create_dir_all(&unique_dir)?;
+        fs::write(
+            unique_dir.join("metadata.yaml"),
+            "title: Third\ntype: page\nid: unique\n",
+        )?;
+        let config = Config {
+            content_dir: content_dir.clone(),
+            build_dir: temp_dir.path().join("build"),
+            ..Default::default()
+        };
+
+        let duplicates = find_duplicate_ids(&content_dir, &config)?;
+
+        assert_eq!(
+            duplicates,
+            vec![DuplicateId {
+                id: "dup".to_string(),
+                paths: vec![content_dir.join("first"), content_dir.join("second")],
+            }]
+        );
+
+        let strict = Config {
+            strict_ids: true,
+            ..config
+        };
+        let err = check_duplicate_ids(&strict).unwrap_err().to_string();
+        assert!(err.contains("id `dup`"), "{}", err);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::list::IndexConfig;
+use crate::config::Config;
+use crate::content::{join_base_url, output_url, ContentMetadata};
+
+const FEED_FILE: &str = "feed.xml";
+
+/// Render an RSS 2.0 feed of `content_items` for the list written to
+/// `output_base_dir`. Links are made absolute with `config.base_url`.
+pub fn rss_feed(
+    index_config: &IndexConfig,
+    content_items: &[ContentMetadata],
+    output_base_dir: &Path,
+    config: &Config,
+) -> Result<String, Box<dyn Error>> {
+    let base_url = config
+        .base_url
+        .as_deref()
+        .ok_or("base_url must be set in the config to write a feed")?;
+    let list_url = output_url(
+        &output_base_dir.join(format!("index.{}", config.output_extension)),
+        config,
+    )?;
+    let title = index_config.title.as_deref().unwrap_or_default();
+
+    let mut feed = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    feed.push_str("<rss version=\"2.0\">\n<channel>\n");
+    feed.push_str(&format!("<title>{}</title>\n", escape_xml(title)));
+    feed.push_str(&format!(
+        "<link>{}</link>\n",
+        escape_xml(&join_base_url(base_url, &list_url, config.trailing_slash))
+    ));
+    feed.push_str(&format!(
+        "<description>{}</description>\n",
+        escape_xml(title)
+    ));
+    feed.push_str(&format!(
+        "<language>{}</language>\n",
+        escape_xml(&config.language)
+    ));
+
+    for item in content_items {
+        let link = join_base_url(base_url, &item.url, config.trailing_slash);
+        feed.push_str("<item>\n");
+        feed.push_str(&format!("<title>{}</title>\n", escape_xml(&item.title)));
+        feed.push_str(&format!("<link>{}</link>\n", escape_xml(&link)));
+        feed.push_str(&format!("<guid>{}</guid>\n", escape_xml(&link)));
+        if let Some(timestamp) = item.timestamp {
+            feed.push_str(&format!("<pubDate>{}</pubDate>\n", timestamp.to_rfc2822()));
+        }
+        if let Some(description) = item.excerpt.as_ref().or(item.description.as_ref()) {
+            feed.push_str(&format!(
+                "<description>{}</description>\n",
+                escape_xml(description)
+            ));
+        }
+        feed.push_str("</item>\n");
+    }
+
+    feed.push_str("</channel>\n</rss>\n");
+    Ok(feed)
+}
+
+/// Write `feed.xml` next to the list's `index.html` and return its path.
+pub fn write_feed(
+    index_config: &IndexConfig,
+    content_items: &[ContentMetadata],
+    output_base_dir: &Path,
+    config: &Config,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let feed = rss_feed(index_config, content_items, output_base_dir, config)?;
+    let output_path = output_base_dir.join(FEED_FILE);
+
+    fs::create_dir_all(output_base_dir)?;
+    fs::write(&output_path, feed)?;
+
+    Ok(output_path)
+}
+
+pub(super) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, TimeZone, Utc};
+
+    #[test]
+    fn feed_lists_items_with_absolute_links_and_rfc822_dates() -> Result<(), Box<dyn Error>> {
+        let config = Config {
+            build_dir: PathBuf::from("build"),
+            base_url: Some("https://example.com/".to_string()),
+            ..Default::default()
+        };
+        let index_config: IndexConfig =
+            serde_yaml::from_str("title: Tom & Jerry's blog\ncontent-type: blog\nfeed: true\n")?;
+        let items: Vec<ContentMetadata> = (1..=2)
+            .map(|day| ContentMetadata {
+                title: format!("Post <{day}>"),
+                url: format!("/blog/post-{day}.html"),
+                timestamp: Some(Utc.with_ymd_and_hms(2025, 3, day, 12, 0, 0).unwrap()),
+                excerpt: Some("A & B".to_string()),
+                ..Default::default()
+            })
+            .collect();
+
+        let feed = rss_feed(&index_config, &items, Path::new("build/blog"), &config)?;
+
+        assert!(
+            feed.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rss version=\"2.0\">")
+        );
+        assert!(feed.ends_with("</channel>\n</rss>\n"));
+        assert_eq!(feed.matches("<item>").count(), 2);
+        assert_eq!(feed.matches("</item>").count(), 2);
+        assert!(feed.contains("<title>Tom &amp; Jerry&apos;s blog</title>"));
+        assert!(feed.contains("<link>https://example.com/blog/index.html</link>"));
+        assert!(feed.contains("<title>Post &lt;1&gt;</title>"));
+        assert!(feed.contains("<link>https://example.com/blog/post-2.html</link>"));
+        assert!(feed.contains("<description>A &amp; B</description>"));
+
+        let pub_date = feed
+            .split("<pubDate>")
+            .nth(1)
+            .and_then(|rest| rest.split("</pubDate>").next())
+            .ok_or("feed has no pubDate")?;
+        assert_eq!(pub_date, "Sat, 1 Mar 2025 12:00:00 +0000");
+        assert!(DateTime::parse_from_rfc2822(pub_date).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn feed_requires_base_url() {
+        let index_config: IndexConfig = serde_yaml::from_str("content-type: blog\n").unwrap();
+
+        let err = rss_feed(&index_config, &[], Path::new("build"), &Config::default()).unwrap_err();
+
+        assert!(err.to_string().contains("base_url"));
+    }
+}
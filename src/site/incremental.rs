@@ -0,0 +1,204 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::config::Config;
+use crate::content::{content_inputs, ORDER_FILE};
+
+const BUILD_MANIFEST_FILE: &str = ".ssg-build-manifest.json";
+const METADATA_FILE: &str = "metadata.yaml";
+const INHERITED_METADATA_FILE: &str = "_index.yaml";
+
+/// Size and modification time of one input file.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct InputStamp {
+    pub size: u64,
+    pub modified_nanos: u64,
+}
+
+/// Stamps of every input of one item, keyed by path.
+pub type InputStamps = BTreeMap<PathBuf, InputStamp>;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct ItemRecord {
+    output_path: PathBuf,
+    inputs: InputStamps,
+}
+
+/// What every item is built from besides its own files: the config, the
+/// templates, translations and data dir, the metadata of every item, which
+/// feeds neighbors, related content and `url_for`, and the build time pinned
+/// by `SOURCE_DATE_EPOCH`. A change to any of them rebuilds everything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SharedInputs {
+    config: u64,
+    files: InputStamps,
+    #[serde(default)]
+    source_date_epoch: Option<String>,
+}
+
+/// Record of what the last batch build produced from which inputs, kept in
+/// the build dir so unchanged content can be skipped next time.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct BuildManifest {
+    shared: SharedInputs,
+    items: BTreeMap<PathBuf, ItemRecord>,
+    /// The build time of the last build, for content that changes with it.
+    #[serde(default)]
+    built_at: Option<DateTime<Utc>>,
+}
+
+impl BuildManifest {
+    /// Load the manifest from the build dir. A missing or unreadable
+    /// manifest is empty, so everything gets rebuilt.
+    pub fn load(config: &Config) -> Self {
+        fs::read_to_string(manifest_path(config))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, config: &Config) -> Result<(), Box<dyn Error>> {
+        fs::create_dir_all(&config.build_dir)?;
+        fs::write(manifest_path(config), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Whether the output of the item at `path` is still there and was built
+    /// from exactly these inputs and shared inputs.
+    pub fn is_fresh(&self, path: &Path, inputs: &InputStamps, shared: &SharedInputs) -> bool {
+        self.shared == *shared
+            && self
+                .items
+                .get(path)
+                .is_some_and(|record| record.inputs == *inputs && record.output_path.is_file())
+    }
+
+    pub fn record(&mut self, path: PathBuf, output_path: PathBuf, inputs: InputStamps) {
+        self.items.insert(
+            path,
+            ItemRecord {
+                output_path,
+                inputs,
+            },
+        );
+    }
+
+    /// Carry the record of an unchanged item over from `previous`.
+    pub fn keep(&mut self, path: &Path, previous: &BuildManifest) {
+        if let Some(record) = previous.items.get(path) {
+            self.items.insert(path.to_path_buf(), record.clone());
+        }
+    }
+
+    pub fn set_shared(&mut self, shared: SharedInputs) {
+        self.shared = shared;
+    }
+
+    pub fn set_built_at(&mut self, built_at: DateTime<Utc>) {
+        self.built_at = Some(built_at);
+    }
+
+    /// Whether `time`, such as an item's `solutions_visible_after`, was
+    /// passed between the last build and `now`, so that an item built
+    /// before it renders differently now.
+    pub fn passed_since_last_build(&self, time: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        time <= now && self.built_at.is_none_or(|built_at| built_at < time)
+    }
+}
+
+/// Stamp every input of the content item at `path`.
+pub fn content_input_stamps(path: &Path, config: &Config) -> Result<InputStamps, Box<dyn Error>> {
+    stamp_files(content_inputs(path, config)?)
+}
+
+/// Hash the config and stamp the files shared by the content items at
+/// `paths`: every file in the template, translations and data dirs, each
+/// item's `metadata.yaml` (or bare page file) and every `_index.yaml` and
+/// `order.yaml` of the content.
+pub fn shared_input_stamps(
+    paths: &[PathBuf],
+    config: &Config,
+) -> Result<SharedInputs, Box<dyn Error>> {
+    let mut files = Vec::new();
+    for dir in std::iter::once(&config.template_dir)
+        .chain(&config.translations_dir)
+        .chain(&config.data_dir)
+    {
+        files.extend(files_in(dir, |_| true)?);
+    }
+
+    for path in paths {
+        if path.is_dir() {
+            files.push(path.join(METADATA_FILE));
+        } else {
+            files.push(path.clone());
+        }
+    }
+    for dir in std::iter::once(&config.content_dir).chain(&config.drafts_dir) {
+        files.extend(files_in(dir, |file| {
+            file.file_name()
+                .is_some_and(|name| name == INHERITED_METADATA_FILE || name == ORDER_FILE)
+        })?);
+    }
+
+    files.sort();
+    files.dedup();
+    Ok(SharedInputs {
+        config: config_hash(config)?,
+        files: stamp_files(files)?,
+        source_date_epoch: std::env::var("SOURCE_DATE_EPOCH").ok(),
+    })
+}
+
+/// Files below `dir` accepted by `keep`; a missing dir has none.
+fn files_in(dir: &Path, keep: impl Fn(&Path) -> bool) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut files = Vec::new();
+    if !dir.exists() {
+        return Ok(files);
+    }
+    for entry in WalkDir::new(dir) {
+        let entry = entry?;
+        if entry.file_type().is_file() && keep(entry.path()) {
+            files.push(entry.into_path());
+        }
+    }
+    Ok(files)
+}
+
+fn config_hash(config: &Config) -> Result<u64, Box<dyn Error>> {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(config)?.hash(&mut hasher);
+    Ok(hasher.finish())
+}
+
+fn stamp_files(files: Vec<PathBuf>) -> Result<InputStamps, Box<dyn Error>> {
+    files
+        .into_iter()
+        .map(|file| {
+            let metadata = fs::metadata(&file)
+                .map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+            let modified_nanos = metadata
+                .modified()?
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_nanos() as u64);
+            let stamp = InputStamp {
+                size: metadata.len(),
+                modified_nanos,
+            };
+            Ok((file, stamp))
+        })
+        .collect()
+}
+
+fn manifest_path(config: &Config) -> PathBuf {
+    config.build_dir.join(BUILD_MANIFEST_FILE)
+}
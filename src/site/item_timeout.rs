@@ -0,0 +1,105 @@
+use std::error::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::formatted_text::cancel_processes_on;
+
+/// Run the build step for one content item on a worker thread and give up on
+/// it once `timeout` has passed.
+///
+/// External tools already have their own timeouts, but an item can still hang
+/// elsewhere (for example while expanding a huge include). When an item
+/// times out its work is cancelled: the external processes it runs are
+/// killed, it starts no new ones and [`build_item`](super::build_item)
+/// writes no output for it, so the worker stops at its next process or
+/// write while the rest of the build goes on.
+pub fn run_item_with_timeout<T, F>(
+    item: &str,
+    timeout: Duration,
+    build: F,
+) -> Result<T, Box<dyn Error>>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let worker_cancelled = Arc::clone(&cancelled);
+    thread::Builder::new()
+        .name(format!("render {}", item))
+        .spawn(move || {
+            cancel_processes_on(worker_cancelled);
+            // The receiver is gone if the item already timed out.
+            let _ = sender.send(build());
+        })?;
+
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result.map_err(Into::into),
+        Err(RecvTimeoutError::Timeout) => {
+            cancelled.store(true, Ordering::SeqCst);
+            Err(format!(
+                "Rendering {} timed out after {} seconds",
+                item,
+                timeout.as_secs_f64()
+            )
+            .into())
+        }
+        Err(RecvTimeoutError::Disconnected) => {
+            Err(format!("Rendering {} stopped unexpectedly", item).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_slow_item_and_keeps_building() {
+        let timeout = Duration::from_millis(50);
+
+        let slow = run_item_with_timeout("slow", timeout, || {
+            thread::sleep(Duration::from_secs(2));
+            Ok("late")
+        });
+        let fast = run_item_with_timeout("fast", timeout, || Ok("done"));
+
+        let err = slow.unwrap_err().to_string();
+        assert!(err.contains("Rendering slow timed out"), "{err}");
+        assert_eq!(fast.unwrap(), "done");
+    }
+
+    #[test]
+    fn cancels_processes_of_timed_out_item() {
+        let (sender, receiver) = mpsc::channel();
+
+        let result = run_item_with_timeout("hung", Duration::from_millis(50), move || {
+            let output = crate::formatted_text::run_with_timeout(
+                "sleep",
+                &["5"],
+                None,
+                Duration::from_secs(10),
+            );
+            let _ = sender.send(output);
+            Ok(())
+        });
+
+        assert!(result.is_err());
+        let output = receiver
+            .recv_timeout(Duration::from_secs(2))
+            .expect("the worker stopped");
+        assert_eq!(output.unwrap_err(), "Cancelled");
+    }
+
+    #[test]
+    fn passes_through_item_errors() {
+        let result: Result<(), _> = run_item_with_timeout("broken", Duration::from_secs(5), || {
+            Err("bad input".to_string())
+        });
+
+        assert_eq!(result.unwrap_err().to_string(), "bad input");
+    }
+}
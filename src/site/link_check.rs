@@ -0,0 +1,167 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use regex::Regex;
+use walkdir::WalkDir;
+
+use super::resolve_request_path;
+use crate::config::Config;
+use crate::content::find_content_files;
+
+static HREF_REGEX: OnceLock<Regex> = OnceLock::new();
+
+fn href_regex() -> &'static Regex {
+    HREF_REGEX.get_or_init(|| {
+        Regex::new(r#"<a\s+[^>]*href=["']([^"']+)["'][^>]*>"#).expect("valid href regex")
+    })
+}
+
+/// A site-relative link that resolves neither to a built file nor to a
+/// content url.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokenLink {
+    /// The built page containing the link.
+    pub page: PathBuf,
+    pub href: String,
+}
+
+/// Check the `<a href>` links of every HTML page in `build_dir`. External
+/// links, fragments and other schemes such as `mailto:` are skipped.
+pub fn check_links(config: &Config) -> Result<Vec<BrokenLink>, Box<dyn Error>> {
+    let content_urls: HashSet<String> = find_content_files(&config.content_dir, None, config)?
+        .into_iter()
+        .map(|metadata| metadata.url)
+        .collect();
+
+    let mut broken = Vec::new();
+    for entry in WalkDir::new(&config.build_dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let page = entry.path();
+        if !page.is_file() || !is_html_file(page) {
+            continue;
+        }
+        let html = fs::read_to_string(page)?;
+        for href in broken_links_in_page(&config.build_dir, page, &html, &content_urls) {
+            broken.push(BrokenLink {
+                page: page.to_path_buf(),
+                href,
+            });
+        }
+    }
+
+    Ok(broken)
+}
+
+fn broken_links_in_page(
+    build_dir: &Path,
+    page: &Path,
+    html: &str,
+    content_urls: &HashSet<String>,
+) -> Vec<String> {
+    href_regex()
+        .captures_iter(html)
+        .map(|caps| caps[1].to_string())
+        .filter(|href| {
+            let Some(url_path) = site_path(build_dir, page, href) else {
+                return false;
+            };
+            !content_urls.contains(&url_path)
+                && resolve_request_path(build_dir, &url_path).is_none()
+        })
+        .collect()
+}
+
+/// The site-relative path `href` points at from `page`, or `None` for
+/// links that are not checked.
+fn site_path(build_dir: &Path, page: &Path, href: &str) -> Option<String> {
+    let href = href.split(['#', '?']).next().unwrap_or_default();
+    if href.is_empty() || href.starts_with("//") || has_scheme(href) {
+        return None;
+    }
+    if href.starts_with('/') {
+        return Some(href.to_string());
+    }
+
+    let page_dir = page.parent()?.strip_prefix(build_dir).ok()?;
+    let mut segments: Vec<String> = page_dir
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    for segment in href.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment.to_string()),
+        }
+    }
+
+    let mut path = format!("/{}", segments.join("/"));
+    if href.ends_with('/') && !path.ends_with('/') {
+        path.push('/');
+    }
+    Some(path)
+}
+
+fn has_scheme(href: &str) -> bool {
+    href.split_once(':').is_some_and(|(scheme, _)| {
+        !scheme.is_empty()
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    })
+}
+
+fn is_html_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("html" | "htm")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn flags_only_dangling_internal_links() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let build_dir = temp_dir.path().join("build");
+        fs::create_dir_all(build_dir.join("blog"))?;
+        fs::write(build_dir.join("about.html"), "About")?;
+        fs::write(
+            build_dir.join("blog/post.html"),
+            concat!(
+                r#"<a href="../about.html#team">About</a>"#,
+                r#"<a href="/missing.html">Missing</a>"#,
+                r#"<a href="https://example.com/nowhere.html">External</a>"#,
+                r##"<a href="#top">Top</a>"##,
+            ),
+        )?;
+        let config = Config {
+            content_dir: temp_dir.path().join("content"),
+            build_dir: build_dir.clone(),
+            ..Default::default()
+        };
+
+        let broken = check_links(&config)?;
+
+        assert_eq!(
+            broken,
+            vec![BrokenLink {
+                page: build_dir.join("blog/post.html"),
+                href: "/missing.html".to_string(),
+            }]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,637 @@
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::write_output;
+use crate::config::Config;
+use crate::content::{output_url, ContentKind, ContentMetadata, SortBy};
+use crate::render::{slugify, Renderer};
+
+const TAGS_DIR: &str = "tags";
+const ARCHIVE_DIR: &str = "archive";
+const UNDATED_DIR: &str = "undated";
+
+fn default_template() -> String {
+    "list.html".to_string()
+}
+
+fn default_tags_template() -> String {
+    "tags.html".to_string()
+}
+
+fn default_archive_template() -> String {
+    "archive.html".to_string()
+}
+
+/// How `ssg-list` groups the listed items into pages.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    /// One list page per tag under `tags/<tag>/` plus a `tags/` overview.
+    Tag,
+    /// One list page per year under `archive/<year>/` and per month under
+    /// `archive/<year>/<month>/`, plus an `archive/` overview.
+    Date,
+}
+
+/// Settings of a list page, read from an `index.yaml`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct IndexConfig {
+    pub title: Option<String>,
+    #[serde(rename = "content-type")]
+    pub content_type: ContentKind,
+    pub path: Option<String>,
+    #[serde(default = "default_template")]
+    pub template: String,
+    /// Split the list into pages of this many items.
+    pub per_page: Option<usize>,
+    /// Only list items carrying at least one of these tags; empty lists all.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Date that orders the list, newest first.
+    #[serde(default)]
+    pub sort_by: SortBy,
+    pub group_by: Option<GroupBy>,
+    /// Template of the tag overview page written with `group_by: tag`.
+    #[serde(default = "default_tags_template")]
+    pub tags_template: String,
+    /// Template of the archive overview page written with `group_by: date`.
+    #[serde(default = "default_archive_template")]
+    pub archive_template: String,
+    /// Also write an RSS feed of the list to `feed.xml`.
+    #[serde(default)]
+    pub feed: bool,
+}
+
+/// A tag and the number of items carrying it, as shown on the overview page.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct TagSummary {
+    pub name: String,
+    pub slug: String,
+    pub url: String,
+    pub count: usize,
+}
+
+/// A year, month or the undated items of the archive, with its item count.
+/// Years list their months, newest first.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ArchiveSummary {
+    pub name: String,
+    pub url: String,
+    pub count: usize,
+    pub months: Vec<ArchiveSummary>,
+}
+
+impl IndexConfig {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let config_content = fs::read_to_string(path)?;
+        Ok(serde_yaml::from_str(&config_content)?)
+    }
+}
+
+/// Keep the items that share a tag with `tags`, or all of them when `tags`
+/// is empty.
+pub fn filter_by_tags(
+    content_items: Vec<ContentMetadata>,
+    tags: &[String],
+) -> Vec<ContentMetadata> {
+    if tags.is_empty() {
+        return content_items;
+    }
+
+    content_items
+        .into_iter()
+        .filter(|item| item.tags.iter().flatten().any(|tag| tags.contains(tag)))
+        .collect()
+}
+
+/// Render the list pages for `content_items` into `output_base_dir` and return
+/// the written paths.
+///
+/// Without `per_page` everything goes to `index.html`; otherwise page `n > 1`
+/// is written to `page/<n>/index.html`.
+pub fn write_list_pages(
+    renderer: &Renderer,
+    index_config: &IndexConfig,
+    content_items: &[ContentMetadata],
+    output_base_dir: &Path,
+    config: &Config,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    write_pages(
+        renderer,
+        index_config,
+        content_items,
+        output_base_dir,
+        HashMap::new(),
+        config,
+    )
+}
+
+/// Write one paginated list per tag to `tags/<slug>/` under
+/// `output_base_dir`, plus an overview page at `tags/index.html` listing every
+/// tag with its item count. Returns the written paths.
+///
+/// Tags whose slugs collide, such as `C` and `C++`, get a numbered slug
+/// (`c-1`) in the order of their names, with a warning.
+pub fn write_tag_pages(
+    renderer: &Renderer,
+    index_config: &IndexConfig,
+    content_items: &[ContentMetadata],
+    output_base_dir: &Path,
+    config: &Config,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let tags_dir = output_base_dir.join(TAGS_DIR);
+    let mut written = Vec::new();
+    let mut summaries = Vec::new();
+    let mut used_slugs: HashMap<String, String> = HashMap::new();
+
+    for (tag, items) in group_by_tag(content_items) {
+        let slug = unique_tag_slug(&tag, &mut used_slugs);
+        let tag_dir = tags_dir.join(&slug);
+        let mut context = HashMap::new();
+        context.insert("tag".to_string(), json!(tag));
+
+        written.extend(write_pages(
+            renderer,
+            index_config,
+            &items,
+            &tag_dir,
+            context,
+            config,
+        )?);
+        summaries.push(TagSummary {
+            url: output_url(
+                &tag_dir.join(format!("index.{}", config.output_extension)),
+                config,
+            )?,
+            name: tag,
+            slug,
+            count: items.len(),
+        });
+    }
+
+    let mut context = HashMap::new();
+    if let Some(title) = &index_config.title {
+        context.insert("title".to_string(), Value::String(title.clone()));
+    }
+    context.insert("tags".to_string(), json!(summaries));
+    let html = renderer.render(&index_config.tags_template, context)?;
+    let overview_path = tags_dir.join(format!("index.{}", config.output_extension));
+    write_output(&overview_path, &html, config)?;
+    written.push(overview_path);
+
+    Ok(written)
+}
+
+/// Write one paginated list per year and per month of the items'
+/// `timestamp` to `archive/<year>/` and `archive/<year>/<month>/` under
+/// `output_base_dir`, items without one to `archive/undated/`, plus an
+/// overview at `archive/index.html`. Returns the written paths.
+pub fn write_date_pages(
+    renderer: &Renderer,
+    index_config: &IndexConfig,
+    content_items: &[ContentMetadata],
+    output_base_dir: &Path,
+    config: &Config,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let archive_dir = output_base_dir.join(ARCHIVE_DIR);
+    let (by_month, undated) = group_by_date(content_items);
+    let mut written = Vec::new();
+    let mut years = Vec::new();
+
+    for (year, months) in by_month.into_iter().rev() {
+        let year_dir = archive_dir.join(year.to_string());
+        let mut month_summaries = Vec::new();
+        let mut year_items = Vec::new();
+
+        for (month, items) in months.into_iter().rev() {
+            let month = format!("{:02}", month);
+            let mut context = HashMap::new();
+            context.insert("year".to_string(), json!(year));
+            context.insert("month".to_string(), json!(month));
+            let month_dir = year_dir.join(&month);
+            written.extend(write_pages(
+                renderer,
+                index_config,
+                &items,
+                &month_dir,
+                context,
+                config,
+            )?);
+            month_summaries.push(ArchiveSummary {
+                url: index_url(&month_dir, config)?,
+                name: month,
+                count: items.len(),
+                months: Vec::new(),
+            });
+            year_items.extend(items);
+        }
+
+        let mut context = HashMap::new();
+        context.insert("year".to_string(), json!(year));
+        written.extend(write_pages(
+            renderer,
+            index_config,
+            &year_items,
+            &year_dir,
+            context,
+            config,
+        )?);
+        years.push(ArchiveSummary {
+            name: year.to_string(),
+            url: index_url(&year_dir, config)?,
+            count: year_items.len(),
+            months: month_summaries,
+        });
+    }
+
+    let undated = if undated.is_empty() {
+        None
+    } else {
+        let undated_dir = archive_dir.join(UNDATED_DIR);
+        let mut context = HashMap::new();
+        context.insert("undated".to_string(), json!(true));
+        written.extend(write_pages(
+            renderer,
+            index_config,
+            &undated,
+            &undated_dir,
+            context,
+            config,
+        )?);
+        Some(ArchiveSummary {
+            name: UNDATED_DIR.to_string(),
+            url: index_url(&undated_dir, config)?,
+            count: undated.len(),
+            months: Vec::new(),
+        })
+    };
+
+    let mut context = HashMap::new();
+    if let Some(title) = &index_config.title {
+        context.insert("title".to_string(), Value::String(title.clone()));
+    }
+    context.insert("years".to_string(), json!(years));
+    context.insert("undated".to_string(), json!(undated));
+    let html = renderer.render(&index_config.archive_template, context)?;
+    let overview_path = archive_dir.join(format!("index.{}", config.output_extension));
+    write_output(&overview_path, &html, config)?;
+    written.push(overview_path);
+
+    Ok(written)
+}
+
+type ItemsByMonth = BTreeMap<i32, BTreeMap<u32, Vec<ContentMetadata>>>;
+
+/// Bucket items by the year and month of their `timestamp`, keeping their
+/// order inside each bucket, and return the undated ones separately.
+fn group_by_date(content_items: &[ContentMetadata]) -> (ItemsByMonth, Vec<ContentMetadata>) {
+    let mut groups = ItemsByMonth::new();
+    let mut undated = Vec::new();
+    for item in content_items {
+        match item.timestamp {
+            Some(timestamp) => groups
+                .entry(timestamp.year())
+                .or_default()
+                .entry(timestamp.month())
+                .or_default()
+                .push(item.clone()),
+            None => undated.push(item.clone()),
+        }
+    }
+    (groups, undated)
+}
+
+fn index_url(dir: &Path, config: &Config) -> Result<String, Box<dyn Error>> {
+    output_url(
+        &dir.join(format!("index.{}", config.output_extension)),
+        config,
+    )
+}
+
+fn unique_tag_slug(tag: &str, used_slugs: &mut HashMap<String, String>) -> String {
+    let base = slugify(tag);
+    let base = if base.is_empty() { "tag" } else { &base };
+    let mut slug = base.to_string();
+    let mut suffix = 1;
+    while used_slugs.contains_key(&slug) {
+        slug = format!("{base}-{suffix}");
+        suffix += 1;
+    }
+    if let Some(other) = used_slugs.get(base) {
+        crate::logging::warn(format!(
+            "Tags `{other}` and `{tag}` share the slug `{base}`; `{tag}` is listed under `{slug}`"
+        ));
+    }
+    used_slugs.insert(slug.clone(), tag.to_string());
+    slug
+}
+
+fn group_by_tag(content_items: &[ContentMetadata]) -> BTreeMap<String, Vec<ContentMetadata>> {
+    let mut groups: BTreeMap<String, Vec<ContentMetadata>> = BTreeMap::new();
+    for item in content_items {
+        for tag in item.tags.iter().flatten() {
+            groups.entry(tag.clone()).or_default().push(item.clone());
+        }
+    }
+    groups
+}
+
+fn write_pages(
+    renderer: &Renderer,
+    index_config: &IndexConfig,
+    content_items: &[ContentMetadata],
+    output_base_dir: &Path,
+    extra_context: HashMap<String, Value>,
+    config: &Config,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let pages = paginate(content_items, index_config.per_page);
+    let total_pages = pages.len();
+    let mut written = Vec::with_capacity(total_pages);
+
+    for (index, items) in pages.into_iter().enumerate() {
+        let current_page = index + 1;
+        let output_dir = page_dir(output_base_dir, current_page);
+        let mut context = extra_context.clone();
+        context.insert("current_page".to_string(), json!(current_page));
+        context.insert("total_pages".to_string(), json!(total_pages));
+        let html = render_list(renderer, index_config, items, context)?;
+
+        let output_path = output_dir.join(format!("index.{}", config.output_extension));
+        write_output(&output_path, &html, config)?;
+        written.push(output_path);
+    }
+
+    Ok(written)
+}
+
+fn paginate(content_items: &[ContentMetadata], per_page: Option<usize>) -> Vec<&[ContentMetadata]> {
+    match per_page {
+        Some(per_page) if per_page > 0 && !content_items.is_empty() => {
+            content_items.chunks(per_page).collect()
+        }
+        _ => vec![content_items],
+    }
+}
+
+fn page_dir(output_base_dir: &Path, page: usize) -> PathBuf {
+    if page == 1 {
+        output_base_dir.to_path_buf()
+    } else {
+        output_base_dir.join("page").join(page.to_string())
+    }
+}
+
+fn render_list(
+    renderer: &Renderer,
+    index_config: &IndexConfig,
+    content_items: &[ContentMetadata],
+    mut context: HashMap<String, Value>,
+) -> Result<String, Box<dyn Error>> {
+    if let Some(title) = &index_config.title {
+        context.insert("title".to_string(), Value::String(title.clone()));
+    }
+
+    context.insert("content_items".to_string(), json!(content_items));
+
+    renderer.render(&index_config.template, context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn per_page_splits_list_into_pages() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        let output_dir = temp_dir.path().join("build/blog");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(
+            template_dir.join("list.html"),
+            "{{ current_page }}/{{ total_pages }}: {{ content_items | length }}",
+        )?;
+        let config = Config {
+            template_dir,
+            ..Default::default()
+        };
+        let index_config: IndexConfig = serde_yaml::from_str("content-type: blog\nper_page: 10\n")?;
+        let items: Vec<ContentMetadata> = (0..25)
+            .map(|i| ContentMetadata {
+                title: format!("Post {i}"),
+                ..Default::default()
+            })
+            .collect();
+
+        let renderer = Renderer::new(&config)?;
+        let written = write_list_pages(&renderer, &index_config, &items, &output_dir, &config)?;
+
+        assert_eq!(
+            written,
+            [
+                output_dir.join("index.html"),
+                output_dir.join("page/2/index.html"),
+                output_dir.join("page/3/index.html"),
+            ]
+        );
+        assert_eq!(fs::read_to_string(&written[0])?, "1/3: 10");
+        assert_eq!(fs::read_to_string(&written[2])?, "3/3: 5");
+
+        Ok(())
+    }
+
+    #[test]
+    fn list_items_expose_author_and_authors() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        let output_dir = temp_dir.path().join("build/blog");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(
+            template_dir.join("list.html"),
+            "{% for item in content_items %}{{ item.author }} ({{ item.authors | join(sep=\", \") }});{% endfor %}",
+        )?;
+        let config = Config {
+            template_dir,
+            ..Default::default()
+        };
+        let index_config: IndexConfig = serde_yaml::from_str("content-type: blog\n")?;
+        let items = vec![
+            serde_yaml::from_str::<ContentMetadata>("title: One\ntype: blog\nauthor: Ann\n")?,
+            serde_yaml::from_str::<ContentMetadata>(
+                "title: Two\ntype: blog\nauthors: [Bob, Cy]\n",
+            )?,
+        ];
+
+        let renderer = Renderer::new(&config)?;
+        let written = write_list_pages(&renderer, &index_config, &items, &output_dir, &config)?;
+
+        assert_eq!(fs::read_to_string(&written[0])?, "Ann (Ann);Bob (Bob, Cy);");
+
+        Ok(())
+    }
+
+    #[test]
+    fn tag_filter_keeps_matching_items() {
+        let items: Vec<ContentMetadata> = [
+            ("Rust post", Some(vec!["rust", "code"])),
+            ("Travel post", Some(vec!["travel"])),
+            ("Untagged post", None),
+        ]
+        .into_iter()
+        .map(|(title, tags)| ContentMetadata {
+            title: title.to_string(),
+            tags: tags.map(|tags| tags.into_iter().map(String::from).collect()),
+            ..Default::default()
+        })
+        .collect();
+
+        let filtered = filter_by_tags(items, &["rust".to_string()]);
+
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].title, "Rust post");
+        assert_eq!(filter_by_tags(Vec::new(), &[]).len(), 0);
+    }
+
+    #[test]
+    fn group_by_tag_writes_page_per_tag() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        let build_dir = temp_dir.path().join("build");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(
+            template_dir.join("list.html"),
+            "{{ tag }}: {{ content_items | length }}",
+        )?;
+        fs::write(
+            template_dir.join("tags.html"),
+            "{% for tag in tags %}{{ tag.name }}={{ tag.count }} {{ tag.url | safe }};{% endfor %}",
+        )?;
+        let config = Config {
+            template_dir,
+            build_dir: build_dir.clone(),
+            ..Default::default()
+        };
+        let index_config: IndexConfig =
+            serde_yaml::from_str("content-type: blog\ngroup_by: tag\n")?;
+        let items: Vec<ContentMetadata> = [
+            vec!["Rust Tips"],
+            vec!["Rust Tips", "travel"],
+            vec!["travel"],
+        ]
+        .into_iter()
+        .map(|tags| ContentMetadata {
+            tags: Some(tags.into_iter().map(String::from).collect()),
+            ..Default::default()
+        })
+        .collect();
+
+        let renderer = Renderer::new(&config)?;
+        let written = write_tag_pages(&renderer, &index_config, &items, &build_dir, &config)?;
+
+        assert_eq!(written.len(), 3);
+        assert_eq!(
+            fs::read_to_string(build_dir.join("tags/rust-tips/index.html"))?,
+            "Rust Tips: 2"
+        );
+        assert_eq!(
+            fs::read_to_string(build_dir.join("tags/travel/index.html"))?,
+            "travel: 2"
+        );
+        assert_eq!(
+            fs::read_to_string(build_dir.join("tags/index.html"))?,
+            "Rust Tips=2 /tags/rust-tips/index.html;travel=2 /tags/travel/index.html;"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn colliding_tag_slugs_are_numbered() {
+        let mut used_slugs = HashMap::new();
+
+        let slugs: Vec<String> = ["C", "C++", "Rust", "rust", "++"]
+            .into_iter()
+            .map(|tag| unique_tag_slug(tag, &mut used_slugs))
+            .collect();
+
+        assert_eq!(slugs, ["c", "c-1", "rust", "rust-1", "tag"]);
+    }
+
+    #[test]
+    fn without_per_page_writes_single_index() {
+        let items: Vec<ContentMetadata> = (0..3).map(|_| ContentMetadata::default()).collect();
+
+        let pages = paginate(&items, None);
+
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].len(), 3);
+        assert_eq!(paginate(&[], Some(10)).len(), 1);
+    }
+
+    #[test]
+    fn group_by_date_writes_page_per_year_and_month() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        let build_dir = temp_dir.path().join("build");
+        fs::create_dir_all(&template_dir)?;
+        fs::write(
+            template_dir.join("list.html"),
+            "{{ year | default(value='-') }}/{{ month | default(value='-') }}: {{ content_items | length }}",
+        )?;
+        fs::write(
+            template_dir.join("archive.html"),
+            "{% for year in years %}{{ year.name }}={{ year.count }}\
+             {% for month in year.months %} {{ month.name }}={{ month.count }}{% endfor %};\
+             {% endfor %}undated={{ undated.count }}",
+        )?;
+        let config = Config {
+            template_dir,
+            build_dir: build_dir.clone(),
+            ..Default::default()
+        };
+        let index_config: IndexConfig =
+            serde_yaml::from_str("content-type: blog\ngroup_by: date\n")?;
+        let items: Vec<ContentMetadata> = [
+            Some("2024-03-20"),
+            Some("2024-03-02"),
+            Some("2024-02-11"),
+            None,
+        ]
+        .into_iter()
+        .map(|date| ContentMetadata {
+            timestamp: date.map(|date| crate::content::parse_date(date).unwrap()),
+            ..Default::default()
+        })
+        .collect();
+
+        let renderer = Renderer::new(&config)?;
+        let written = write_date_pages(&renderer, &index_config, &items, &build_dir, &config)?;
+
+        assert_eq!(
+            written,
+            [
+                build_dir.join("archive/2024/03/index.html"),
+                build_dir.join("archive/2024/02/index.html"),
+                build_dir.join("archive/2024/index.html"),
+                build_dir.join("archive/undated/index.html"),
+                build_dir.join("archive/index.html"),
+            ]
+        );
+        assert_eq!(fs::read_to_string(&written[0])?, "2024/03: 2");
+        assert_eq!(fs::read_to_string(&written[1])?, "2024/02: 1");
+        assert_eq!(fs::read_to_string(&written[2])?, "2024/-: 3");
+        assert_eq!(fs::read_to_string(&written[3])?, "-/-: 1");
+        assert_eq!(
+            fs::read_to_string(&written[4])?,
+            "2024=3 03=2 02=1;undated=1"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+mod build;
+mod clean;
+mod content_index;
+mod duplicate_ids;
+mod feed;
+mod incremental;
+mod item_timeout;
+mod link_check;
+mod list;
+mod not_found;
+mod output;
+mod path_filter;
+mod pdf;
+mod redirects;
+mod search_index;
+mod serve;
+mod site_manifest;
+mod sitemap;
+
+pub use build::{build_item, build_site, render_item, BuildOptions, BuildReport, ItemOutcome};
+pub use clean::clean_build_dir;
+pub use content_index::{
+    build_content_index, headers_file, write_content_index, write_headers_file, ContentIndexEntry,
+};
+pub use duplicate_ids::{check_duplicate_ids, find_duplicate_ids, DuplicateId};
+pub use feed::{rss_feed, write_feed};
+pub use incremental::{
+    content_input_stamps, shared_input_stamps, BuildManifest, InputStamp, InputStamps, SharedInputs,
+};
+pub use item_timeout::run_item_with_timeout;
+pub use link_check::{check_links, BrokenLink};
+pub use list::{
+    filter_by_tags, write_date_pages, write_list_pages, write_tag_pages, ArchiveSummary, GroupBy,
+    IndexConfig, TagSummary,
+};
+pub use not_found::write_not_found_page;
+pub use output::{generated_by_comment, write_output};
+pub use path_filter::PathFilter;
+pub use pdf::{export_pdf, printable_html};
+pub use redirects::{
+    build_redirects, redirect_page, redirects_file, write_redirect_pages, write_redirects_file,
+    Redirect,
+};
+pub use search_index::{build_search_index, write_search_index, SearchIndexEntry};
+pub use serve::{content_type, resolve_request_path, DevServer, ServeOptions};
+pub use site_manifest::{write_site_manifest, SiteManifestEntry};
+pub use sitemap::{build_sitemap, sitemap_file, write_sitemap_file, SitemapEntry};
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::PathBuf;
+
+use super::write_output;
+use crate::config::Config;
+use crate::render::Renderer;
+
+const NOT_FOUND_PAGE: &str = "404.html";
+
+/// Render `not_found_template` with the default context to `404.html` in the
+/// build dir. Returns the written path, or `None` when there is no such
+/// template.
+pub fn write_not_found_page(
+    renderer: &Renderer,
+    config: &Config,
+) -> Result<Option<PathBuf>, Box<dyn Error>> {
+    if !renderer.has_template(&config.not_found_template) {
+        return Ok(None);
+    }
+
+    let html = renderer.render(&config.not_found_template, HashMap::new())?;
+    let output_path = config.build_dir.join(NOT_FOUND_PAGE);
+    write_output(&output_path, &html, config)?;
+    Ok(Some(output_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn renders_the_not_found_template_when_present() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let template_dir = temp_dir.path().join("templates");
+        fs::create_dir_all(&template_dir)?;
+        let config = Config {
+            template_dir: template_dir.clone(),
+            build_dir: temp_dir.path().join("build"),
+            language: "fa".to_string(),
+            ..Default::default()
+        };
+
+        let renderer = Renderer::new(&config)?;
+        assert_eq!(write_not_found_page(&renderer, &config)?, None);
+
+        fs::write(
+            template_dir.join("404.html"),
+            "<html lang=\"{{ language }}\">Not found</html>",
+        )?;
+        let renderer = Renderer::new(&config)?;
+        let written = write_not_found_page(&renderer, &config)?;
+
+        assert_eq!(written, Some(config.build_dir.join("404.html")));
+        assert_eq!(
+            fs::read_to_string(config.build_dir.join("404.html"))?,
+            "<html lang=\"fa\">Not found</html>"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use crate::config::Config;
+
+/// Comment prepended to generated files when `generated_by_comment` is on.
+pub fn generated_by_comment() -> String {
+    format!("<!-- generated by ssg v{} -->\n", env!("CARGO_PKG_VERSION"))
+}
+
+/// Write a rendered page, creating its parent directories and adding the
+/// "generated by" comment when configured.
+pub fn write_output(path: &Path, html: &str, config: &Config) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    if config.generated_by_comment {
+        fs::write(path, generated_by_comment() + html)?;
+    } else {
+        fs::write(path, html)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn generated_by_comment_follows_config() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let path = temp_dir.path().join("out/page.html");
+
+        let enabled = Config {
+            generated_by_comment: true,
+            ..Default::default()
+        };
+        write_output(&path, "<p>Hi</p>", &enabled)?;
+        let html = fs::read_to_string(&path)?;
+        assert!(html.starts_with("<!-- generated by ssg v"));
+        assert!(html.ends_with("-->\n<p>Hi</p>"));
+
+        write_output(&path, "<p>Hi</p>", &Config::default())?;
+        assert_eq!(fs::read_to_string(&path)?, "<p>Hi</p>");
+
+        Ok(())
+    }
+}
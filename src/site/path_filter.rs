@@ -0,0 +1,105 @@
+use std::error::Error;
+use std::path::Path;
+
+use regex::Regex;
+
+/// Selects content by glob patterns on its path relative to the content
+/// dir. `*` and `?` stay within one path component, `**` spans any number
+/// of them, so `blog/**` matches `blog` and everything below it.
+///
+/// With no include patterns everything is included; excludes win over
+/// includes.
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    include: Vec<Regex>,
+    exclude: Vec<Regex>,
+}
+
+impl PathFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            include: compile_globs(include)?,
+            exclude: compile_globs(exclude)?,
+        })
+    }
+
+    pub fn matches(&self, relative_path: &Path) -> bool {
+        let path = relative_path.to_string_lossy().replace('\\', "/");
+        let matches_any = |globs: &[Regex]| globs.iter().any(|glob| glob.is_match(&path));
+
+        (self.include.is_empty() || matches_any(&self.include)) && !matches_any(&self.exclude)
+    }
+}
+
+fn compile_globs(patterns: &[String]) -> Result<Vec<Regex>, Box<dyn Error>> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            Regex::new(&glob_to_regex(pattern))
+                .map_err(|e| format!("Invalid glob pattern {pattern:?}: {e}").into())
+        })
+        .collect()
+}
+
+fn glob_to_regex(pattern: &str) -> String {
+    let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+    let mut regex = String::from("^");
+    let mut rest = pattern;
+
+    while let Some(ch) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("**/") {
+            regex.push_str("(?:.*/)?");
+            rest = after;
+        } else if rest == "/**" {
+            regex.push_str("(?:/.*)?");
+            rest = "";
+        } else if let Some(after) = rest.strip_prefix("**") {
+            regex.push_str(".*");
+            rest = after;
+        } else {
+            match ch {
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                ch => regex.push_str(&regex::escape(&ch.to_string())),
+            }
+            rest = &rest[ch.len_utf8()..];
+        }
+    }
+
+    regex.push('$');
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(include: &[&str], exclude: &[&str]) -> PathFilter {
+        let owned = |patterns: &[&str]| patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        PathFilter::new(&owned(include), &owned(exclude)).unwrap()
+    }
+
+    #[test]
+    fn globs_match_within_and_across_components() {
+        let blog = filter(&["blog/**"], &[]);
+        assert!(blog.matches(Path::new("blog")));
+        assert!(blog.matches(Path::new("blog/2025/post")));
+        assert!(!blog.matches(Path::new("blogroll")));
+        assert!(!blog.matches(Path::new("en/blog/post")));
+
+        let posts = filter(&["**/post?", "*.md"], &[]);
+        assert!(posts.matches(Path::new("en/blog/post1")));
+        assert!(posts.matches(Path::new("post2")));
+        assert!(posts.matches(Path::new("about.md")));
+        assert!(!posts.matches(Path::new("en/about.md")));
+    }
+
+    #[test]
+    fn excludes_win_over_includes() {
+        let filter = filter(&["blog/**"], &["blog/drafts/**"]);
+
+        assert!(filter.matches(Path::new("blog/post")));
+        assert!(!filter.matches(Path::new("blog/drafts/idea")));
+        assert!(PathFilter::default().matches(Path::new("anything")));
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+use std::time::Duration;
+
+use serde_json::Value;
+use tera::escape_html;
+
+use crate::config::Config;
+use crate::content::Content;
+use crate::formatted_text::{run_with_timeout, ConfigFingerprint};
+use crate::render::Translations;
+
+/// PDF engines are much slower than HTML conversion.
+const PDF_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Render the content item at `path` to a PDF at `output` with pandoc.
+///
+/// Images keep the paths the content uses, and pandoc resolves them against
+/// the content's directory rather than the site's `static/assets` urls.
+pub fn export_pdf(path: &Path, output: &Path, config: &Config) -> Result<(), Box<dyn Error>> {
+    let content = Content::load(path, config)
+        .map_err(|e| format!("Failed to load content from {}: {e}", path.display()))?;
+    let html = crate::logging::with_current_item(path, || printable_html(&content, config))?;
+    let resource_dir = if path.is_dir() {
+        path
+    } else {
+        path.parent().unwrap_or(Path::new("."))
+    };
+
+    run_with_timeout(
+        &config.pandoc_bin,
+        &[
+            "--from=html",
+            "--standalone",
+            "--metadata",
+            &format!("title={}", content.metadata().title),
+            &format!("--resource-path={}", resource_dir.display()),
+            "--output",
+            &output.to_string_lossy(),
+        ],
+        Some(&html),
+        PDF_TIMEOUT,
+    )?;
+
+    Ok(())
+}
+
+/// The HTML printed for `content`: a problem's statement followed by its
+/// hints and solutions, or the body of a page or blog post. The "Hint {n}"
+/// and "Solution {n}" headings go through the site's translations.
+pub fn printable_html(content: &Content, config: &Config) -> Result<String, Box<dyn Error>> {
+    let config = &*content.metadata().render_config(config);
+    match content {
+        Content::Problem {
+            statement,
+            solutions,
+            hints,
+            ..
+        } => {
+            let translations = Translations::load(config)?;
+            let fingerprint = ConfigFingerprint::new(config);
+            let mut html = statement.to_html_keyed(config, fingerprint)?;
+            for (i, hint) in hints.iter().enumerate() {
+                let args = HashMap::from([("n".to_string(), Value::from(i + 1))]);
+                let label = translations.translate("Hint {n}", &args);
+                html.push_str(&format!("\n<h2>{}</h2>\n", escape_html(&label)));
+                html.push_str(&hint.to_html_keyed(config, fingerprint)?);
+            }
+            for (i, solution) in solutions.iter().enumerate() {
+                let label = solution.label(i, &translations);
+                html.push_str(&format!("\n<h2>{}</h2>\n", escape_html(&label)));
+                html.push_str(&solution.text.to_html_keyed(config, fingerprint)?);
+            }
+            Ok(html)
+        }
+        Content::Blog { body, .. } | Content::Page { body, .. } => Ok(body.to_html(config)?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content::{ContentMetadata, Solution};
+    use crate::formatted_text::FormattedText;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn installed(program: &str) -> bool {
+        std::process::Command::new(program)
+            .arg("--version")
+            .output()
+            .is_ok()
+    }
+
+    #[test]
+    fn problems_print_statement_hints_and_solutions() -> Result<(), Box<dyn Error>> {
+        let content = Content::Problem {
+            metadata: ContentMetadata::default(),
+            statement: FormattedText::Html("<p>Add.</p>".to_string()),
+            solutions: vec![Solution {
+                title: None,
+                text: FormattedText::Html("<p>2</p>".to_string()),
+            }],
+            hints: vec![FormattedText::Html("<p>Count.</p>".to_string())],
+        };
+
+        assert_eq!(
+            printable_html(&content, &Config::default())?,
+            "<p>Add.</p>\n<h2>Hint 1</h2>\n<p>Count.</p>\n<h2>Solution 1</h2>\n<p>2</p>"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn headings_are_translated_and_escaped() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let translations_dir = temp_dir.path().join("translations");
+        fs::create_dir_all(&translations_dir)?;
+        fs::write(
+            translations_dir.join("fr.csv"),
+            "Hint {n},Indice {n}\nSolution {n},Solution n° {n}\n",
+        )?;
+        let config = Config {
+            language: "fr".to_string(),
+            translations_dir: Some(translations_dir),
+            ..Default::default()
+        };
+        let content = Content::Problem {
+            metadata: ContentMetadata::default(),
+            statement: FormattedText::Html("<p>Add.</p>".to_string()),
+            solutions: vec![
+                Solution {
+                    title: Some("<i>Greedy</i> & co".to_string()),
+                    text: FormattedText::Html("<p>2</p>".to_string()),
+                },
+                Solution {
+                    title: None,
+                    text: FormattedText::Html("<p>3</p>".to_string()),
+                },
+            ],
+            hints: vec![FormattedText::Html("<p>Count.</p>".to_string())],
+        };
+
+        assert_eq!(
+            printable_html(&content, &config)?,
+            concat!(
+                "<p>Add.</p>\n<h2>Indice 1</h2>\n<p>Count.</p>\n",
+                "<h2>&lt;i&gt;Greedy&lt;&#x2F;i&gt; &amp; co</h2>\n<p>2</p>\n",
+                "<h2>Solution n° 2</h2>\n<p>3</p>",
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn exports_a_page_to_pdf() -> Result<(), Box<dyn Error>> {
+        if !installed("pandoc") || !installed("pdflatex") {
+            eprintln!("skipping: pandoc or pdflatex is not installed");
+            return Ok(());
+        }
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let page_dir = content_dir.join("sheet");
+        fs::create_dir_all(&page_dir)?;
+        fs::write(page_dir.join("metadata.yaml"), "title: Sheet\ntype: page\n")?;
+        fs::write(
+            page_dir.join("body.md"),
+            "# Worksheet\n\nSolve *all* of them.\n",
+        )?;
+        let config = Config {
+            content_dir,
+            build_dir: temp_dir.path().join("build"),
+            ..Default::default()
+        };
+        let output = temp_dir.path().join("sheet.pdf");
+
+        export_pdf(&page_dir, &output, &config)?;
+
+        assert!(fs::read(&output)?.starts_with(b"%PDF"));
+
+        Ok(())
+    }
+}
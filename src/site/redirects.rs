@@ -0,0 +1,187 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use super::write_output;
+use crate::config::Config;
+use crate::content::{find_content_files, ContentMetadata};
+
+const REDIRECTS_FILE: &str = "_redirects";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirect {
+    pub from: String,
+    pub to: String,
+    pub status: u16,
+}
+
+/// Collect a redirect to each item's url from every path in its
+/// `redirect_from` (or `aliases`) metadata, sorted by source path.
+pub fn build_redirects(base_path: &Path, config: &Config) -> Result<Vec<Redirect>, Box<dyn Error>> {
+    let mut redirects: Vec<Redirect> = find_content_files(base_path, None, config)?
+        .into_iter()
+        .flat_map(|metadata| {
+            let to = metadata.url;
+            metadata
+                .redirect_from
+                .into_iter()
+                .map(move |from| Redirect {
+                    from: normalize_redirect_path(&from),
+                    to: to.clone(),
+                    status: config.redirect_status,
+                })
+        })
+        .collect();
+
+    redirects.sort_by(|a, b| a.from.cmp(&b.from));
+    Ok(redirects)
+}
+
+/// Render redirects in the Netlify/Cloudflare Pages `_redirects` format.
+pub fn redirects_file(redirects: &[Redirect]) -> String {
+    redirects
+        .iter()
+        .map(|redirect| format!("{} {} {}\n", redirect.from, redirect.to, redirect.status))
+        .collect()
+}
+
+/// Write `_redirects` for the whole content tree into the build dir.
+pub fn write_redirects_file(config: &Config) -> Result<PathBuf, Box<dyn Error>> {
+    let redirects = build_redirects(&config.content_dir, config)?;
+    let output_path = config.build_dir.join(REDIRECTS_FILE);
+
+    fs::create_dir_all(&config.build_dir)?;
+    fs::write(&output_path, redirects_file(&redirects))?;
+
+    Ok(output_path)
+}
+
+/// A page sending browsers on to `url` with a `<meta http-equiv="refresh">`.
+pub fn redirect_page(url: &str) -> String {
+    let url = url.replace('&', "&amp;").replace('"', "&quot;");
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta http-equiv=\"refresh\" content=\"0; url={url}\">\n\
+         <link rel=\"canonical\" href=\"{url}\">\n</head>\n\
+         <body><a href=\"{url}\">{url}</a></body>\n</html>\n"
+    )
+}
+
+/// Write a [`redirect_page`] to the item's url at each site path in its
+/// `redirect_from` (or `aliases`/`redirects`) metadata, so old links keep
+/// working on hosts without `_redirects` support. Paths ending in `/` or
+/// without an extension get an `index` page. Returns the written paths.
+pub fn write_redirect_pages(
+    metadata: &ContentMetadata,
+    config: &Config,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let html = redirect_page(&metadata.url);
+    let mut written = Vec::new();
+
+    for from in &metadata.redirect_from {
+        if from.contains("://") {
+            continue;
+        }
+        let output_path = redirect_output_path(from, config)?;
+        if output_path == metadata.output_path {
+            continue;
+        }
+        write_output(&output_path, &html, config)?;
+        written.push(output_path);
+    }
+
+    Ok(written)
+}
+
+fn redirect_output_path(from: &str, config: &Config) -> Result<PathBuf, Box<dyn Error>> {
+    let relative = Path::new(from.trim_start_matches('/'));
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return Err(format!("Invalid redirect path: {}", from).into());
+    }
+
+    if from.ends_with('/') || relative.extension().is_none() {
+        Ok(config
+            .build_dir
+            .join(relative)
+            .join(format!("index.{}", config.output_extension)))
+    } else {
+        Ok(config.build_dir.join(relative))
+    }
+}
+
+fn normalize_redirect_path(path: &str) -> String {
+    if path.starts_with('/') || path.contains("://") {
+        path.to_string()
+    } else {
+        format!("/{}", path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn redirect_from_entries_point_at_content_url() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let post_dir = content_dir.join("blog/new-name");
+        fs::create_dir_all(&post_dir)?;
+        fs::write(
+            post_dir.join("metadata.yaml"),
+            "title: Post\ntype: blog\nredirect_from: [/blog/old-name.html, 2019/post.html]\n",
+        )?;
+        let config = Config {
+            content_dir: content_dir.clone(),
+            build_dir: temp_dir.path().join("build"),
+            redirect_status: 302,
+            ..Default::default()
+        };
+
+        let output = fs::read_to_string(write_redirects_file(&config)?)?;
+
+        assert_eq!(
+            output,
+            "/2019/post.html /blog/new-name.html 302\n/blog/old-name.html /blog/new-name.html 302\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn redirect_pages_point_old_paths_at_new_url() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let build_dir = temp_dir.path().join("build");
+        let config = Config {
+            build_dir: build_dir.clone(),
+            ..Default::default()
+        };
+        let metadata: ContentMetadata = serde_yaml::from_str(
+            "title: Post\ntype: blog\nredirects: [/blog/old-name.html, /2019/post/]\n",
+        )?;
+        let metadata = ContentMetadata {
+            url: "/blog/new-name.html".to_string(),
+            ..metadata
+        };
+
+        let written = write_redirect_pages(&metadata, &config)?;
+
+        assert_eq!(
+            written,
+            [
+                build_dir.join("blog/old-name.html"),
+                build_dir.join("2019/post/index.html"),
+            ]
+        );
+        let html = fs::read_to_string(&written[0])?;
+        assert!(
+            html.contains(r#"<meta http-equiv="refresh" content="0; url=/blog/new-name.html">"#)
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::content::{find_content_files, plain_text};
+
+const SEARCH_INDEX_FILE: &str = "search-index.json";
+
+/// One searchable document, in the shape client-side search libraries such
+/// as Lunr or FlexSearch index.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct SearchIndexEntry {
+    /// The item's `id`, or its url when it has none.
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub tags: Vec<String>,
+    /// Body as plain text, cut near `search_text_length` characters.
+    pub text: String,
+}
+
+/// Describe every published content item under `base_path`, sorted by url.
+pub fn build_search_index(
+    base_path: &Path,
+    config: &Config,
+) -> Result<Vec<SearchIndexEntry>, Box<dyn Error>> {
+    let mut entries: Vec<SearchIndexEntry> = find_content_files(base_path, None, config)?
+        .into_iter()
+        .map(|metadata| {
+            let text = plain_text(&metadata, config, config.search_text_length).unwrap_or_default();
+            SearchIndexEntry {
+                id: metadata.id.unwrap_or_else(|| metadata.url.clone()),
+                title: metadata.title,
+                url: metadata.url,
+                tags: metadata.tags.unwrap_or_default(),
+                text,
+            }
+        })
+        .collect();
+
+    entries.sort_by(|a, b| a.url.cmp(&b.url));
+    Ok(entries)
+}
+
+/// Write `search-index.json` for the whole content tree into the build dir.
+pub fn write_search_index(config: &Config) -> Result<PathBuf, Box<dyn Error>> {
+    let entries = build_search_index(&config.content_dir, config)?;
+    let output_path = config.build_dir.join(SEARCH_INDEX_FILE);
+
+    fs::create_dir_all(&config.build_dir)?;
+    fs::write(&output_path, serde_json::to_string(&entries)?)?;
+
+    Ok(output_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use tempfile::tempdir;
+
+    #[test]
+    fn search_index_holds_plain_text_of_each_item() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        let post_dir = content_dir.join("blog/post");
+        fs::create_dir_all(&post_dir)?;
+        fs::write(
+            post_dir.join("metadata.yaml"),
+            "title: Post\ntype: blog\nid: post-1\ntags: [rust]\n",
+        )?;
+        fs::write(
+            post_dir.join("body.html"),
+            "<p>Some <strong>bold</strong> words and a much longer tail</p>",
+        )?;
+        fs::create_dir_all(content_dir.join("about"))?;
+        fs::write(
+            content_dir.join("about/metadata.yaml"),
+            "title: About\ntype: page\n",
+        )?;
+        fs::write(content_dir.join("about/body.md"), "Hello <em>there</em>\n")?;
+        let config = Config {
+            content_dir: content_dir.clone(),
+            build_dir: temp_dir.path().join("build"),
+            search_text_length: 20,
+            ..Default::default()
+        };
+
+        let index: Value =
+            serde_json::from_str(&fs::read_to_string(write_search_index(&config)?)?)?;
+
+        assert_eq!(
+            index,
+            json!([
+                {"id": "/about.html", "title": "About", "url": "/about.html", "tags": [], "text": "Hello there"},
+                {"id": "post-1", "title": "Post", "url": "/blog/post.html", "tags": ["rust"], "text": "Some bold words and…"},
+            ])
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,294 @@
+use std::error::Error;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use tiny_http::{Header, Request, Response, Server};
+use walkdir::WalkDir;
+
+use crate::config::Config;
+
+/// Polled by the injected live-reload script; answers with [`build_version`].
+const LIVE_RELOAD_PATH: &str = "/__ssg/version";
+
+const LIVE_RELOAD_SCRIPT: &str = r#"<script>
+(function () {
+  var version = null;
+  setInterval(function () {
+    fetch("/__ssg/version").then(function (r) { return r.text(); }).then(function (v) {
+      if (version !== null && v !== version) { location.reload(); }
+      version = v;
+    }).catch(function () {});
+  }, 1000);
+})();
+</script>"#;
+
+/// How `ssg-serve` serves the build dir.
+#[derive(Debug, Clone)]
+pub struct ServeOptions {
+    pub port: u16,
+    /// Page served with status 404, relative to the build dir.
+    pub not_found_page: PathBuf,
+    /// Inject a script into HTML pages that reloads them after a rebuild.
+    pub live_reload: bool,
+}
+
+impl Default for ServeOptions {
+    fn default() -> Self {
+        Self {
+            port: 8000,
+            not_found_page: PathBuf::from("404.html"),
+            live_reload: true,
+        }
+    }
+}
+
+/// Development HTTP server for previewing the build dir.
+pub struct DevServer {
+    server: Server,
+    build_dir: PathBuf,
+    options: ServeOptions,
+}
+
+impl DevServer {
+    /// Listen on `127.0.0.1:<port>`; port 0 picks a free port.
+    pub fn bind(config: &Config, options: ServeOptions) -> Result<Self, Box<dyn Error>> {
+        let server = Server::http(("127.0.0.1", options.port))
+            .map_err(|e| format!("Failed to listen on port {}: {}", options.port, e))?;
+        Ok(Self {
+            server,
+            build_dir: config.build_dir.clone(),
+            options,
+        })
+    }
+
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.server.server_addr().to_ip()
+    }
+
+    /// Serve requests until the process is stopped.
+    pub fn run(&self) {
+        for request in self.server.incoming_requests() {
+            if let Err(err) = self.respond(request) {
+                eprintln!("Error answering request: {}", err);
+            }
+        }
+    }
+
+    fn respond(&self, request: Request) -> Result<(), Box<dyn Error>> {
+        let url_path = request
+            .url()
+            .split(['?', '#'])
+            .next()
+            .unwrap_or("/")
+            .to_string();
+
+        if self.options.live_reload && url_path == LIVE_RELOAD_PATH {
+            let version = build_version(&self.build_dir).to_string();
+            request.respond(with_content_type(
+                Response::from_string(version),
+                "text/plain; charset=utf-8",
+            ))?;
+            return Ok(());
+        }
+
+        let (status, path) = match resolve_request_path(&self.build_dir, &url_path) {
+            Some(path) => (200, path),
+            None => (404, self.build_dir.join(&self.options.not_found_page)),
+        };
+        let body = match fs::read(&path) {
+            Ok(body) => body,
+            Err(_) => {
+                request.respond(Response::from_string("404 Not Found").with_status_code(404))?;
+                return Ok(());
+            }
+        };
+
+        let content_type = content_type(&path);
+        let body = if self.options.live_reload && content_type.starts_with("text/html") {
+            inject_live_reload(&String::from_utf8_lossy(&body)).into_bytes()
+        } else {
+            body
+        };
+        request.respond(with_content_type(
+            Response::from_data(body).with_status_code(status),
+            content_type,
+        ))?;
+        Ok(())
+    }
+}
+
+fn with_content_type<R: std::io::Read>(response: Response<R>, content_type: &str) -> Response<R> {
+    match Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes()) {
+        Ok(header) => response.with_header(header),
+        Err(()) => response,
+    }
+}
+
+/// The file in `build_dir` that `url_path` names, with directories served
+/// by their `index.html`. `None` when there is no such file or the path
+/// tries to leave the build dir.
+pub fn resolve_request_path(build_dir: &Path, url_path: &str) -> Option<PathBuf> {
+    let decoded = percent_decode(url_path)?;
+    let relative = Path::new(decoded.trim_start_matches('/'));
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return None;
+    }
+
+    let path = build_dir.join(relative);
+    let path = if path.is_dir() {
+        path.join("index.html")
+    } else {
+        path
+    };
+    path.is_file().then_some(path)
+}
+
+fn percent_decode(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = text.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// MIME type for a file served from the build dir.
+pub fn content_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("html" | "htm") => "text/html; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("json") => "application/json",
+        Some("xml") => "application/xml",
+        Some("txt") => "text/plain; charset=utf-8",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("ico") => "image/x-icon",
+        Some("pdf") => "application/pdf",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        _ => "application/octet-stream",
+    }
+}
+
+fn inject_live_reload(html: &str) -> String {
+    match html.rfind("</body>") {
+        Some(index) => format!(
+            "{}{}\n{}",
+            &html[..index],
+            LIVE_RELOAD_SCRIPT,
+            &html[index..]
+        ),
+        None => format!("{}\n{}", html, LIVE_RELOAD_SCRIPT),
+    }
+}
+
+/// Latest modification time in the build dir, which changes whenever a
+/// rebuild writes a file.
+fn build_version(build_dir: &Path) -> u128 {
+    WalkDir::new(build_dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok()?.metadata().ok()?.modified().ok())
+        .filter_map(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_nanos())
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use tempfile::tempdir;
+
+    fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn serves_built_pages_and_not_found_page() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let build_dir = temp_dir.path().join("build");
+        fs::create_dir_all(build_dir.join("blog"))?;
+        fs::write(
+            build_dir.join("blog/index.html"),
+            "<body><p>Blog</p></body>",
+        )?;
+        fs::write(build_dir.join("404.html"), "Nothing here")?;
+        let config = Config {
+            build_dir,
+            ..Default::default()
+        };
+        let options = ServeOptions {
+            port: 0,
+            live_reload: false,
+            ..Default::default()
+        };
+        let server = DevServer::bind(&config, options)?;
+        let addr = server.local_addr().ok_or("server has no address")?;
+        std::thread::spawn(move || server.run());
+
+        let page = get(addr, "/blog/");
+        assert!(page.starts_with("HTTP/1.1 200"), "{page}");
+        assert!(
+            page.contains("Content-Type: text/html; charset=utf-8"),
+            "{page}"
+        );
+        assert!(page.ends_with("<body><p>Blog</p></body>"), "{page}");
+
+        let missing = get(addr, "/missing.html");
+        assert!(missing.starts_with("HTTP/1.1 404"), "{missing}");
+        assert!(missing.ends_with("Nothing here"), "{missing}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn request_paths_stay_inside_build_dir() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(temp_dir.path().join("سلام.html"), "hi").unwrap();
+
+        assert_eq!(
+            resolve_request_path(temp_dir.path(), "/%D8%B3%D9%84%D8%A7%D9%85.html"),
+            Some(temp_dir.path().join("سلام.html"))
+        );
+        assert_eq!(resolve_request_path(temp_dir.path(), "/../secret"), None);
+    }
+
+    #[test]
+    fn live_reload_script_goes_before_body_end() {
+        let html = inject_live_reload("<body>Hi</body>");
+
+        assert!(html.starts_with("<body>Hi<script>"));
+        assert!(html.ends_with("</script>\n</body>"));
+    }
+}
@@ -0,0 +1,61 @@
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::content::{Content, ContentMetadata};
+
+const SITE_MANIFEST_FILE: &str = "manifest.json";
+
+/// One page of the built site, as listed in `manifest.json`.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct SiteManifestEntry {
+    /// The content dir or file the page was built from.
+    pub source: PathBuf,
+    pub id: Option<String>,
+    pub url: String,
+    /// Where the page was written, relative to the build dir.
+    pub output_path: PathBuf,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+impl SiteManifestEntry {
+    pub fn new(metadata: &ContentMetadata, config: &Config) -> Self {
+        let output_path = metadata
+            .output_path
+            .strip_prefix(&config.build_dir)
+            .unwrap_or(&metadata.output_path)
+            .to_path_buf();
+        Self {
+            source: metadata.source_path.clone(),
+            id: metadata.id.clone(),
+            url: metadata.url.clone(),
+            output_path,
+            timestamp: metadata.timestamp,
+        }
+    }
+}
+
+/// Write `manifest.json` into the build dir, listing the pages built from
+/// the content at `sources`, sorted by url.
+pub fn write_site_manifest(
+    sources: &[PathBuf],
+    config: &Config,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let mut entries = sources
+        .iter()
+        .map(|source| {
+            let metadata = Content::load_metadata(source, config)?;
+            Ok(SiteManifestEntry::new(&metadata, config))
+        })
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    entries.sort_by(|a, b| a.url.cmp(&b.url));
+
+    let output_path = config.build_dir.join(SITE_MANIFEST_FILE);
+    fs::create_dir_all(&config.build_dir)?;
+    fs::write(&output_path, serde_json::to_string_pretty(&entries)?)?;
+    Ok(output_path)
+}
@@ -0,0 +1,120 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+
+use super::feed::escape_xml;
+use crate::config::Config;
+use crate::content::{find_content_files, join_base_url};
+
+const SITEMAP_FILE: &str = "sitemap.xml";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SitemapEntry {
+    pub loc: String,
+    pub lastmod: Option<DateTime<Utc>>,
+}
+
+/// Collect an absolute url for every published content item under
+/// `base_path`, sorted by url. Drafts are left out.
+pub fn build_sitemap(
+    base_path: &Path,
+    config: &Config,
+) -> Result<Vec<SitemapEntry>, Box<dyn Error>> {
+    let base_url = config
+        .base_url
+        .as_deref()
+        .ok_or("base_url must be set in the config to write a sitemap")?;
+
+    let mut entries: Vec<SitemapEntry> = find_content_files(base_path, None, config)?
+        .into_iter()
+        .map(|metadata| SitemapEntry {
+            loc: join_base_url(base_url, &metadata.url, config.trailing_slash),
+            lastmod: metadata.updated.or(metadata.timestamp),
+        })
+        .collect();
+
+    entries.sort_by(|a, b| a.loc.cmp(&b.loc));
+    Ok(entries)
+}
+
+/// Render entries in the sitemaps.org XML format.
+pub fn sitemap_file(entries: &[SitemapEntry]) -> String {
+    let mut sitemap = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    sitemap.push_str("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+
+    for entry in entries {
+        sitemap.push_str("<url>\n");
+        sitemap.push_str(&format!("<loc>{}</loc>\n", escape_xml(&entry.loc)));
+        if let Some(lastmod) = entry.lastmod {
+            sitemap.push_str(&format!(
+                "<lastmod>{}</lastmod>\n",
+                lastmod.format("%Y-%m-%d")
+            ));
+        }
+        sitemap.push_str("</url>\n");
+    }
+
+    sitemap.push_str("</urlset>\n");
+    sitemap
+}
+
+/// Write `sitemap.xml` for the whole content tree into the build dir.
+pub fn write_sitemap_file(config: &Config) -> Result<PathBuf, Box<dyn Error>> {
+    let entries = build_sitemap(&config.content_dir, config)?;
+    let output_path = config.build_dir.join(SITEMAP_FILE);
+
+    fs::create_dir_all(&config.build_dir)?;
+    fs::write(&output_path, sitemap_file(&entries))?;
+
+    Ok(output_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn sitemap_lists_published_pages_with_lastmod() -> Result<(), Box<dyn Error>> {
+        let temp_dir = tempdir()?;
+        let content_dir = temp_dir.path().join("content");
+        fs::create_dir_all(content_dir.join("blog/first"))?;
+        fs::create_dir_all(content_dir.join("blog/second"))?;
+        fs::create_dir_all(content_dir.join("blog/unfinished"))?;
+        fs::write(content_dir.join("about.md"), "Hello\n")?;
+        fs::write(
+            content_dir.join("blog/first/metadata.yaml"),
+            "title: First\ntype: blog\ntimestamp: 2025-01-02\n",
+        )?;
+        fs::write(
+            content_dir.join("blog/second/metadata.yaml"),
+            "title: Second\ntype: blog\ntimestamp: 2025-02-01\nupdated: 2025-03-04T10:00:00Z\n",
+        )?;
+        fs::write(
+            content_dir.join("blog/unfinished/metadata.yaml"),
+            "title: Unfinished\ntype: blog\ndraft: true\n",
+        )?;
+        let config = Config {
+            content_dir: content_dir.clone(),
+            build_dir: temp_dir.path().join("build"),
+            base_url: Some("https://example.com/".to_string()),
+            ..Default::default()
+        };
+
+        let sitemap = fs::read_to_string(write_sitemap_file(&config)?)?;
+
+        assert_eq!(
+            sitemap,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n\
+             <url>\n<loc>https://example.com/about.html</loc>\n</url>\n\
+             <url>\n<loc>https://example.com/blog/first.html</loc>\n<lastmod>2025-01-02</lastmod>\n</url>\n\
+             <url>\n<loc>https://example.com/blog/second.html</loc>\n<lastmod>2025-03-04</lastmod>\n</url>\n\
+             </urlset>\n"
+        );
+
+        Ok(())
+    }
+}